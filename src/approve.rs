@@ -0,0 +1,201 @@
+use std::time::Duration;
+
+use ethers::abi::{encode, Token};
+use ethers::prelude::{Address, BlockNumber, Bytes, LocalWallet, Middleware, Signer, H256, U256};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::types::TransactionRequest;
+
+use crate::{
+    build_tx, confirm_batch, retry_on_timeout, sign_tx, Config, InkError, MintContext, TxStatus,
+};
+
+/// approve(address,uint256) 的函数选择器
+pub const APPROVE_SELECTOR: [u8; 4] = [0x09, 0x5e, 0xa7, 0xb3];
+/// allowance(address,address) 的函数选择器
+pub const ALLOWANCE_SELECTOR: [u8; 4] = [0xdd, 0x62, 0xed, 0x3e];
+
+/// 开打前的 ERC-20 授权
+#[derive(Debug, Clone, PartialEq)]
+pub struct Approve {
+    pub token: Address,
+    pub spender: Address,
+    /// 代币最小单位的数量
+    pub amount: U256,
+}
+
+fn calldata(selector: [u8; 4], tokens: &[Token]) -> Bytes {
+    let mut data = selector.to_vec();
+    data.extend(encode(tokens));
+    Bytes::from(data)
+}
+
+/// approve(spender, amount) 的 calldata
+pub fn approve_calldata(spender: Address, amount: U256) -> Bytes {
+    calldata(
+        APPROVE_SELECTOR,
+        &[Token::Address(spender), Token::Uint(amount)],
+    )
+}
+
+/// allowance(owner, spender) 的 calldata
+pub fn allowance_calldata(owner: Address, spender: Address) -> Bytes {
+    calldata(
+        ALLOWANCE_SELECTOR,
+        &[Token::Address(owner), Token::Address(spender)],
+    )
+}
+
+/// 解析 allowance 的返回值(一个 uint256)
+pub fn decode_allowance(output: &[u8]) -> Result<U256, InkError> {
+    if output.len() < 32 {
+        return Err(InkError::Rpc(format!(
+            "allowance 返回值长度不正确: 0x{}",
+            ethers::utils::hex::encode(output)
+        )));
+    }
+    Ok(U256::from_big_endian(&output[..32]))
+}
+
+/// 授权数量, 代币最小单位的整数 或 max(uint256 最大值)
+pub fn parse_approve_amount(text: &str) -> Result<U256, InkError> {
+    let text = text.trim();
+    if text.eq_ignore_ascii_case("max") {
+        return Ok(U256::MAX);
+    }
+    U256::from_dec_str(text)
+        .map_err(|e| InkError::Config(format!("approve_amount 不合法: {}: {}", text, e)))
+}
+
+/// 确保 wallet 给 spender 的授权不少于 amount, 不足时发送 approve 并等待上链
+///
+/// 已有足额授权时返回 None, 否则返回 approve 交易的哈希
+pub async fn ensure_approved(
+    ctx: &MintContext,
+    config: &Config,
+    wallet: &LocalWallet,
+    approve: &Approve,
+) -> Result<Option<H256>, InkError> {
+    let from = wallet.address();
+    let timeout = config.rpc_timeout();
+    let retries = config.max_retries;
+
+    let query: TypedTransaction = TransactionRequest::new()
+        .to(approve.token)
+        .data(allowance_calldata(from, approve.spender))
+        .into();
+    let output = retry_on_timeout(timeout, retries, "查询 allowance", || {
+        ctx.provider.call(&query, None)
+    })
+    .await?;
+    if decode_allowance(&output)? >= approve.amount {
+        return Ok(None);
+    }
+
+    let nonce = retry_on_timeout(timeout, retries, "get_transaction_count", || {
+        ctx.provider
+            .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+    })
+    .await?;
+    let mut gas_price = config.init_gas_price()?;
+    gas_price.value = U256::zero();
+    let mut tx = build_tx(
+        from,
+        ctx.chain_id.as_u64(),
+        approve.token,
+        &gas_price,
+        nonce,
+        approve_calldata(approve.spender, approve.amount),
+    );
+    let gas = retry_on_timeout(timeout, retries, "估算 approve gas", || {
+        ctx.provider.estimate_gas(&tx, None)
+    })
+    .await?;
+    tx.set_gas(gas);
+    let raw_tx = sign_tx(wallet, &tx)?;
+    let tx_hash = retry_on_timeout(timeout, retries, "发送 approve", || {
+        ctx.sender.send_batch(std::slice::from_ref(&raw_tx))
+    })
+    .await?
+    .pop()
+    .unwrap_or_else(|| Err("节点没有返回响应".to_string()))
+    .map_err(|e| InkError::Send(format!("approve 发送失败: {}", e)))?;
+
+    let statuses = confirm_batch(
+        &ctx.provider,
+        &[tx_hash],
+        Duration::from_secs(config.receipt_timeout_secs),
+        timeout,
+        retries,
+    )
+    .await?;
+    match &statuses[0] {
+        TxStatus::Confirmed(receipt) if receipt.status.is_some_and(|s| s.as_u64() == 1) => {
+            Ok(Some(tx_hash))
+        }
+        TxStatus::Confirmed(_) => Err(InkError::Send(format!(
+            "approve 交易执行失败: {:?}",
+            tx_hash
+        ))),
+        _ => Err(InkError::Send(format!(
+            "approve 交易 {} 秒内没有上链: {:?}",
+            config.receipt_timeout_secs, tx_hash
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::utils::{hex, id};
+
+    #[test]
+    fn selectors() {
+        assert_eq!(APPROVE_SELECTOR, id("approve(address,uint256)"));
+        assert_eq!(ALLOWANCE_SELECTOR, id("allowance(address,address)"));
+    }
+
+    #[test]
+    fn approve_calldata_encoding() {
+        let spender: Address = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+            .parse()
+            .unwrap();
+        let data = approve_calldata(spender, U256::from(1000));
+        assert_eq!(
+            hex::encode(&data),
+            concat!(
+                "095ea7b3",
+                "0000000000000000000000007e5f4552091a69125d5dfcb7b8c2659029395bdf",
+                "00000000000000000000000000000000000000000000000000000000000003e8",
+            )
+        );
+        let data = approve_calldata(spender, U256::MAX);
+        assert_eq!(data.len(), 4 + 64);
+        assert!(data[36..].iter().all(|byte| *byte == 0xff));
+
+        let owner = Address::from_low_u64_be(1);
+        assert_eq!(
+            hex::encode(allowance_calldata(owner, spender)),
+            concat!(
+                "dd62ed3e",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000007e5f4552091a69125d5dfcb7b8c2659029395bdf",
+            )
+        );
+    }
+
+    #[test]
+    fn allowance_and_amount() {
+        let mut output = [0u8; 32];
+        output[31] = 0x10;
+        assert_eq!(decode_allowance(&output).unwrap(), U256::from(16));
+        assert!(decode_allowance(&[]).is_err());
+
+        assert_eq!(parse_approve_amount(" max ").unwrap(), U256::MAX);
+        assert_eq!(
+            parse_approve_amount("1000000000000000000").unwrap(),
+            U256::exp10(18)
+        );
+        assert!(parse_approve_amount("1.5").is_err());
+        assert!(parse_approve_amount("-1").is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use ethers::prelude::{Address, Bytes, LocalWallet, Signer, H256, U256};
+use ethers::types::transaction::eip2718::TypedTransaction;
+
+use crate::{
+    build_tx, confirm_batch, retry_on_timeout, sign_tx, Config, GasPrice, InkError, MintContext,
+    TxStatus, TRANSFER_GAS_LIMIT,
+};
+
+/// 替换一个 nonce 的结果
+#[derive(Debug, Clone)]
+pub struct Cancel {
+    pub nonce: U256,
+    /// 替换交易上链时为交易哈希, 失败时为原因
+    pub result: Result<H256, String>,
+}
+
+/// 取消 nonce 的替换交易: 转给自己、value 为 0、data 为空
+///
+/// 节点只接受 gas 比原交易高(通常至少 10%)的替换, 所以 gas_price 要按卡住的交易设高一些
+pub fn cancel_tx(
+    from: Address,
+    chain_id: u64,
+    gas_price: &GasPrice,
+    nonce: U256,
+) -> TypedTransaction {
+    let gas_price = GasPrice {
+        value: U256::zero(),
+        ..gas_price.clone()
+    };
+    let mut tx = build_tx(from, chain_id, from, &gas_price, nonce, Bytes::new());
+    tx.set_gas(TRANSFER_GAS_LIMIT);
+    tx
+}
+
+/// 对 from_nonce..=to_nonce 的每个 nonce 发送一笔替换交易, 按 batch_size 分批发送并等待上链
+///
+/// gas 使用 max_fee_per_gas / max_priority_fee_per_gas 配置
+pub async fn cancel_nonces(
+    ctx: &MintContext,
+    config: &Config,
+    from_nonce: u64,
+    to_nonce: u64,
+) -> Result<Vec<Cancel>, InkError> {
+    if from_nonce > to_nonce {
+        return Err(InkError::Config(format!(
+            "from_nonce({}) 不能大于 to_nonce({})",
+            from_nonce, to_nonce
+        )));
+    }
+    let wallet = config
+        .private_key
+        .parse::<LocalWallet>()
+        .map_err(|e| InkError::Wallet(e.to_string()))?
+        .with_chain_id(ctx.chain_id.as_u64());
+    let from = wallet.address();
+    let timeout = config.rpc_timeout();
+    let retries = config.max_retries;
+    let gas_price = config.init_gas_price()?;
+
+    let nonces: Vec<U256> = (from_nonce..=to_nonce).map(U256::from).collect();
+    let mut results = Vec::with_capacity(nonces.len());
+    for chunk in nonces.chunks(config.batch_size.max(1) as usize) {
+        let raw_txs = chunk
+            .iter()
+            .map(|nonce| {
+                sign_tx(
+                    &wallet,
+                    &cancel_tx(from, ctx.chain_id.as_u64(), &gas_price, *nonce),
+                )
+            })
+            .collect::<Result<Vec<String>, InkError>>()?;
+        let responses = retry_on_timeout(timeout, retries, "发送替换交易", || {
+            ctx.sender.send_batch(&raw_txs)
+        })
+        .await?;
+        let sent: Vec<(U256, H256)> = chunk
+            .iter()
+            .zip(&responses)
+            .filter_map(|(nonce, response)| {
+                response.as_ref().ok().map(|tx_hash| (*nonce, *tx_hash))
+            })
+            .collect();
+        let tx_hashes: Vec<H256> = sent.iter().map(|(_, tx_hash)| *tx_hash).collect();
+        let statuses = confirm_batch(
+            &ctx.provider,
+            &tx_hashes,
+            Duration::from_secs(config.receipt_timeout_secs),
+            timeout,
+            retries,
+        )
+        .await?;
+        let mut statuses = sent.iter().zip(statuses);
+        for (nonce, response) in chunk.iter().zip(responses) {
+            let result = match response {
+                Ok(_) => {
+                    let ((_, tx_hash), status) = statuses.next().unwrap();
+                    match status {
+                        TxStatus::Confirmed(_) => Ok(*tx_hash),
+                        _ => Err(format!(
+                            "{} 秒内没有上链: {:?}",
+                            config.receipt_timeout_secs, tx_hash
+                        )),
+                    }
+                }
+                Err(e) => Err(e),
+            };
+            results.push(Cancel {
+                nonce: *nonce,
+                result,
+            });
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::NameOrAddress;
+
+    fn gas_price(eip1559: bool) -> GasPrice {
+        GasPrice {
+            eip1559,
+            max_fee_per_gas: U256::exp10(11),
+            max_priority_fee_per_gas: U256::exp10(10),
+            value: U256::exp10(18),
+        }
+    }
+
+    #[test]
+    fn cancel_tx_fields() {
+        let from = Address::from_low_u64_be(0xabc);
+        for eip1559 in [true, false] {
+            let tx = cancel_tx(from, 5, &gas_price(eip1559), U256::from(42));
+            assert_eq!(tx.from(), Some(&from));
+            assert_eq!(tx.to(), Some(&NameOrAddress::Address(from)));
+            assert_eq!(tx.nonce(), Some(&U256::from(42)));
+            // 不带 value 和 data
+            assert_eq!(tx.value(), Some(&U256::zero()));
+            assert_eq!(tx.data(), Some(&Bytes::new()));
+            assert_eq!(tx.gas(), Some(&U256::from(TRANSFER_GAS_LIMIT)));
+            assert_eq!(tx.chain_id(), Some(5u64.into()));
+            assert_eq!(matches!(tx, TypedTransaction::Eip1559(_)), eip1559);
+        }
+
+        let tx = cancel_tx(from, 5, &gas_price(true), U256::zero());
+        let TypedTransaction::Eip1559(tx) = tx else {
+            panic!("应为 eip1559 交易");
+        };
+        assert_eq!(tx.max_fee_per_gas, Some(U256::exp10(11)));
+        assert_eq!(tx.max_priority_fee_per_gas, Some(U256::exp10(10)));
+        let tx = cancel_tx(from, 5, &gas_price(false), U256::zero());
+        assert_eq!(tx.gas_price(), Some(U256::exp10(11)));
+    }
+}
@@ -0,0 +1,226 @@
+use std::path::PathBuf;
+
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use ethers::prelude::Address;
+use evm_ink_rs::{parse_wallet_range, WalletFilter};
+use log::LevelFilter;
+
+/// 命令行参数, 其余配置都在 .env 里
+#[derive(Parser, Debug)]
+#[command(version, about = "evm 系列通用打铭文工具")]
+pub struct Cli {
+    /// 只输出 error 日志
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+    /// 输出更详细的日志, -v 为 debug, -vv 为 trace
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+    /// 终端输出不带颜色, 也可以设置 NO_COLOR 环境变量
+    #[arg(long, global = true)]
+    pub no_color: bool,
+    /// 结果输出格式, json 时结束后向 stdout 打印一个 JSON 对象, 日志改输出到 stderr
+    #[arg(long, value_enum, default_value_t = Output::Text)]
+    pub output: Output,
+    /// 从本地 TOML/JSON 文件或 http(s):// 地址加载配置, 代替 .env; 本机环境变量和 .env 里同名的项优先
+    #[arg(long, global = true, value_name = "PATH_OR_URL")]
+    pub config: Option<String>,
+    /// 远程配置拉取失败时改用的本地配置文件
+    #[arg(long, global = true, value_name = "FILE", requires = "config")]
+    pub config_fallback: Option<PathBuf>,
+    /// 只重发上次导出到这个文件(如 failed.json)里的失败交易, nonce 按链上当前值重新分配
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+    /// 只执行这个索引范围内的钱包(从 0 开始, 包含两端), 如 0-9
+    #[arg(long, value_name = "START-END", value_parser = parse_wallet_range)]
+    pub wallet_range: Option<(usize, usize)>,
+    /// 只执行这些地址的钱包, 逗号分隔
+    #[arg(long, value_name = "ADDRESSES", value_delimiter = ',')]
+    pub wallet_filter: Vec<Address>,
+    /// 打印带注释的完整示例配置(所有配置项和默认值)后退出, 默认 TOML 格式
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        default_missing_value = "toml"
+    )]
+    pub print_example_config: Option<ExampleFormat>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Output {
+    Text,
+    Json,
+}
+
+/// --print-example-config 的格式
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ExampleFormat {
+    /// 给 --config 使用
+    Toml,
+    Env,
+}
+
+#[derive(Subcommand, Debug, PartialEq)]
+pub enum Command {
+    /// 从源钱包给 wallets_file 里的每个地址转指定金额
+    Distribute {
+        /// 每个钱包的金额(ether)
+        amount: String,
+    },
+    /// 把每个钱包扣除手续费后的余额转到 to_address
+    Sweep,
+    /// 用转给自己、value 为 0 的交易替换 private_key 钱包 nonce 范围内卡住的交易
+    Cancel {
+        /// 起始 nonce(包含)
+        #[arg(long)]
+        from_nonce: u64,
+        /// 结束 nonce(包含)
+        #[arg(long)]
+        to_nonce: u64,
+    },
+    /// 打印 wallets_file 里每个钱包 latest/pending 的 nonce 和积压的交易数
+    NonceStatus,
+    /// 把 hex 或链上交易的 input 解码成铭文文本
+    Decode {
+        /// 0x 开头的十六进制数据
+        #[arg(required_unless_present = "tx", conflicts_with = "tx")]
+        hex: Option<String>,
+        /// 从链上拉取这笔交易的 input 解码
+        #[arg(long)]
+        tx: Option<String>,
+        /// --tx 使用的 rpc 地址, 不填时用 .env 里的 rpc_url
+        #[arg(long)]
+        rpc_url: Option<String>,
+    },
+}
+
+impl Cli {
+    pub fn wallet_filter(&self) -> WalletFilter {
+        WalletFilter {
+            range: self.wallet_range,
+            addresses: self.wallet_filter.clone(),
+        }
+    }
+
+    /// 命令行指定的日志级别, 没有指定时为 None 由 log_level 配置决定
+    pub fn log_level(&self) -> Option<LevelFilter> {
+        if self.quiet {
+            return Some(LevelFilter::Error);
+        }
+        match self.verbose {
+            0 => None,
+            1 => Some(LevelFilter::Debug),
+            _ => Some(LevelFilter::Trace),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from(std::iter::once("evm_ink_rs").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn log_level_from_flags() {
+        assert_eq!(parse(&[]).log_level(), None);
+        assert_eq!(parse(&["-q"]).log_level(), Some(LevelFilter::Error));
+        assert_eq!(parse(&["--quiet"]).log_level(), Some(LevelFilter::Error));
+        assert_eq!(parse(&["-v"]).log_level(), Some(LevelFilter::Debug));
+        assert_eq!(parse(&["-vv"]).log_level(), Some(LevelFilter::Trace));
+        assert_eq!(parse(&["-vvv"]).log_level(), Some(LevelFilter::Trace));
+        assert_eq!(parse(&["--verbose", "--verbose"]).log_level(), Some(LevelFilter::Trace));
+        assert!(Cli::try_parse_from(["evm_ink_rs", "-q", "-v"]).is_err());
+    }
+
+    #[test]
+    fn subcommands() {
+        assert_eq!(parse(&[]).command, None);
+        assert_eq!(
+            parse(&["-v", "distribute", "0.01"]).command,
+            Some(Command::Distribute {
+                amount: "0.01".to_string()
+            })
+        );
+        assert_eq!(parse(&["sweep"]).command, Some(Command::Sweep));
+        assert_eq!(
+            parse(&["cancel", "--from-nonce", "3", "--to-nonce", "5"]).command,
+            Some(Command::Cancel {
+                from_nonce: 3,
+                to_nonce: 5
+            })
+        );
+        assert!(Cli::try_parse_from(["evm_ink_rs", "cancel", "--from-nonce", "3"]).is_err());
+        assert_eq!(parse(&["nonce-status"]).command, Some(Command::NonceStatus));
+        assert!(parse(&["sweep", "--no-color"]).no_color);
+        assert_eq!(parse(&[]).output, Output::Text);
+        assert_eq!(parse(&["--output", "json"]).output, Output::Json);
+        assert!(Cli::try_parse_from(["evm_ink_rs", "--output", "xml"]).is_err());
+        assert_eq!(parse(&[]).replay, None);
+        let cli = parse(&["--config", "https://example.com/a.toml", "--config-fallback", "a.toml"]);
+        assert_eq!(cli.config.as_deref(), Some("https://example.com/a.toml"));
+        assert_eq!(cli.config_fallback, Some(PathBuf::from("a.toml")));
+        assert!(Cli::try_parse_from(["evm_ink_rs", "--config-fallback", "a.toml"]).is_err());
+        assert_eq!(
+            parse(&["--replay", "failed.json"]).replay,
+            Some(PathBuf::from("failed.json"))
+        );
+        assert_eq!(parse(&[]).print_example_config, None);
+        assert_eq!(
+            parse(&["--print-example-config"]).print_example_config,
+            Some(ExampleFormat::Toml)
+        );
+        assert_eq!(
+            parse(&["--print-example-config", "env"]).print_example_config,
+            Some(ExampleFormat::Env)
+        );
+    }
+
+    #[test]
+    fn wallet_filter_args() {
+        assert!(parse(&[]).wallet_filter().is_empty());
+        let cli = parse(&[
+            "--wallet-range",
+            "0-9",
+            "--wallet-filter",
+            "0x0000000000000000000000000000000000000001,0x0000000000000000000000000000000000000002",
+        ]);
+        assert_eq!(
+            cli.wallet_filter(),
+            WalletFilter {
+                range: Some((0, 9)),
+                addresses: vec![Address::from_low_u64_be(1), Address::from_low_u64_be(2)],
+            }
+        );
+        assert_eq!(parse(&["--wallet-range", "3"]).wallet_range, Some((3, 3)));
+        assert!(Cli::try_parse_from(["evm_ink_rs", "--wallet-range", "9-0"]).is_err());
+        assert!(Cli::try_parse_from(["evm_ink_rs", "--wallet-filter", "0xabc"]).is_err());
+    }
+
+    #[test]
+    fn decode_subcommand() {
+        assert_eq!(
+            parse(&["decode", "0x6461"]).command,
+            Some(Command::Decode {
+                hex: Some("0x6461".to_string()),
+                tx: None,
+                rpc_url: None,
+            })
+        );
+        assert_eq!(
+            parse(&["decode", "--tx", "0xabc", "--rpc-url", "http://localhost:8545"]).command,
+            Some(Command::Decode {
+                hex: None,
+                tx: Some("0xabc".to_string()),
+                rpc_url: Some("http://localhost:8545".to_string()),
+            })
+        );
+        assert!(Cli::try_parse_from(["evm_ink_rs", "decode"]).is_err());
+        assert!(Cli::try_parse_from(["evm_ink_rs", "decode", "0x64", "--tx", "0xabc"]).is_err());
+    }
+}
@@ -0,0 +1,227 @@
+use std::path::Path;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::{Config, InkError};
+
+/// 拉取远程配置的超时时间
+pub const REMOTE_CONFIG_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 是否为 http(s):// 开头的远程配置地址
+pub fn is_remote_config(source: &str) -> bool {
+    let source = source.trim().to_ascii_lowercase();
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+/// 读取配置文本, http(s):// 开头时用 GET 拉取, 否则当作本地路径
+pub async fn read_config_text(source: &str) -> Result<String, InkError> {
+    let source = source.trim();
+    if !is_remote_config(source) {
+        return std::fs::read_to_string(source)
+            .map_err(|e| InkError::Config(format!("读取配置文件失败: {}: {}", source, e)));
+    }
+    let client = reqwest::Client::builder()
+        .timeout(REMOTE_CONFIG_TIMEOUT)
+        .build()
+        .map_err(|e| InkError::Config(format!("创建 http client 失败: {}", e)))?;
+    let response = client
+        .get(source)
+        .send()
+        .await
+        .map_err(|e| InkError::Config(format!("拉取远程配置失败: {}: {}", source, e)))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(InkError::Config(format!(
+            "拉取远程配置失败: {}: HTTP {}",
+            source, status
+        )));
+    }
+    response
+        .text()
+        .await
+        .map_err(|e| InkError::Config(format!("读取远程配置失败: {}: {}", source, e)))
+}
+
+/// 配置项的值转成和 .env 相同的文本: 数组用逗号连接, 对象(如 rpc_headers)转成 JSON
+fn config_value_text(key: &str, value: &Value) -> Result<String, InkError> {
+    match value {
+        Value::String(text) => Ok(text.clone()),
+        Value::Number(_) | Value::Bool(_) => Ok(value.to_string()),
+        Value::Object(_) => Ok(value.to_string()),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Array(_) | Value::Object(_) | Value::Null => Err(InkError::Config(format!(
+                    "配置项 {} 的数组只能包含字符串或数字",
+                    key
+                ))),
+                _ => config_value_text(key, item),
+            })
+            .collect::<Result<Vec<String>, InkError>>()
+            .map(|items| items.join(",")),
+        Value::Null => Err(InkError::Config(format!("配置项 {} 不能为 null", key))),
+    }
+}
+
+/// 解析 TOML 或 JSON 配置(以 { 开头时按 JSON), 返回和环境变量相同的 (key, value) 列表
+pub fn parse_config_text(text: &str) -> Result<Vec<(String, String)>, InkError> {
+    let value: Value = if text.trim_start().starts_with('{') {
+        serde_json::from_str(text)
+            .map_err(|e| InkError::Config(format!("JSON 配置格式不正确: {}", e)))?
+    } else {
+        let table: toml::Table = text
+            .parse()
+            .map_err(|e| InkError::Config(format!("TOML 配置格式不正确: {}", e)))?;
+        serde_json::to_value(table)
+            .map_err(|e| InkError::Config(format!("TOML 配置格式不正确: {}", e)))?
+    };
+    let Value::Object(map) = value else {
+        return Err(InkError::Config("配置必须是一个对象".to_string()));
+    };
+    map.iter()
+        .map(|(key, value)| Ok((key.to_lowercase(), config_value_text(key, value)?)))
+        .collect()
+}
+
+/// 从本地路径或 http(s):// 地址加载配置, env 里同名的项优先(私钥等可以只放在本机 .env)
+///
+/// 远程拉取失败且设置了 fallback 时改读本地的 fallback 文件, 同时返回远程拉取失败的原因用于提示
+/// (加载配置时日志还没有初始化)
+pub async fn load_config<I>(
+    source: &str,
+    fallback: Option<&Path>,
+    env: I,
+) -> Result<(Config, Option<InkError>), InkError>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let (text, fallback_error) = match read_config_text(source).await {
+        Ok(text) => (text, None),
+        Err(e) => match fallback {
+            Some(fallback) if is_remote_config(source) => (
+                read_config_text(&fallback.to_string_lossy()).await?,
+                Some(e),
+            ),
+            _ => return Err(e),
+        },
+    };
+    let mut vars = parse_config_text(&text)?;
+    for (key, value) in env {
+        let key = key.to_lowercase();
+        vars.retain(|(name, _)| *name != key);
+        vars.push((key, value));
+    }
+    let config = envy::from_iter::<_, Config>(vars)
+        .map_err(|e| InkError::Config(format!("配置不正确: {}: {}", source, e)))?;
+    Ok((config, fallback_error))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Request, Response, Server, StatusCode};
+
+    use super::*;
+
+    const TOML_CONFIG: &str = r#"
+rpc_url = "http://127.0.0.1:8545"
+private_key = "0x0000000000000000000000000000000000000000000000000000000000000001"
+max_fee_per_gas = 30
+count = 5
+batch_size = 2
+send_on_new_block = true
+rpc_headers = { "x-api-key" = "abc" }
+data = 'data:,{"p":"erc-20","op":"mint","tick":"test","amt":"1000"}'
+"#;
+
+    /// 本地 http 服务器, /config.toml 返回 TOML_CONFIG, 其它路径 404
+    fn serve_config() -> SocketAddr {
+        let make_service = make_service_fn(|_| async {
+            Ok::<_, Infallible>(service_fn(|request: Request<Body>| async move {
+                let response = match request.uri().path() {
+                    "/config.toml" => Response::new(Body::from(TOML_CONFIG)),
+                    _ => Response::builder()
+                        .status(StatusCode::NOT_FOUND)
+                        .body(Body::empty())
+                        .unwrap(),
+                };
+                Ok::<_, Infallible>(response)
+            }))
+        });
+        let server = Server::bind(&SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    #[test]
+    fn parse_toml_and_json() {
+        let vars = parse_config_text(TOML_CONFIG).unwrap();
+        let get = |key: &str| vars.iter().find(|(name, _)| name == key).unwrap().1.clone();
+        assert_eq!(get("count"), "5");
+        assert_eq!(get("send_on_new_block"), "true");
+        assert_eq!(get("rpc_headers"), r#"{"x-api-key":"abc"}"#);
+
+        let vars =
+            parse_config_text(r#"{"COUNT": 3, "rpc_url": ["http://a", "http://b"]}"#).unwrap();
+        assert_eq!(
+            vars,
+            vec![
+                ("count".to_string(), "3".to_string()),
+                ("rpc_url".to_string(), "http://a,http://b".to_string()),
+            ]
+        );
+        assert!(parse_config_text(r#"{"count": null}"#).is_err());
+        assert!(parse_config_text("count = [[1]]").is_err());
+        assert!(parse_config_text("count =").is_err());
+        assert!(is_remote_config("HTTPS://example.com/a.toml"));
+        assert!(!is_remote_config("./config.toml"));
+    }
+
+    #[tokio::test]
+    async fn load_remote_config() {
+        let addr = serve_config();
+        let url = format!("http://{}/config.toml", addr);
+        let (config, fallback_error) = load_config(&url, None, Vec::new()).await.unwrap();
+        assert!(fallback_error.is_none());
+        assert_eq!(config.count, 5);
+        assert_eq!(config.batch_size, 2);
+        assert!(config.send_on_new_block);
+        assert_eq!(config.rpc_headers.get("x-api-key").unwrap(), "abc");
+
+        // 本机环境变量优先
+        let env = vec![("COUNT".to_string(), "9".to_string())];
+        let (config, _) = load_config(&url, None, env).await.unwrap();
+        assert_eq!(config.count, 9);
+    }
+
+    #[tokio::test]
+    async fn remote_config_fallback() {
+        let addr = serve_config();
+        let url = format!("http://{}/missing.toml", addr);
+        let error = load_config(&url, None, Vec::new()).await.unwrap_err();
+        assert!(error.to_string().contains("HTTP 404"), "{}", error);
+
+        let path = std::env::temp_dir().join(format!(
+            "evm_ink_rs_config_fallback_{}.toml",
+            std::process::id()
+        ));
+        std::fs::write(&path, TOML_CONFIG.replace("count = 5", "count = 7")).unwrap();
+        let (config, fallback_error) = load_config(&url, Some(&path), Vec::new()).await.unwrap();
+        assert_eq!(config.count, 7);
+        assert!(fallback_error.unwrap().to_string().contains("HTTP 404"));
+
+        // 本地配置读取失败时不回退
+        let missing = path.with_extension("missing");
+        assert!(
+            load_config(&missing.to_string_lossy(), Some(&path), Vec::new())
+                .await
+                .is_err()
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,125 @@
+use std::io::IsTerminal;
+
+use colored::Colorize;
+use ethers::prelude::{Http, Middleware, Provider, H256};
+use ethers::utils::hex;
+use serde_json::Value;
+
+use evm_ink_rs::{decode_inscription, DecodedInscription, InkError};
+
+use crate::initialization::use_color;
+
+/// decode 子命令: 解码命令行给出的 hex 或链上交易的 input 并打印
+pub async fn run_decode(
+    hex_data: Option<&str>,
+    tx: Option<&str>,
+    rpc_url: Option<&str>,
+    no_color: bool,
+) -> Result<(), InkError> {
+    let no_color_env = std::env::var("NO_COLOR").ok();
+    colored::control::set_override(use_color(
+        no_color,
+        no_color_env.as_deref(),
+        std::io::stdout().is_terminal(),
+    ));
+    let input = match (hex_data, tx) {
+        (_, Some(tx)) => fetch_input(tx, rpc_url).await?,
+        (Some(hex_data), None) => hex_data.to_string(),
+        (None, None) => return Err(InkError::Config("需要提供 hex 或 --tx".to_string())),
+    };
+    let decoded = decode_inscription(&input)?;
+    println!("{}", render(&decoded));
+    Ok(())
+}
+
+/// 从链上拉取交易的 input, rpc 地址优先用 --rpc-url, 否则用 .env 里的 rpc_url
+async fn fetch_input(tx: &str, rpc_url: Option<&str>) -> Result<String, InkError> {
+    let tx_hash: H256 = tx
+        .trim()
+        .parse()
+        .map_err(|e| InkError::Config(format!("交易哈希不合法: {}: {}", tx, e)))?;
+    let rpc_url = match rpc_url {
+        Some(rpc_url) => rpc_url.to_string(),
+        None => std::env::var("rpc_url")
+            .map_err(|_| InkError::Config("--tx 需要 --rpc-url 或 rpc_url 配置".to_string()))?,
+    };
+    let provider = Provider::<Http>::try_from(rpc_url.as_str())
+        .map_err(|e| InkError::Config(format!("rpc_url 不合法: {}: {}", rpc_url, e)))?;
+    let transaction = provider
+        .get_transaction(tx_hash)
+        .await
+        .map_err(|e| InkError::Rpc(format!("获取交易失败: {}", e)))?
+        .ok_or_else(|| InkError::Rpc(format!("没有找到交易: {:?}", tx_hash)))?;
+    Ok(hex::encode_prefixed(&transaction.input))
+}
+
+/// 高亮显示解码结果: 前缀、JSON 的键和值分别上色, 非 UTF-8 内容附加提示
+pub fn render(decoded: &DecodedInscription) -> String {
+    let mut text = String::new();
+    if let Some(prefix) = &decoded.prefix {
+        text.push_str(&prefix.yellow().bold().to_string());
+    }
+    match &decoded.json {
+        Some(json) => render_json(json, &mut text),
+        None => text.push_str(&decoded.body),
+    }
+    if !decoded.valid_utf8 {
+        text.push_str(&format!(
+            "\n{}",
+            "(不是合法的 UTF-8, 无法识别的字节显示为 �)".red()
+        ));
+    }
+    text
+}
+
+fn render_json(value: &Value, text: &mut String) {
+    match value {
+        Value::Object(map) => {
+            text.push('{');
+            for (i, (key, value)) in map.iter().enumerate() {
+                if i > 0 {
+                    text.push(',');
+                }
+                text.push_str(&Value::from(key.as_str()).to_string().cyan().to_string());
+                text.push(':');
+                render_json(value, text);
+            }
+            text.push('}');
+        }
+        Value::Array(items) => {
+            text.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    text.push(',');
+                }
+                render_json(item, text);
+            }
+            text.push(']');
+        }
+        Value::String(_) => text.push_str(&value.to_string().green().to_string()),
+        _ => text.push_str(&value.to_string().yellow().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_plain(text: &[u8]) -> String {
+        colored::control::set_override(false);
+        render(&decode_inscription(&hex::encode_prefixed(text)).unwrap())
+    }
+
+    #[test]
+    fn render_inscriptions() {
+        assert_eq!(render_plain(b"data:,hello"), "data:,hello");
+        assert_eq!(render_plain(b"just text"), "just text");
+        let json = r#"data:,{"p":"erc-20","op":"mint","amt":1000,"list":[1,"a"]}"#;
+        assert_eq!(render_plain(json.as_bytes()), json);
+        // 非 JSON 的花括号原样输出
+        assert_eq!(render_plain(b"data:,{not json"), "data:,{not json");
+        let binary = render_plain(&[0x12, 0x49, 0xff, 0x8b]);
+        assert!(binary.starts_with("\u{12}I\u{FFFD}"));
+        assert!(binary.contains("不是合法的 UTF-8"));
+    }
+}
@@ -0,0 +1,162 @@
+use std::str::FromStr;
+
+use base64::engine::general_purpose;
+use base64::Engine;
+use ethers::utils::hex;
+
+use crate::{gzip_compress, InkError};
+
+/// 编码管线的一步, 按顺序作用在上一步的结果上
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncodingStep {
+    /// 替换 data 里的占位符, 需要 Config 上下文, 只能是第一步, 由 Config 在管线之前展开
+    Template,
+    /// 在前面拼接 prefix, 配置里写 prefix 时使用 Config.prefix
+    Prefix(String),
+    Gzip,
+    /// 转成 base64 文本
+    Base64,
+    /// 转成 0x 开头的十六进制文本, 作为最后一步时就是交易的 calldata
+    Hex,
+}
+
+impl FromStr for EncodingStep {
+    type Err = InkError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_lowercase().as_str() {
+            "template" => Ok(EncodingStep::Template),
+            "prefix" => Ok(EncodingStep::Prefix(String::new())),
+            "gzip" => Ok(EncodingStep::Gzip),
+            "base64" => Ok(EncodingStep::Base64),
+            "hex" => Ok(EncodingStep::Hex),
+            _ => Err(InkError::Config(format!(
+                "encoding 不支持的步骤: {}(可选 template/prefix/gzip/base64/hex)",
+                text.trim()
+            ))),
+        }
+    }
+}
+
+/// 解析逗号分隔的编码管线, 可以带中括号, 如 [template, gzip, base64]
+///
+/// prefix 步骤拼接的是传入的 prefix
+pub fn parse_encoding(text: &str, prefix: &str) -> Result<Vec<EncodingStep>, InkError> {
+    let text = text.trim();
+    let text = text
+        .strip_prefix('[')
+        .and_then(|text| text.strip_suffix(']'))
+        .unwrap_or(text);
+    let steps = text
+        .split(',')
+        .filter(|step| !step.trim().is_empty())
+        .map(|step| match step.parse::<EncodingStep>()? {
+            EncodingStep::Prefix(_) => Ok(EncodingStep::Prefix(prefix.to_string())),
+            step => Ok(step),
+        })
+        .collect::<Result<Vec<EncodingStep>, InkError>>()?;
+    if steps.is_empty() {
+        return Err(InkError::Config("encoding 不能为空".to_string()));
+    }
+    if steps[1..].contains(&EncodingStep::Template) {
+        return Err(InkError::Config(
+            "encoding 的 template 只能是第一步".to_string(),
+        ));
+    }
+    Ok(steps)
+}
+
+/// 对 text 按顺序执行编码管线, 返回最后一步的字节
+///
+/// Template 由调用方展开后再传入, 这里跳过
+pub fn apply_pipeline(text: &str, steps: &[EncodingStep]) -> Result<Vec<u8>, InkError> {
+    let mut bytes = text.as_bytes().to_vec();
+    for step in steps {
+        bytes = match step {
+            EncodingStep::Template => bytes,
+            EncodingStep::Prefix(prefix) => {
+                let mut prefixed = prefix.as_bytes().to_vec();
+                prefixed.extend(bytes);
+                prefixed
+            }
+            EncodingStep::Gzip => gzip_compress(&bytes)?,
+            EncodingStep::Base64 => general_purpose::STANDARD.encode(&bytes).into_bytes(),
+            EncodingStep::Hex => hex::encode_prefixed(&bytes).into_bytes(),
+        };
+    }
+    Ok(bytes)
+}
+
+/// 管线结果对应的交易 data: 最后一步是 hex 时结果本身就是 0x 文本, 否则把结果的字节转成十六进制
+pub fn pipeline_calldata(text: &str, steps: &[EncodingStep]) -> Result<String, InkError> {
+    let bytes = apply_pipeline(text, steps)?;
+    match steps.last() {
+        Some(EncodingStep::Hex) => String::from_utf8(bytes)
+            .map_err(|e| InkError::Data(format!("编码结果不是十六进制文本: {}", e))),
+        _ => Ok(hex::encode_prefixed(bytes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gzip_decompress;
+
+    const TEXT: &str = r#"{"p":"erc-20","op":"mint","tick":"test","amt":"1000"}"#;
+
+    #[test]
+    fn parse_steps() {
+        assert_eq!(
+            parse_encoding("[template, prefix, hex]", "data:,").unwrap(),
+            vec![
+                EncodingStep::Template,
+                EncodingStep::Prefix("data:,".to_string()),
+                EncodingStep::Hex,
+            ]
+        );
+        assert_eq!(
+            parse_encoding("gzip,BASE64", "").unwrap(),
+            vec![EncodingStep::Gzip, EncodingStep::Base64]
+        );
+        assert!(parse_encoding("[]", "").is_err());
+        assert!(parse_encoding("prefix,rot13", "").is_err());
+        assert!(parse_encoding("prefix,template", "").is_err());
+    }
+
+    #[test]
+    fn pipeline_combinations() {
+        let prefix = EncodingStep::Prefix("data:,".to_string());
+        // 和默认的 prefix 拼接相同
+        assert_eq!(
+            pipeline_calldata(
+                TEXT,
+                &[EncodingStep::Template, prefix.clone(), EncodingStep::Hex]
+            )
+            .unwrap(),
+            hex::encode_prefixed(format!("data:,{}", TEXT))
+        );
+        // 没有 hex 步骤时结果的字节直接作为 calldata
+        assert_eq!(
+            pipeline_calldata(TEXT, std::slice::from_ref(&prefix)).unwrap(),
+            hex::encode_prefixed(format!("data:,{}", TEXT))
+        );
+
+        let bytes = apply_pipeline(TEXT, &[EncodingStep::Gzip, EncodingStep::Base64]).unwrap();
+        let gzipped = general_purpose::STANDARD.decode(&bytes).unwrap();
+        assert_eq!(gzip_decompress(&gzipped).unwrap(), TEXT.as_bytes());
+
+        // 和 compress=gzip 相同: gzip 之后拼接 prefix
+        let gzip_prefix = EncodingStep::Prefix("data:application/gzip,".to_string());
+        let bytes = apply_pipeline(TEXT, &[EncodingStep::Gzip, gzip_prefix]).unwrap();
+        assert!(bytes.starts_with(b"data:application/gzip,"));
+        assert_eq!(
+            gzip_decompress(&bytes["data:application/gzip,".len()..]).unwrap(),
+            TEXT.as_bytes()
+        );
+
+        // hex 不在最后时十六进制文本继续参与后面的步骤
+        let bytes = apply_pipeline("hi", &[EncodingStep::Hex, prefix]).unwrap();
+        assert_eq!(bytes, b"data:,0x6869");
+        assert_eq!(apply_pipeline("", &[EncodingStep::Base64]).unwrap(), b"");
+    }
+}
@@ -0,0 +1,420 @@
+use std::fmt::Write;
+
+/// 示例配置里的一项, 和 Config 的字段一一对应, 新增配置项时要同步加到 EXAMPLE_FIELDS
+struct ExampleField {
+    key: &'static str,
+    value: &'static str,
+    /// TOML 里按字符串写
+    quoted: bool,
+    comment: &'static str,
+    /// 选填且没有默认值的项, 示例里注释掉
+    enabled: bool,
+}
+
+const fn text(key: &'static str, value: &'static str, comment: &'static str) -> ExampleField {
+    ExampleField {
+        key,
+        value,
+        quoted: true,
+        comment,
+        enabled: true,
+    }
+}
+
+const fn raw(key: &'static str, value: &'static str, comment: &'static str) -> ExampleField {
+    ExampleField {
+        quoted: false,
+        ..text(key, value, comment)
+    }
+}
+
+impl ExampleField {
+    const fn disabled(self) -> Self {
+        ExampleField {
+            enabled: false,
+            ..self
+        }
+    }
+}
+
+const EXAMPLE_FIELDS: &[ExampleField] = &[
+    text(
+        "rpc_url",
+        "https://rpc.ankr.com/eth",
+        "rpc 地址 必填, 多链模式时逗号分隔多个",
+    ),
+    text(
+        "private_key",
+        "",
+        "你的私钥 带不带0x前缀都可以, 使用 wallets_file 时可以不填",
+    ),
+    text(
+        "to_address",
+        "0x...",
+        "接收地址, 不填默认为私钥对应的钱包地址",
+    )
+    .disabled(),
+    raw("max_fee_per_gas", "30", "最大 gas 费用(gwei) 必填"),
+    raw(
+        "max_priority_fee_per_gas",
+        "1",
+        "gas 优先费用(gwei), 不支持 EIP-1559 的链不填",
+    ),
+    raw(
+        "priority_equals_max",
+        "false",
+        "优先费用直接等于 max_fee_per_gas",
+    ),
+    raw(
+        "count",
+        "1",
+        "mint 的数量 必填, 填 0 或 auto 时按余额自动计算",
+    ),
+    raw(
+        "reserve_ether",
+        "0.0",
+        "count 自动计算时保留不花的余额(ether)",
+    ),
+    text(
+        "data",
+        r#"{"p":"erc-20","op":"mint","tick":"eths","amt":"1000"}"#,
+        "mint 的数据 必填, 十六进制(0x开头)或文本",
+    ),
+    text("prefix", "data:,", "文本铭文的前缀"),
+    text(
+        "encoding",
+        "template,prefix,hex",
+        "编码管线, 设置后代替 prefix 和 compress",
+    )
+    .disabled(),
+    text("compress", "none", "文本铭文压缩方式 none/gzip"),
+    text(
+        "address_case",
+        "raw",
+        "[address] 的地址格式 lower/checksum/raw",
+    ),
+    raw("strict_utf8", "true", "data 不是合法 UTF-8 文本时直接退出"),
+    text("placeholders", r#"{"[tick]":"eths"}"#, "自定义占位符(JSON)").disabled(),
+    raw(
+        "value",
+        "0.0",
+        "每笔附带的 value(ether), 可以写 0.001+id*0.0001 这样的表达式",
+    ),
+    raw("gas_limit", "50000", "gas_limit"),
+    raw("gas_limit_buffer_percent", "0.0", "gas_limit 的百分比余量"),
+    raw("gas_limit_min", "30000", "加上余量后 gas_limit 的下限").disabled(),
+    raw("gas_limit_max", "300000", "加上余量后 gas_limit 的上限").disabled(),
+    raw("estimate_gas", "false", "用 estimate_gas 估算 gas_limit"),
+    raw(
+        "estimate_sample_interval",
+        "0",
+        "每 N 笔重新估算一次 0 为只估算一次",
+    ),
+    raw("batch_size", "100", "每批发送的笔数"),
+    text(
+        "interval",
+        "0",
+        "批次间隔(秒): 固定值 0.5, 随机范围 0.5-1.5, 或倍数 0.1*1.5",
+    ),
+    raw("adaptive_batch", "false", "节点提示 batch 过大时自动减半"),
+    raw("nonce_step", "1", "每笔交易 nonce 的增量"),
+    raw(
+        "id_stride",
+        "0",
+        "多钱包打同一个 id 范围时每个钱包错开的 id 数量",
+    ),
+    text("items_file", "items.json", "任务条目 JSON 文件").disabled(),
+    text(
+        "tx_hashes_file",
+        "tx_hashes.csv",
+        "签名后预先算出的交易哈希写入的 CSV",
+    )
+    .disabled(),
+    raw("concurrency", "1", "同时执行的钱包数量"),
+    raw(
+        "wallet_interval",
+        "0.0",
+        "一个钱包结束后进入下一个钱包前等待的秒数",
+    ),
+    text(
+        "wallet_start_jitter_secs",
+        "0-5",
+        "每个钱包开始前随机等待的秒数",
+    )
+    .disabled(),
+    text(
+        "send_window",
+        "22:00-06:00",
+        "每天允许发送的时间段(本机时间)",
+    )
+    .disabled(),
+    raw("rpc_timeout_secs", "30", "单次 RPC 请求超时时间(秒)"),
+    raw("max_retries", "3", "超时等可重试错误的最大重试次数"),
+    raw("network_retries", "3", "发送 batch 超时或连不上节点时整批重试的次数"),
+    raw(
+        "network_backoff_secs",
+        "1.0",
+        "连接级错误第一次重试前等待的秒数, 之后每次翻倍",
+    ),
+    text(
+        "error_policy",
+        r#"{"already known":"skip"}"#,
+        "按错误关键字 retry/skip/abort",
+    )
+    .disabled(),
+    text(
+        "rpc_headers",
+        r#"{"Authorization":"Bearer xxx"}"#,
+        "RPC 请求附带的 header",
+    )
+    .disabled(),
+    raw("wait_receipt", "false", "每批发送后等待回执"),
+    raw("receipt_timeout_secs", "120", "等待一批回执的最长时间(秒)"),
+    raw(
+        "verify_in_mempool",
+        "false",
+        "每批发送后确认交易已进入节点的交易池",
+    ),
+    text(
+        "ws_url",
+        "wss://...",
+        "ws 节点地址, send_on_new_block 时使用",
+    )
+    .disabled(),
+    raw("send_on_new_block", "false", "每收到一个新区块发送一批"),
+    raw("max_gas_gate_gwei", "20", "网络 gas 高于该值(gwei)时等待").disabled(),
+    raw("gate_poll_secs", "12", "gas 高于门控时重查的间隔(秒)"),
+    raw(
+        "watch_mode",
+        "false",
+        "持续监控模式, gas 不高于门控时打一批",
+    ),
+    raw("watch_batch_size", "10", "监控模式每批的笔数"),
+    raw(
+        "watch_timeout_secs",
+        "0",
+        "监控模式最长运行时间(秒), 0 为不限",
+    ),
+    raw("start_block", "19000000", "到达该区块高度才开始发送").disabled(),
+    raw("base_fee_clamp_mul", "2", "按 base fee 收紧 max_fee 的倍数").disabled(),
+    text(
+        "private_rpc_url",
+        "https://rpc.flashbots.net",
+        "私有/MEV 保护 RPC",
+    )
+    .disabled(),
+    raw("bundle_mode", "false", "每批交易打包成一个 bundle 发送"),
+    text(
+        "bundle_method",
+        "eth_sendBundle",
+        "eth_sendBundle 或 mev_sendBundle",
+    ),
+    raw("bundle_retries", "5", "bundle 没有上链时重投的次数"),
+    text(
+        "funding_private_key",
+        "0x...",
+        "资助钱包私钥, 开打前给余额不足的钱包补 gas",
+    )
+    .disabled(),
+    raw(
+        "min_gas_ether",
+        "0.0",
+        "钱包余额低于该值(ether)时补足到该值",
+    ),
+    text("approve_token", "0x...", "开打前授权的 ERC-20 代币").disabled(),
+    text("approve_spender", "0x...", "授权给的地址").disabled(),
+    text("approve_amount", "max", "授权数量(代币最小单位)或 max").disabled(),
+    text(
+        "fee_contract",
+        "0x...",
+        "付费铭文合约, 设置后附带 fee_value 代替 value",
+    )
+    .disabled(),
+    raw("fee_value", "0.0", "付费铭文每笔附带的服务费(ether)"),
+    text(
+        "fee_calldata",
+        "0x1249c58b",
+        "付费铭文的 calldata, 设置后代替 data",
+    )
+    .disabled(),
+    text(
+        "expected_selector",
+        "0x1249c58b",
+        "0x data 的方法选择器必须与之相同",
+    )
+    .disabled(),
+    raw(
+        "multichain",
+        "false",
+        "多链模式: rpc_url 里每个地址当作一条链",
+    ),
+    raw(
+        "max_inflight",
+        "0",
+        "所有钱包共用的最大在途发送请求数, 0 为不限",
+    ),
+    raw(
+        "max_tps",
+        "0.0",
+        "所有钱包合计每秒最多发送的交易数, 0 为不限",
+    ),
+    text("log_level", "info", "日志级别 error/warn/info/debug/trace").disabled(),
+    raw(
+        "log_max_bytes",
+        "0",
+        "日志文件超过该字节数时切分, 0 为不按大小切分",
+    ),
+    raw("log_rotate_daily", "false", "每天切分一个新的日志文件"),
+    raw("log_keep_files", "5", "切分后保留的旧日志文件数量"),
+    raw(
+        "metrics_port",
+        "9100",
+        "Prometheus /metrics 和 /status 的端口",
+    )
+    .disabled(),
+    raw("status_port", "9101", "单独提供 /status 的端口").disabled(),
+    raw("quiet_banner", "false", "启动时不打印 banner"),
+];
+
+/// 只从环境变量读取的项, TOML 配置里写了也不生效
+const ENV_ONLY_FIELDS: &[ExampleField] = &[
+    text("wallets_file", "./wallets.txt", "多钱包文件, 逗号分隔多个").disabled(),
+    text("keystore_dir", "./keystores", "geth keystore 文件或目录").disabled(),
+    text("keystore_password", "", "keystore 的口令").disabled(),
+];
+
+/// TOML 字符串, 含双引号时用单引号字面量避免转义
+fn toml_string(value: &str) -> String {
+    if value.contains('"') && !value.contains('\'') {
+        format!("'{}'", value)
+    } else {
+        toml::Value::String(value.to_string()).to_string()
+    }
+}
+
+/// .env 的值, JSON 等带特殊字符的值前后加单引号
+fn env_value(value: &str) -> String {
+    if value.contains(['{', '"', '#', ' ']) {
+        format!("'{}'", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// 带注释的完整 TOML 示例配置, 可以直接用 --config 加载
+pub fn example_toml() -> String {
+    render_toml(false)
+}
+
+/// all 为 true 时注释掉的项也写出来
+fn render_toml(all: bool) -> String {
+    let mut text = String::from(
+        "# evm_ink_rs 示例配置, 用 --config 加载; 注释掉的是选填且没有默认值的项\n\
+         # wallets_file、keystore_dir、keystore_password、PRIVATE_KEY_N 只从环境变量读取, 请写在 .env 里\n",
+    );
+    for field in EXAMPLE_FIELDS {
+        let value = if field.quoted {
+            toml_string(field.value)
+        } else {
+            field.value.to_string()
+        };
+        let disabled = if field.enabled || all { "" } else { "# " };
+        let _ = write!(
+            text,
+            "\n# {}\n{}{} = {}\n",
+            field.comment, disabled, field.key, value
+        );
+    }
+    text
+}
+
+/// 带注释的完整 .env 示例配置
+pub fn example_env() -> String {
+    let mut text = String::from("# evm_ink_rs 示例 .env; 注释掉的是选填且没有默认值的项\n");
+    for field in EXAMPLE_FIELDS.iter().chain(ENV_ONLY_FIELDS) {
+        let disabled = if field.enabled { "" } else { "# " };
+        let value = env_value(field.value);
+        let _ = write!(
+            text,
+            "\n# {}\n{}{}={}\n",
+            field.comment, disabled, field.key, value
+        );
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_config_text, Config};
+
+    /// 按 dotenv 的写法解析示例 .env, 跳过注释和空行
+    fn parse_env(text: &str) -> Vec<(String, String)> {
+        text.lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let (key, value) = line.split_once('=').unwrap();
+                let value = value
+                    .strip_prefix('\'')
+                    .and_then(|value| value.strip_suffix('\''));
+                (
+                    key.to_string(),
+                    value.unwrap_or(&line[key.len() + 1..]).to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn example_toml_is_valid_config() {
+        let vars = parse_config_text(&example_toml()).unwrap();
+        let config = envy::from_iter::<_, Config>(vars).unwrap();
+        assert!(config.validate().is_ok(), "{:?}", config.validate());
+        assert_eq!(config.batch_size, 100);
+        assert_eq!(
+            config.data,
+            r#"{"p":"erc-20","op":"mint","tick":"eths","amt":"1000"}"#
+        );
+
+        // 注释掉的项取消注释后也都能解析
+        let vars = parse_config_text(&render_toml(true)).unwrap();
+        assert_eq!(vars.len(), EXAMPLE_FIELDS.len());
+        envy::from_iter::<_, Config>(vars).unwrap();
+    }
+
+    #[test]
+    fn example_env_is_valid_config() {
+        let config = envy::from_iter::<_, Config>(parse_env(&example_env())).unwrap();
+        assert!(config.validate().is_ok(), "{:?}", config.validate());
+        assert_eq!(
+            config.data,
+            r#"{"p":"erc-20","op":"mint","tick":"eths","amt":"1000"}"#
+        );
+        assert_eq!(config.max_fee_per_gas, 30.0);
+    }
+
+    #[test]
+    fn example_covers_config_fields() {
+        // 从源码里取 Config 可以从配置读取的字段, 检查示例没有漏掉
+        let source = include_str!("lib.rs");
+        let start = source.find("pub struct Config {").unwrap();
+        let end = start + source[start..].find("\n}").unwrap();
+        let mut skip = false;
+        for line in source[start..end].lines().map(str::trim) {
+            if line.starts_with("#[serde(skip_deserializing)]") {
+                skip = true;
+            } else if let Some(field) = line
+                .strip_prefix("pub ")
+                .and_then(|line| line.split_once(':'))
+            {
+                if !std::mem::take(&mut skip) {
+                    assert!(
+                        EXAMPLE_FIELDS.iter().any(|example| example.key == field.0),
+                        "示例配置缺少 {}",
+                        field.0
+                    );
+                }
+            }
+        }
+    }
+}
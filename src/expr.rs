@@ -0,0 +1,280 @@
+use crate::InkError;
+
+/// [expr:...] 里可以使用的变量
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExprVars {
+    /// 这笔交易使用的 id, data 没有 id 范围时为 None
+    pub id: Option<u64>,
+    pub nonce: u64,
+}
+
+/// 计算只包含整数、id/nonce 变量、+ - * / 和括号的表达式, 除法向零取整
+///
+/// 除数为 0 或结果溢出时返回 Err
+pub fn eval_expr(text: &str, vars: &ExprVars) -> Result<i128, InkError> {
+    eval_decimal_expr(text, vars, 0)
+}
+
+/// 和 eval_expr 相同, 但数字可以带最多 decimals 位小数, 结果放大 10^decimals 倍
+///
+/// 如 decimals 为 18 时 "0.001+id*0.0001" 在 id 为 2 时得到 1.2e15, 乘除的中间结果同样只保留 decimals 位小数
+pub fn eval_decimal_expr(text: &str, vars: &ExprVars, decimals: u32) -> Result<i128, InkError> {
+    let mut parser = Parser {
+        text,
+        chars: text.char_indices().peekable(),
+        vars,
+        decimals,
+        scale: 10i128.pow(decimals),
+    };
+    let value = parser.expr()?;
+    match parser.next_token() {
+        None => Ok(value),
+        Some((index, c)) => Err(parser.error(&format!("第 {} 个字符 '{}' 无法解析", index + 1, c))),
+    }
+}
+
+/// 替换 [expr:表达式] 为表达式的值
+pub fn replace_expr_tokens(text: &str, vars: &ExprVars) -> Result<String, InkError> {
+    if !text.contains("[expr:") {
+        return Ok(text.to_string());
+    }
+    let re = regex::Regex::new(r"\[expr:([^\]]*)]").unwrap();
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for caps in re.captures_iter(text) {
+        let token = caps.get(0).unwrap();
+        result.push_str(&text[last..token.start()]);
+        result.push_str(&eval_expr(&caps[1], vars)?.to_string());
+        last = token.end();
+    }
+    result.push_str(&text[last..]);
+    Ok(result)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+    vars: &'a ExprVars,
+    decimals: u32,
+    /// 10^decimals, 数值都按这个倍数放大成整数
+    scale: i128,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> InkError {
+        InkError::Data(format!("[expr:{}] {}", self.text, message))
+    }
+
+    /// 跳过空白后的下一个字符, 不消耗
+    fn next_token(&mut self) -> Option<(usize, char)> {
+        while let Some((_, c)) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+        self.chars.peek().copied()
+    }
+
+    fn overflow(&self) -> InkError {
+        self.error("计算结果溢出")
+    }
+
+    /// expr = term (('+' | '-') term)*
+    fn expr(&mut self) -> Result<i128, InkError> {
+        let mut value = self.term()?;
+        while let Some((_, op @ ('+' | '-'))) = self.next_token() {
+            self.chars.next();
+            let rhs = self.term()?;
+            value = match op {
+                '+' => value.checked_add(rhs),
+                _ => value.checked_sub(rhs),
+            }
+            .ok_or_else(|| self.overflow())?;
+        }
+        Ok(value)
+    }
+
+    /// term = factor (('*' | '/') factor)*
+    fn term(&mut self) -> Result<i128, InkError> {
+        let mut value = self.factor()?;
+        while let Some((_, op @ ('*' | '/'))) = self.next_token() {
+            self.chars.next();
+            let rhs = self.factor()?;
+            value = if op == '*' {
+                value
+                    .checked_mul(rhs)
+                    .map(|value| value / self.scale)
+                    .ok_or_else(|| self.overflow())?
+            } else if rhs == 0 {
+                return Err(self.error("除数为 0"));
+            } else {
+                value
+                    .checked_mul(self.scale)
+                    .and_then(|value| value.checked_div(rhs))
+                    .ok_or_else(|| self.overflow())?
+            };
+        }
+        Ok(value)
+    }
+
+    /// factor = '-' factor | '(' expr ')' | 数字 | 变量
+    fn factor(&mut self) -> Result<i128, InkError> {
+        match self.next_token() {
+            Some((_, '-')) => {
+                self.chars.next();
+                self.factor()?.checked_neg().ok_or_else(|| self.overflow())
+            }
+            Some((_, '(')) => {
+                self.chars.next();
+                let value = self.expr()?;
+                match self.next_token() {
+                    Some((_, ')')) => {
+                        self.chars.next();
+                        Ok(value)
+                    }
+                    _ => Err(self.error("缺少右括号")),
+                }
+            }
+            Some((_, c)) if c.is_ascii_digit() || c == '.' => {
+                let number = self.take_while(|c| c.is_ascii_digit() || c == '.');
+                self.number(&number)
+            }
+            Some((_, c)) if c.is_ascii_alphabetic() => {
+                let name = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+                let value = match name.as_str() {
+                    "id" => self
+                        .vars
+                        .id
+                        .ok_or_else(|| self.error("data 里没有 id 范围, 不能使用 id"))?,
+                    "nonce" => self.vars.nonce,
+                    _ => return Err(self.error(&format!("不支持的变量: {}(可选 id/nonce)", name))),
+                };
+                i128::from(value)
+                    .checked_mul(self.scale)
+                    .ok_or_else(|| self.overflow())
+            }
+            Some((index, c)) => {
+                Err(self.error(&format!("第 {} 个字符 '{}' 无法解析", index + 1, c)))
+            }
+            None => Err(self.error("表达式不完整")),
+        }
+    }
+
+    /// 解析整数或小数, 小数位数不能超过 decimals
+    fn number(&self, text: &str) -> Result<i128, InkError> {
+        let invalid = || self.error(&format!("不是合法的数字: {}", text));
+        let (integer, fraction) = text.split_once('.').unwrap_or((text, ""));
+        if fraction.contains('.') || (integer.is_empty() && fraction.is_empty()) {
+            return Err(invalid());
+        }
+        if fraction.len() > self.decimals as usize {
+            return Err(match self.decimals {
+                0 => self.error(&format!("不支持小数: {}", text)),
+                decimals => self.error(&format!("最多 {} 位小数: {}", decimals, text)),
+            });
+        }
+        let integer: i128 = match integer {
+            "" => 0,
+            integer => integer.parse().map_err(|_| self.overflow())?,
+        };
+        let fraction: i128 = match self.decimals {
+            0 => 0,
+            decimals => format!("{:0<width$}", fraction, width = decimals as usize)
+                .parse()
+                .map_err(|_| invalid())?,
+        };
+        integer
+            .checked_mul(self.scale)
+            .and_then(|integer| integer.checked_add(fraction))
+            .ok_or_else(|| self.overflow())
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> String {
+        let mut token = String::new();
+        while let Some((_, c)) = self.chars.peek() {
+            if !f(*c) {
+                break;
+            }
+            token.push(*c);
+            self.chars.next();
+        }
+        token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VARS: ExprVars = ExprVars {
+        id: Some(12),
+        nonce: 7,
+    };
+
+    #[test]
+    fn eval_common_expressions() {
+        let eval = |text| eval_expr(text, &VARS).unwrap();
+        assert_eq!(eval("id*10"), 120);
+        assert_eq!(eval(" id * 10 + 5 "), 125);
+        assert_eq!(eval("(id + 3) * 2"), 30);
+        assert_eq!(eval("1000 - nonce*100"), 300);
+        assert_eq!(eval("id / 5"), 2);
+        assert_eq!(eval("-id + 20"), 8);
+        assert_eq!(eval("2 - 3 - 4"), -5);
+        assert_eq!(eval("100 / 10 / 5"), 2);
+
+        assert!(eval_expr("", &VARS).is_err());
+        assert!(eval_expr("id *", &VARS).is_err());
+        assert!(eval_expr("(id + 1", &VARS).is_err());
+        assert!(eval_expr("id 1", &VARS).is_err());
+        assert!(eval_expr("amt * 2", &VARS).is_err());
+        assert!(eval_expr("id * 2", &ExprVars::default()).is_err());
+        assert!(eval_expr("170141183460469231731687303715884105727 + 1", &VARS).is_err());
+    }
+
+    #[test]
+    fn eval_divide_by_zero() {
+        let error = eval_expr("id / (nonce - 7)", &VARS).unwrap_err();
+        assert!(error.to_string().contains("除数为 0"), "{}", error);
+        assert!(eval_expr("1 / 0", &VARS).is_err());
+    }
+
+    #[test]
+    fn eval_decimal_expressions() {
+        let eval = |text, id| {
+            let vars = ExprVars {
+                id: Some(id),
+                nonce: 7,
+            };
+            eval_decimal_expr(text, &vars, 18).unwrap()
+        };
+        let ether = 10i128.pow(18);
+        assert_eq!(eval("0.001+id*0.0001", 0), ether / 1000);
+        assert_eq!(eval("0.001+id*0.0001", 1), ether / 10000 * 11);
+        assert_eq!(eval("0.001+id*0.0001", 25), ether / 10000 * 35);
+        assert_eq!(eval("1.5 * 2", 0), ether * 3);
+        assert_eq!(eval(".5 / 4", 0), ether / 8);
+        assert_eq!(eval("nonce / 2", 0), ether / 2 * 7);
+
+        let vars = ExprVars::default();
+        assert!(eval_decimal_expr("0.1.2", &vars, 18).is_err());
+        assert!(eval_decimal_expr("0.0000000000000000001", &vars, 18).is_err());
+        // 整数表达式不接受小数
+        assert!(eval_expr("1.5", &vars).is_err());
+    }
+
+    #[test]
+    fn replace_tokens() {
+        assert_eq!(
+            replace_expr_tokens(r#"{"id":"12","amt":"[expr:id*10]"}"#, &VARS).unwrap(),
+            r#"{"id":"12","amt":"120"}"#
+        );
+        assert_eq!(
+            replace_expr_tokens("[expr:id][expr:nonce+1]", &VARS).unwrap(),
+            "128"
+        );
+        assert_eq!(replace_expr_tokens("no expr", &VARS).unwrap(), "no expr");
+        assert!(replace_expr_tokens("[expr:1/0]", &VARS).is_err());
+    }
+}
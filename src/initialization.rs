@@ -1,9 +1,16 @@
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
 use anyhow::Result;
 use colored::Colorize;
 use fern::colors::{Color, ColoredLevelConfig};
 use indoc::indoc;
 use log::LevelFilter;
 
+use evm_ink_rs::{Config, RollingFile};
+
+use crate::cli::{Cli, Output};
+
 pub fn print_banner() {
     let banner = indoc! {
 r#"
@@ -17,42 +24,177 @@ r#"
 ╚══════╝  ╚═══╝  ╚═╝     ╚═╝    ╚═╝╚═╝  ╚═══╝╚═╝  ╚═╝
 "#};
 
-    log::info!("{}", format!("{}", banner.green().bold()));
+    log::info!("{}", banner.green().bold());
 }
 
 pub fn log_banner(text: String) {
     let banner = indoc! {r#"========================================================================================================"#};
-    log::info!("{}", format!("{}", banner.green().bold()));
+    log::info!("{}", banner.green().bold());
     let text = format!("{:^width$}", text, width = banner.len());
-    log::info!("{}", format!("{}", text.green().bold()));
-    log::info!("{}", format!("{}", banner.green().bold()));
+    log::info!("{}", text.green().bold());
+    log::info!("{}", banner.green().bold());
+}
+
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}时{}分{}秒", secs / 3600, secs % 3600 / 60, secs % 60)
+    } else if secs >= 60 {
+        format!("{}分{}秒", secs / 60, secs % 60)
+    } else {
+        format!("{:.1}秒", duration.as_secs_f64())
+    }
+}
+
+/// evm_ink_rs 的日志级别, 优先使用命令行的 -q/-v, 其次 log_level 和 RUST_LOG, 都没有时为 Info
+///
+/// 值不合法时回退到 Info, 并返回不合法的值用于提示
+fn resolve_log_level(
+    cli_level: Option<LevelFilter>,
+    log_level: Option<&str>,
+) -> (LevelFilter, Option<String>) {
+    if let Some(level) = cli_level {
+        return (level, None);
+    }
+    let text = log_level
+        .map(str::to_string)
+        .or_else(|| std::env::var("RUST_LOG").ok())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty());
+    match text {
+        Some(text) => match text.parse::<LevelFilter>() {
+            Ok(level) => (level, None),
+            Err(_) => (LevelFilter::Info, Some(text)),
+        },
+        None => (LevelFilter::Info, None),
+    }
+}
+
+/// 终端输出是否带颜色: --no-color 或设置了非空的 NO_COLOR 时关闭, 输出不是终端(被重定向)时也关闭
+pub fn use_color(no_color: bool, no_color_env: Option<&str>, is_terminal: bool) -> bool {
+    !no_color && no_color_env.unwrap_or("").is_empty() && is_terminal
+}
+
+/// 去掉文本里的 ANSI 转义序列, 文件日志不需要颜色
+fn strip_ansi(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' && chars.peek() == Some(&'[') {
+            chars.next();
+            // 跳过参数 直到结尾的字母
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            output.push(c);
+        }
+    }
+    output
 }
 
-pub fn setup_logger() -> Result<()> {
+/// 终端日志默认输出到 stdout, --output json 时改到 stderr 保证 stdout 只有 JSON 结果
+pub fn setup_logger(config: &Config, cli: &Cli) -> Result<()> {
+    let (level, invalid) = resolve_log_level(cli.log_level(), config.log_level.as_deref());
+    let to_stderr = cli.output == Output::Json;
+    let is_terminal = if to_stderr {
+        std::io::stderr().is_terminal()
+    } else {
+        std::io::stdout().is_terminal()
+    };
+    let no_color_env = std::env::var("NO_COLOR").ok();
+    let color = use_color(cli.no_color, no_color_env.as_deref(), is_terminal);
+    // banner 等自己上色的文本也跟着关闭颜色
+    colored::control::set_override(color);
+    let log_file: Box<dyn Write + Send> = if config.log_max_bytes > 0 || config.log_rotate_daily {
+        Box::new(RollingFile::new(
+            "evm_ink.log",
+            config.log_max_bytes,
+            config.log_rotate_daily,
+            config.log_keep_files,
+        )?)
+    } else {
+        Box::new(fern::log_file("evm_ink.log")?)
+    };
     let colors = ColoredLevelConfig {
         trace: Color::Cyan,
         debug: Color::Magenta,
         info: Color::Green,
         warn: Color::Blue,
         error: Color::Red,
-        ..ColoredLevelConfig::new()
     };
 
-    fern::Dispatch::new()
+    let terminal = fern::Dispatch::new()
         .format(move |out, message, record| {
+            if color {
+                out.finish(format_args!(
+                    "{}[{}] {}",
+                    chrono::Local::now().format("[%H:%M:%S]"),
+                    colors.color(record.level()),
+                    message
+                ))
+            } else {
+                out.finish(format_args!(
+                    "{}[{}] {}",
+                    chrono::Local::now().format("[%H:%M:%S]"),
+                    record.level(),
+                    strip_ansi(&message.to_string())
+                ))
+            }
+        })
+        .chain(if to_stderr {
+            fern::Output::stderr("\n")
+        } else {
+            fern::Output::stdout("\n")
+        });
+    let file = fern::Dispatch::new()
+        .format(|out, message, record| {
             out.finish(format_args!(
                 "{}[{}] {}",
                 chrono::Local::now().format("[%H:%M:%S]"),
-                colors.color(record.level()),
-                message
+                record.level(),
+                strip_ansi(&message.to_string())
             ))
         })
-        .chain(std::io::stdout())
-        .chain(fern::log_file("evm_ink.log")?)
+        .chain(log_file);
+
+    fern::Dispatch::new()
+        .chain(terminal)
+        .chain(file)
         .level(log::LevelFilter::Error)
         .level(log::LevelFilter::Warn)
-        .level_for("evm_ink_rs", LevelFilter::Info)
+        .level_for("evm_ink_rs", level)
         .apply()?;
 
+    if let Some(invalid) = invalid {
+        log::warn!(
+            "日志级别不合法: {} 可选 error/warn/info/debug/trace, 使用默认的 info",
+            invalid
+        );
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_policy() {
+        assert!(use_color(false, None, true));
+        assert!(use_color(false, Some(""), true));
+        assert!(!use_color(true, None, true));
+        assert!(!use_color(false, Some("1"), true));
+        // 重定向到文件时不带颜色
+        assert!(!use_color(false, None, false));
+    }
+
+    #[test]
+    fn strip_ansi_codes() {
+        assert_eq!(strip_ansi("plain 文本"), "plain 文本");
+        assert_eq!(strip_ansi("\x1b[1;32mgreen\x1b[0m done"), "green done");
+        assert_eq!(strip_ansi("\x1b[31m错误\x1b[0m"), "错误");
+    }
+}
@@ -1,35 +1,309 @@
-use std::{env, process};
+use std::cmp::min;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use ethers::prelude::U256;
-use ethers::utils::{hex, parse_units};
-use log::error;
-use serde::Deserialize;
+use base64::engine::general_purpose;
+use base64::Engine;
+use ethers::core::k256::ecdsa::SigningKey;
+use ethers::prelude::{
+    Address, BlockNumber, Bytes, Eip1559TransactionRequest, Http, LocalWallet, Middleware,
+    Provider, Signer, TransactionReceipt, TransactionRequest, Wallet, H256, U256,
+};
+use ethers::types::transaction::eip2718::TypedTransaction;
+use ethers::utils::{hex, keccak256, parse_units, to_checksum};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::stream::{self, StreamExt};
+use log::{debug, info, warn};
+use rand::{Rng, RngCore};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{watch, Semaphore};
+use url::Url;
+
+pub use crate::approve::{
+    allowance_calldata, approve_calldata, decode_allowance, ensure_approved, parse_approve_amount,
+    Approve, ALLOWANCE_SELECTOR, APPROVE_SELECTOR,
+};
+pub use crate::cancel::{cancel_nonces, cancel_tx, Cancel};
+pub use crate::config_file::{
+    is_remote_config, load_config, parse_config_text, read_config_text, REMOTE_CONFIG_TIMEOUT,
+};
+pub use crate::encoding::{apply_pipeline, parse_encoding, pipeline_calldata, EncodingStep};
+pub use crate::example::{example_env, example_toml};
+pub use crate::expr::{eval_decimal_expr, eval_expr, replace_expr_tokens, ExprVars};
+pub use crate::metrics::{serve_metrics, Metrics, RunStatus};
+pub use crate::rate_limit::RateLimiter;
+pub use crate::replay::{
+    read_failed_txs, replay, replay_wallet, write_failed_txs, FailedTx, FAILED_FILE,
+};
+pub use crate::rolling::RollingFile;
+pub use crate::rpc::{
+    balance_requests, build_headers, build_http_client, build_provider, bundle_request_body,
+    classify_send_error, flashbots_signature, is_batch_too_large, is_sensitive_header,
+    mask_header_value, nonce_status_requests, parse_balances, parse_batch_response,
+    parse_nonce_status, parse_rpc_url, retry_on_timeout, retry_on_transport, send_adaptive,
+    send_with_split, spawn_new_block_watcher, BatchSender, ChainIdCache, LimitedSender,
+    NonceStatus, RpcPool, SendErrorKind, TxSender,
+};
+pub use crate::send_window::SendWindow;
+pub use crate::transfer::{
+    distribute, source_wallet, sweep_amount, sweep_wallets, top_up_wallets, wallet_addresses,
+    Sweep, Transfer, TRANSFER_GAS_LIMIT,
+};
+pub use crate::wallets::{
+    assign_id_offsets, env_private_keys, execution_addresses, load_keystore_dir, load_wallets_files,
+    parse_wallet_line, parse_wallet_range, parse_wallets_csv, parse_wallets_json,
+    verify_address_matches, WalletFilter, WalletOverride,
+};
+
+mod approve;
+mod cancel;
+mod config_file;
+mod encoding;
+mod example;
+mod expr;
+mod metrics;
+mod rate_limit;
+mod replay;
+mod rolling;
+mod rpc;
+mod send_window;
+mod transfer;
+mod wallets;
+
+/// 库对外的错误类型
+#[derive(Error, Debug)]
+pub enum InkError {
+    #[error("配置错误: {0}")]
+    Config(String),
+    #[error("数据错误: {0}")]
+    Data(String),
+    #[error("RPC 错误: {0}")]
+    Rpc(String),
+    #[error("钱包错误: {0}")]
+    Wallet(String),
+    #[error("IO 错误: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("交易发送错误: {0}")]
+    Send(String),
+    #[error("请求超时: {0}")]
+    Timeout(String),
+}
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Config {
     #[serde(default = "default_prefix")]
     pub prefix: String,
     pub rpc_url: String,
+    /// 使用 wallets_file、keystore_dir 或 PRIVATE_KEY_N 环境变量时可以不填
+    #[serde(default)]
     pub private_key: String,
     #[serde(skip_deserializing)]
     pub address: String,
     pub to_address: Option<String>,
+    #[serde(deserialize_with = "deserialize_gwei")]
     pub max_fee_per_gas: f64,
+    #[serde(default, deserialize_with = "deserialize_optional_gwei")]
     pub max_priority_fee_per_gas: Option<f64>,
-    #[serde(default = "default_gas_limit")]
+    /// priority fee 直接等于 max_fee_per_gas(部分 L2 要求满额小费), 开启时忽略 max_priority_fee_per_gas
+    #[serde(default)]
+    pub priority_equals_max: bool,
+    #[serde(default = "default_gas_limit", deserialize_with = "deserialize_integer")]
     pub gas_limit: u64,
+    /// 最终使用的 gas_limit(手填或估算)统一加上的百分比余量, 向上取整
+    #[serde(default)]
+    pub gas_limit_buffer_percent: f64,
+    /// 加上余量后的 gas_limit 不低于这个值, 防止估算结果离谱
+    pub gas_limit_min: Option<u64>,
+    /// 加上余量后的 gas_limit 不高于这个值
+    pub gas_limit_max: Option<u64>,
+    /// 0 或 auto 时按余额自动计算
+    #[serde(deserialize_with = "deserialize_count")]
     pub count: u64,
+    /// 设置了 fee_calldata 时可以不填
+    #[serde(default)]
     pub data: String,
     #[serde(skip_deserializing)]
     pub hex_text: Option<String>,
     #[serde(skip_deserializing)]
     pub id: Option<crate::Id>,
+    /// 每笔附带的 value(ether), 也可以写 0.001+id*0.0001 这样的表达式 每笔按 id/nonce 计算
     #[serde(default = "default_value")]
-    pub value: f64,
+    pub value: TxValue,
     #[serde(default = "default_batch_size")]
     pub batch_size: u64,
+    /// 批次间隔(秒): 固定值 0.5, 随机范围 0.5-1.5, 或每批乘以倍数 0.1*1.5
     #[serde(default = "default_interval")]
-    pub interval: f64,
+    pub interval: IntervalStrategy,
+    #[serde(default)]
+    pub address_case: AddressCase,
+    #[serde(default, deserialize_with = "deserialize_json_map")]
+    pub placeholders: BTreeMap<String, String>,
+    #[serde(default = "default_strict_utf8")]
+    pub strict_utf8: bool,
+    #[serde(default)]
+    pub compress: Compress,
+    /// 文本铭文的编码管线, 如 template,prefix,hex; 设置后代替 prefix 拼接和 compress
+    pub encoding: Option<String>,
+    /// 多条 data 时按顺序轮流使用
+    #[serde(skip_deserializing)]
+    pub data_list: Vec<String>,
+    #[serde(skip_deserializing)]
+    pub data_index: usize,
+    /// 多钱包打同一个 id 范围时, 第 k 个钱包(从 0 开始)的 id 从起点往后错开 k * id_stride, 0 为不错开
+    #[serde(default)]
+    pub id_stride: u64,
+    /// 按钱包序号算出的 id 偏移, 由 execution_addresses 设置
+    #[serde(skip_deserializing)]
+    pub id_offset: u64,
+    /// 任务条目 JSON 文件, 每条指定自己的 data/to/value, count 按条目数量计算
+    pub items_file: Option<String>,
+    /// 任务条目, 按顺序每条一笔, 用 set_items 设置
+    #[serde(skip_deserializing)]
+    pub items: Vec<MintItem>,
+    /// 签名后预先算出的交易 hash 追加写入的 CSV 文件, 发送前就能对账
+    pub tx_hashes_file: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_ether")]
+    pub reserve_ether: f64,
+    /// 用 estimate_gas 估算 gas_limit 代替固定值
+    #[serde(default)]
+    pub estimate_gas: bool,
+    /// 同一 data 模板每 N 笔重新估算一次 0 为只估算一次
+    #[serde(default)]
+    pub estimate_sample_interval: u64,
+    /// 同时执行的钱包数量
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// 单次 RPC 请求超时时间(秒)
+    #[serde(default = "default_rpc_timeout_secs")]
+    pub rpc_timeout_secs: u64,
+    /// 可重试错误(如超时)的最大重试次数
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// 发送 batch 遇到连接级错误(连不上节点、DNS 失败等)时整批重试的次数
+    #[serde(default = "default_network_retries")]
+    pub network_retries: u32,
+    /// 连接级错误第一次重试前等待的时间(秒), 之后每次翻倍
+    #[serde(default = "default_network_backoff_secs")]
+    pub network_backoff_secs: f64,
+    /// 发送失败时按错误信息里的关键字决定重试/跳过/中止, JSON 格式 如 {"already known":"skip"}
+    #[serde(default, deserialize_with = "deserialize_json_map")]
+    pub error_policy: BTreeMap<String, ErrorAction>,
+    /// RPC 请求附带的 header, JSON 格式 如 {"Authorization":"Bearer xxx"}
+    #[serde(default, deserialize_with = "deserialize_json_map")]
+    pub rpc_headers: BTreeMap<String, String>,
+    /// 节点提示 batch 过大时自动把 batch_size 减半重试, 并沿用到后续批次
+    #[serde(default)]
+    pub adaptive_batch: bool,
+    /// 每批发送后等待回执, 统计实际上链数量和 gas 消耗
+    #[serde(default)]
+    pub wait_receipt: bool,
+    /// 等待一批回执的最长时间(秒)
+    #[serde(default = "default_receipt_timeout_secs")]
+    pub receipt_timeout_secs: u64,
+    /// 每批发送后短暂等待再确认交易已进入节点的 mempool, 没有进入的记为失败
+    #[serde(default)]
+    pub verify_in_mempool: bool,
+    /// ws 节点地址, send_on_new_block 开启时用来订阅新区块
+    pub ws_url: Option<String>,
+    /// 每收到一个新区块发送一批, 代替固定的 interval
+    #[serde(default)]
+    pub send_on_new_block: bool,
+    /// gas 门控(gwei), 每批发送前网络 gas 高于该值时等待
+    #[serde(default, deserialize_with = "deserialize_optional_gwei")]
+    pub max_gas_gate_gwei: Option<f64>,
+    /// gas 高于门控时重查的间隔(秒)
+    #[serde(default = "default_gate_poll_secs")]
+    pub gate_poll_secs: u64,
+    /// 持续监控模式: 只在 gas 不高于 max_gas_gate_gwei 时打一批 watch_batch_size, 直到 count 耗尽
+    #[serde(default)]
+    pub watch_mode: bool,
+    #[serde(default = "default_watch_batch_size")]
+    pub watch_batch_size: u64,
+    /// 监控模式最长运行时间(秒), 0 为不限
+    #[serde(default)]
+    pub watch_timeout_secs: u64,
+    /// 到达该区块高度才开始发送第一批
+    pub start_block: Option<u64>,
+    /// 私有/MEV 保护 RPC, 设置后交易只通过它发送, nonce 等查询仍走 rpc_url
+    pub private_rpc_url: Option<String>,
+    /// bundle 模式: 每批交易打包成一个 bundle 通过 private_rpc_url 发送
+    #[serde(default)]
+    pub bundle_mode: bool,
+    /// eth_sendBundle 或 mev_sendBundle
+    #[serde(default = "default_bundle_method")]
+    pub bundle_method: String,
+    /// bundle 被拒或目标区块没有上链时 以后续区块为目标重投的次数
+    #[serde(default = "default_bundle_retries")]
+    pub bundle_retries: u32,
+    /// 每笔交易 nonce 的增量, 大于 1 时给其它操作预留 nonce
+    #[serde(default = "default_nonce_step")]
+    pub nonce_step: u64,
+    /// 多钱包时一个钱包结束后 进入下一个钱包前等待的时间(秒), 与批次间的 interval 独立
+    #[serde(default)]
+    pub wallet_interval: f64,
+    /// 每个钱包开始前随机等待的秒数范围, 如 0-5, 只写一个数 N 时为 0-N, 并发多钱包时错开请求峰值
+    pub wallet_start_jitter_secs: Option<String>,
+    /// 每天允许发送的时间段(本地时间), 如 22:00-06:00, 不在窗口内时等到窗口开启再发下一批
+    pub send_window: Option<String>,
+    /// 资助钱包私钥, 设置后先给余额低于 min_gas_ether 的钱包补 gas
+    pub funding_private_key: Option<String>,
+    /// 钱包余额低于该值(ether)时由资助钱包补足到该值
+    #[serde(default, deserialize_with = "deserialize_ether")]
+    pub min_gas_ether: f64,
+    /// error/warn/info/debug/trace, 没有设置时读取 RUST_LOG, 默认 info
+    pub log_level: Option<String>,
+    /// 日志文件超过该字节数时切分新文件, 0 为不按大小切分
+    #[serde(default, deserialize_with = "deserialize_integer")]
+    pub log_max_bytes: u64,
+    /// 每天切分一个新的日志文件
+    #[serde(default)]
+    pub log_rotate_daily: bool,
+    /// 切分后保留的旧日志文件数量
+    #[serde(default = "default_log_keep_files")]
+    pub log_keep_files: usize,
+    /// 设置后在该端口提供 Prometheus 格式的 /metrics 和运行状态 /status
+    pub metrics_port: Option<u16>,
+    /// 单独提供 /status 的端口, 与 metrics_port 相同或不设置时共用 metrics_port
+    pub status_port: Option<u16>,
+    /// 设置后每个钱包开打前先给 approve_spender 授权该 ERC-20 代币
+    pub approve_token: Option<String>,
+    pub approve_spender: Option<String>,
+    /// 授权数量(代币最小单位) 或 max
+    pub approve_amount: Option<String>,
+    /// 付费铭文: 设置后交易发到该合约, 附带 fee_value 代替 value
+    pub fee_contract: Option<String>,
+    /// 付费铭文每笔附带的服务费(ether)
+    #[serde(default, deserialize_with = "deserialize_ether")]
+    pub fee_value: f64,
+    /// 付费铭文的十六进制 calldata, 设置后代替 data
+    pub fee_calldata: Option<String>,
+    /// 每批发送前按 feeHistory 的 base fee 收紧 max_fee: min(max_fee, base_fee * 倍数 + priority)
+    #[serde(default)]
+    pub base_fee_clamp_mul: Option<f64>,
+    /// 多链模式: rpc_url 里逗号分隔的每个地址当作一条链, 各自用自己的 chain_id/nonce 打同一份 data
+    #[serde(default)]
+    pub multichain: bool,
+    /// 所有钱包共用的最大在途发送请求数, 0 为不限制
+    #[serde(default)]
+    pub max_inflight: usize,
+    /// 所有钱包合计每秒最多发送的交易数, 0 为不限制
+    #[serde(default)]
+    pub max_tps: f64,
+    /// 启动时不打印 banner 和作者信息, 作为流水线的一步运行时减少输出
+    #[serde(default)]
+    pub quiet_banner: bool,
+    /// 0x data 的前 4 字节(方法选择器)必须与之相同, 防止改 data 时把合约方法敲错
+    pub expected_selector: Option<String>,
 }
 fn default_prefix() -> String {
     "data:,".to_string()
@@ -37,14 +311,497 @@ fn default_prefix() -> String {
 fn default_gas_limit() -> u64 {
     50000
 }
-fn default_value() -> f64 {
-    0.0
+fn default_value() -> TxValue {
+    TxValue::Fixed(0.0)
 }
 fn default_batch_size() -> u64 {
     100
 }
-fn default_interval() -> f64 {
-    0.0
+fn default_interval() -> IntervalStrategy {
+    IntervalStrategy::Fixed(0.0)
+}
+fn default_strict_utf8() -> bool {
+    true
+}
+fn default_concurrency() -> usize {
+    1
+}
+fn default_rpc_timeout_secs() -> u64 {
+    30
+}
+fn default_max_retries() -> u32 {
+    3
+}
+fn default_network_retries() -> u32 {
+    3
+}
+fn default_network_backoff_secs() -> f64 {
+    1.0
+}
+fn default_receipt_timeout_secs() -> u64 {
+    120
+}
+fn default_gate_poll_secs() -> u64 {
+    12
+}
+fn default_watch_batch_size() -> u64 {
+    10
+}
+fn default_bundle_method() -> String {
+    "eth_sendBundle".to_string()
+}
+fn default_bundle_retries() -> u32 {
+    5
+}
+fn default_nonce_step() -> u64 {
+    1
+}
+fn default_log_keep_files() -> usize {
+    5
+}
+
+/// 必填项为空 其余字段与环境变量缺省时的默认值一致
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            prefix: default_prefix(),
+            rpc_url: String::new(),
+            private_key: String::new(),
+            address: String::new(),
+            to_address: None,
+            max_fee_per_gas: 0.0,
+            max_priority_fee_per_gas: None,
+            priority_equals_max: false,
+            gas_limit: default_gas_limit(),
+            gas_limit_buffer_percent: 0.0,
+            gas_limit_min: None,
+            gas_limit_max: None,
+            count: 0,
+            data: String::new(),
+            hex_text: None,
+            id: None,
+            value: default_value(),
+            batch_size: default_batch_size(),
+            interval: default_interval(),
+            address_case: AddressCase::default(),
+            placeholders: BTreeMap::new(),
+            strict_utf8: default_strict_utf8(),
+            compress: Compress::default(),
+            encoding: None,
+            data_list: Vec::new(),
+            data_index: 0,
+            id_stride: 0,
+            id_offset: 0,
+            items_file: None,
+            tx_hashes_file: None,
+            items: Vec::new(),
+            reserve_ether: 0.0,
+            estimate_gas: false,
+            estimate_sample_interval: 0,
+            concurrency: default_concurrency(),
+            rpc_timeout_secs: default_rpc_timeout_secs(),
+            max_retries: default_max_retries(),
+            network_retries: default_network_retries(),
+            network_backoff_secs: default_network_backoff_secs(),
+            error_policy: BTreeMap::new(),
+            rpc_headers: BTreeMap::new(),
+            adaptive_batch: false,
+            wait_receipt: false,
+            receipt_timeout_secs: default_receipt_timeout_secs(),
+            verify_in_mempool: false,
+            ws_url: None,
+            send_on_new_block: false,
+            max_gas_gate_gwei: None,
+            gate_poll_secs: default_gate_poll_secs(),
+            watch_mode: false,
+            watch_batch_size: default_watch_batch_size(),
+            watch_timeout_secs: 0,
+            start_block: None,
+            private_rpc_url: None,
+            bundle_mode: false,
+            bundle_method: default_bundle_method(),
+            bundle_retries: default_bundle_retries(),
+            nonce_step: default_nonce_step(),
+            wallet_interval: 0.0,
+            wallet_start_jitter_secs: None,
+            send_window: None,
+            funding_private_key: None,
+            min_gas_ether: 0.0,
+            log_level: None,
+            log_max_bytes: 0,
+            log_rotate_daily: false,
+            log_keep_files: default_log_keep_files(),
+            metrics_port: None,
+            status_port: None,
+            approve_token: None,
+            approve_spender: None,
+            approve_amount: None,
+            fee_contract: None,
+            fee_value: 0.0,
+            fee_calldata: None,
+            base_fee_clamp_mul: None,
+            multichain: false,
+            max_inflight: 0,
+            max_tps: 0.0,
+            quiet_banner: false,
+            expected_selector: None,
+        }
+    }
+}
+
+/// count 可以填数字 或 auto(等同于 0)
+fn deserialize_count<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    let text = text.trim();
+    if text.eq_ignore_ascii_case("auto") {
+        return Ok(0);
+    }
+    text.parse().map_err(serde::de::Error::custom)
+}
+
+/// 数值后缀支持的单位和对应的小数位数
+const NUMBER_UNITS: [(&str, i32); 7] = [
+    ("wei", 0),
+    ("kwei", 3),
+    ("mwei", 6),
+    ("gwei", 9),
+    ("szabo", 12),
+    ("finney", 15),
+    ("ether", 18),
+];
+
+/// 解析数值配置, 支持科学计数法(1e-6) 下划线分隔(1_000) 和单位后缀(5gwei)
+///
+/// 带单位后缀时换算成 base_unit 表示的数值, 如 base_unit 为 gwei 时 "1ether" 得到 1e9
+pub fn parse_number(text: &str, base_unit: &str) -> Result<f64, String> {
+    let text: String = text.trim().chars().filter(|c| *c != '_').collect();
+    let lower = text.to_lowercase();
+    // gwei 也以 wei 结尾 先匹配更长的单位
+    let mut units = NUMBER_UNITS.to_vec();
+    units.sort_by_key(|(unit, _)| std::cmp::Reverse(unit.len()));
+    let (number, unit_decimals) = match units.iter().find(|(unit, _)| lower.ends_with(unit)) {
+        Some((unit, decimals)) => (&text[..text.len() - unit.len()], Some(*decimals)),
+        None => (text.as_str(), None),
+    };
+    let value: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("不是合法的数值: {}", text))?;
+    let unit_decimals = match unit_decimals {
+        Some(decimals) => decimals,
+        None => return Ok(value),
+    };
+    let base_decimals = NUMBER_UNITS
+        .iter()
+        .find(|(unit, _)| *unit == base_unit)
+        .map(|(_, decimals)| *decimals)
+        .ok_or_else(|| format!("不支持的单位: {}", base_unit))?;
+    // 用整数次幂做乘除 避免 10^-n 本身的精度误差
+    let exponent = unit_decimals - base_decimals;
+    if exponent >= 0 {
+        Ok(value * 10f64.powi(exponent))
+    } else {
+        Ok(value / 10f64.powi(-exponent))
+    }
+}
+
+fn deserialize_number<'de, D>(deserializer: D, base_unit: &str) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    parse_number(&text, base_unit).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_gwei<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_number(deserializer, "gwei")
+}
+
+fn deserialize_ether<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_number(deserializer, "ether")
+}
+
+/// 空字符串视为未填写
+fn deserialize_optional_gwei<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    if text.trim().is_empty() {
+        return Ok(None);
+    }
+    parse_number(&text, "gwei")
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+/// 整数配置 同样支持 1e6 和 1_000_000 的写法
+fn deserialize_integer<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    let value = parse_number(&text, "wei").map_err(serde::de::Error::custom)?;
+    if value < 0.0 || value.fract() != 0.0 || value > u64::MAX as f64 {
+        return Err(serde::de::Error::custom(format!("不是合法的整数: {}", text)));
+    }
+    Ok(value as u64)
+}
+
+/// 环境变量里的 JSON 对象字符串 如 `{"[tick]":"pi"}`, 空字符串视为空 map
+fn deserialize_json_map<'de, D, V>(deserializer: D) -> Result<BTreeMap<String, V>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    V: serde::de::DeserializeOwned,
+{
+    let text = String::deserialize(deserializer)?;
+    if text.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    serde_json::from_str(&text).map_err(serde::de::Error::custom)
+}
+
+/// 替换 [address] 时使用的地址格式
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressCase {
+    /// 全小写
+    Lower,
+    /// EIP-55 checksum 格式
+    Checksum,
+    /// 原样使用 config.address
+    #[default]
+    Raw,
+}
+
+/// 批次之间等待的间隔(秒)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntervalStrategy {
+    Fixed(f64),
+    /// 每批在 [min, max] 内随机
+    Range(f64, f64),
+    /// 第 n 批(从 0 开始)之后等待 initial * ratio^n, ratio 大于 1 时逐渐降速, 小于 1 时逐渐加速
+    Geometric {
+        initial: f64,
+        ratio: f64,
+    },
+}
+
+impl From<f64> for IntervalStrategy {
+    fn from(interval: f64) -> Self {
+        IntervalStrategy::Fixed(interval)
+    }
+}
+
+impl FromStr for IntervalStrategy {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let number = |text: &str| {
+            text.trim()
+                .parse::<f64>()
+                .map_err(|_| format!("interval 不合法: {}", text.trim()))
+        };
+        // 先按单个数字解析, 1e-3 这样的科学计数法里也有 -, 不能当作范围
+        if let Ok(interval) = number(text) {
+            Ok(IntervalStrategy::Fixed(interval))
+        } else if let Some((initial, ratio)) = text.split_once('*') {
+            Ok(IntervalStrategy::Geometric {
+                initial: number(initial)?,
+                ratio: number(ratio)?,
+            })
+        } else if let Some((min, max)) = text.split_once('-').filter(|(min, _)| !min.is_empty()) {
+            Ok(IntervalStrategy::Range(number(min)?, number(max)?))
+        } else {
+            Err(format!("interval 不合法: {}", text))
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for IntervalStrategy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // 钱包 JSON 文件里可以直接写数字
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(f64),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(interval) => Ok(IntervalStrategy::Fixed(interval)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+impl IntervalStrategy {
+    /// 第 batch 批(从 0 开始)发送完后等待的秒数
+    pub fn at(&self, batch: u64) -> f64 {
+        match *self {
+            IntervalStrategy::Fixed(interval) => interval,
+            IntervalStrategy::Range(min, max) if min < max => {
+                rand::thread_rng().gen_range(min..=max)
+            }
+            IntervalStrategy::Range(min, _) => min,
+            IntervalStrategy::Geometric { initial, ratio } => initial * ratio.powf(batch as f64),
+        }
+    }
+
+    /// 从第 next 批起 remaining 批的间隔合计, Range 按中间值估算
+    pub fn eta(&self, next: u64, remaining: u64) -> Duration {
+        let total = match *self {
+            IntervalStrategy::Fixed(interval) => return estimate_eta(interval, remaining),
+            IntervalStrategy::Range(min, max) => return estimate_eta((min + max) / 2.0, remaining),
+            IntervalStrategy::Geometric { initial, ratio: 1.0 } => {
+                initial * remaining as f64
+            }
+            IntervalStrategy::Geometric { initial, ratio } => {
+                initial * ratio.powf(next as f64) * (ratio.powf(remaining as f64) - 1.0)
+                    / (ratio - 1.0)
+            }
+        };
+        if total.is_nan() || total <= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::try_from_secs_f64(total).unwrap_or(Duration::MAX)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let negative = |value: f64| value.is_nan() || value < 0.0;
+        match *self {
+            IntervalStrategy::Fixed(interval) if negative(interval) => {
+                Err(format!("interval 不能小于 0: {}", interval))
+            }
+            IntervalStrategy::Range(min, max) if negative(min) || negative(max) || min > max => {
+                Err(format!("interval 范围不合法: {}-{}", min, max))
+            }
+            IntervalStrategy::Geometric { initial, ratio }
+                if negative(initial) || ratio.is_nan() || ratio <= 0.0 =>
+            {
+                Err(format!(
+                    "interval 初始值不能小于 0 且倍数必须大于 0: {}*{}",
+                    initial, ratio
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 每笔交易附带的 value(ether)
+#[derive(Debug, Clone, PartialEq)]
+pub enum TxValue {
+    Fixed(f64),
+    /// 和 [expr:...] 相同的表达式, 数字可以带小数, 每笔按这笔交易的 id/nonce 计算
+    Expr(String),
+}
+
+impl From<f64> for TxValue {
+    fn from(value: f64) -> Self {
+        TxValue::Fixed(value)
+    }
+}
+
+impl FromStr for TxValue {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        match parse_number(text, "ether") {
+            Ok(value) => Ok(TxValue::Fixed(value)),
+            // 不是数值时按表达式处理, 写错的表达式在 validate 时报错
+            Err(_) if !text.is_empty() => Ok(TxValue::Expr(text.to_string())),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TxValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // 钱包 JSON 文件里可以直接写数字
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(f64),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(value) => Ok(TxValue::Fixed(value)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+impl TxValue {
+    pub fn is_expr(&self) -> bool {
+        matches!(self, TxValue::Expr(_))
+    }
+
+    /// 按这笔交易的 id/nonce 换算成 wei, 表达式结果小于 0 时返回 Err
+    pub fn to_wei(&self, vars: &ExprVars) -> Result<U256, InkError> {
+        match self {
+            TxValue::Fixed(value) => to_wei("value", *value, "ether"),
+            TxValue::Expr(expr) => {
+                let wei = eval_decimal_expr(expr, vars, 18)?;
+                u128::try_from(wei).map(U256::from).map_err(|_| {
+                    InkError::Data(format!("value 表达式 {} 的结果小于 0: {}", expr, wei))
+                })
+            }
+        }
+    }
+}
+
+/// 文本铭文的压缩方式
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Compress {
+    #[default]
+    None,
+    /// 文本 gzip 压缩后拼接在 prefix 之后
+    Gzip,
+}
+
+/// error_policy 里命中关键字的发送错误的处理方式, 没有命中时照常记为失败并继续
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorAction {
+    /// 原样重发这笔已签名的交易, 最多 max_retries 次
+    Retry,
+    /// 忽略这个错误: 不计入失败, 也不导出到 failed.json(如 already known 表示交易其实已在交易池)
+    Skip,
+    /// 记为失败后停止这个钱包剩余的发送
+    Abort,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Asc,
+    Desc,
+}
+
+/// id 填入 data 时的格式
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum IdFormat {
+    #[default]
+    Decimal,
+    /// 小写十六进制, 不足 width 位时补前导零
+    Hex { width: usize },
 }
 
 #[derive(Debug, Clone)]
@@ -53,9 +810,84 @@ pub struct Id {
     pub start_id: Option<u64>,
     pub end_id: Option<u64>,
     pub match_id: String,
+    pub direction: Direction,
+    pub format: IdFormat,
 }
 
-#[derive(Debug)]
+impl Id {
+    /// 起点沿 direction 往后错开 offset, 返回错开后剩余的 id 数量, 超出范围时返回错误
+    ///
+    /// count 为错开前范围内的 id 数量
+    pub fn skip(&mut self, offset: u64, count: u64) -> Result<u64, InkError> {
+        let id = match self.direction {
+            Direction::Asc => self.id.checked_add(offset),
+            Direction::Desc => self.id.checked_sub(offset),
+        };
+        match id {
+            Some(id) if offset < count => {
+                self.id = id;
+                Ok(if count == u64::MAX { count } else { count - offset })
+            }
+            _ => Err(InkError::Config(format!(
+                "id 从 {} 错开 {} 后超出范围: {}",
+                self.id, offset, self.match_id
+            ))),
+        }
+    }
+
+    /// 当前 id 按 format 填入 data 的文本
+    pub fn text(&self) -> String {
+        match self.format {
+            IdFormat::Decimal => self.id.to_string(),
+            IdFormat::Hex { width } => format!("{:0width$x}", self.id, width = width),
+        }
+    }
+}
+
+/// 生成每笔交易 data 时可用的上下文
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TxContext {
+    /// 这笔交易实际使用的 nonce
+    pub nonce: U256,
+}
+
+/// 收服务费的铭文: 交易发到 contract, 每笔附带 value(服务费), calldata 为合约方法调用
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaidInscription {
+    pub contract: Address,
+    pub value: U256,
+    /// 没有设置 fee_calldata 时为 None, 仍使用 data 生成的铭文数据
+    pub calldata: Option<Bytes>,
+}
+
+/// 任务条目列表里的一笔: data 的写法和 Config.data 相同, to/value 不填时使用全局配置
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct MintItem {
+    pub data: String,
+    #[serde(default)]
+    pub to: Option<Address>,
+    /// 附带的 ether
+    #[serde(default)]
+    pub value: Option<f64>,
+}
+
+impl MintItem {
+    pub fn new(data: impl Into<String>) -> Self {
+        MintItem {
+            data: data.into(),
+            to: None,
+            value: None,
+        }
+    }
+
+    pub fn value_wei(&self) -> Result<Option<U256>, InkError> {
+        self.value
+            .map(|value| to_wei("value", value, "ether"))
+            .transpose()
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct GasPrice {
     pub eip1559: bool,
     pub max_fee_per_gas: U256,
@@ -64,22 +896,122 @@ pub struct GasPrice {
 }
 
 impl crate::Config {
-    pub fn get_hex_text(&mut self) -> String {
+    pub fn get_hex_text(&mut self, ctx: &TxContext) -> Result<String, InkError> {
+        if let Some(PaidInscription {
+            calldata: Some(calldata),
+            ..
+        }) = self.paid_inscription()?
+        {
+            return Ok(hex::encode_prefixed(calldata));
+        }
+        self.next_data();
         if self.data.starts_with("0x") {
-            self.data.clone()
+            validate_hex_data(&self.data)?;
+            if let Some(selector) = self.expected_selector()? {
+                check_selector(&self.data, selector)?;
+            }
+            Ok(self.data.clone())
+        } else if let Some(encoded) = self.data.strip_prefix("base64:") {
+            // base64 数据原样解码 不拼接 prefix
+            let bytes = general_purpose::STANDARD
+                .decode(encoded.trim())
+                .map_err(|e| InkError::Data(format!("base64 数据解码失败: {}", e)))?;
+            Ok(hex::encode_prefixed(bytes))
         } else {
-            let data = self.process_text();
-            hex::encode_prefixed(format!("{}{}", self.prefix, data).as_bytes())
+            if let Some(steps) = self.encoding_steps()? {
+                let text = match steps.first() {
+                    Some(EncodingStep::Template) => self.process_text(ctx)?,
+                    _ => self.data.clone(),
+                };
+                return pipeline_calldata(&text, &steps);
+            }
+            let data = self.process_text(ctx)?;
+            match self.compress {
+                Compress::None => Ok(hex::encode_prefixed(
+                    format!("{}{}", self.prefix, data).as_bytes(),
+                )),
+                Compress::Gzip => {
+                    let mut bytes = self.prefix.as_bytes().to_vec();
+                    bytes.extend(gzip_compress(data.as_bytes())?);
+                    Ok(hex::encode_prefixed(bytes))
+                }
+            }
         }
     }
 
-    pub fn process_text(&mut self) -> String {
-        let mut text = self.data.replace("[address]", &self.address);
+    /// 设置任务条目: count 改为条目数量, 不再轮流使用 data_list
+    pub fn set_items(&mut self, items: Vec<MintItem>) {
+        if let Some(item) = items.first() {
+            self.data = item.data.clone();
+            self.count = items.len() as u64;
+        }
+        self.data_list.clear();
+        self.items = items;
+    }
+
+    /// data_list 有多条时 轮到下一条 data
+    fn next_data(&mut self) {
+        if self.data_list.len() > 1 {
+            self.data = self.data_list[self.data_index % self.data_list.len()].clone();
+            self.data_index += 1;
+        }
+    }
+
+    /// encoding 解析出的编码管线, 没有设置时为 None
+    pub fn encoding_steps(&self) -> Result<Option<Vec<EncodingStep>>, InkError> {
+        match self.encoding.as_deref().map(str::trim) {
+            Some(encoding) if !encoding.is_empty() => {
+                parse_encoding(encoding, &self.prefix).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// get_hex_text 生成的是否为 gzip 压缩后的文本铭文
+    pub fn is_gzip_text(&self) -> bool {
+        self.compress == Compress::Gzip
+            && self.encoding.is_none()
+            && !self.data.starts_with("0x")
+            && !self.data.starts_with("base64:")
+    }
+
+    /// 替换 data 里的占位符, [expr:...] 求值失败(如除数为 0)时返回 Err
+    pub fn process_text(&mut self, ctx: &TxContext) -> Result<String, InkError> {
+        let vars = ExprVars {
+            id: self.id.as_ref().map(|id| id.id),
+            nonce: ctx.nonce.low_u64(),
+        };
+        let mut text = replace_expr_tokens(&self.data, &vars)?;
+        text = text.replace("[address]", &self.formatted_address());
+        text = text.replace("[nonce]", &ctx.nonce.to_string());
+        text = replace_timestamp_tokens(&text, SystemTime::now());
+        text = replace_random_tokens(&text)?;
         if let Some(id) = &self.id {
-            text = text.replace(&id.match_id, &id.id.to_string());
+            text = text.replace(&id.match_id, &id.text());
             self.auto_set_id(); // 确保在每次调用 process_text 时调用 auto_set_id
         }
-        text
+        // 静态占位符最后替换 内置的动态占位符优先
+        Ok(replace_placeholders(&text, &self.placeholders))
+    }
+
+    /// 第一笔交易的表达式变量(nonce 按 0 计), 用于预估和校验 value 表达式
+    fn first_expr_vars(&self) -> ExprVars {
+        let id = match &self.id {
+            Some(id) => Some(id.id),
+            None => process_id(&self.data).ok().and_then(|(_, id, _)| id),
+        };
+        ExprVars { id, nonce: 0 }
+    }
+
+    pub fn formatted_address(&self) -> String {
+        match self.address_case {
+            AddressCase::Lower => self.address.to_lowercase(),
+            AddressCase::Checksum => match self.address.parse::<Address>() {
+                Ok(address) => to_checksum(&address, None),
+                Err(_) => self.address.clone(),
+            },
+            AddressCase::Raw => self.address.clone(),
+        }
     }
 
     pub fn set_id(&mut self, _id: u64) {
@@ -90,98 +1022,5229 @@ impl crate::Config {
 
     fn auto_set_id(&mut self) {
         let id = self.id.as_ref().unwrap();
-        match id.start_id {
-            Some(_) => {
-                self.set_id(id.id + 1);
+        match id.direction {
+            Direction::Asc => {
+                self.set_id(id.id.saturating_add(1));
             }
-            None => {
-                self.set_id(id.id - 1);
+            Direction::Desc => {
+                self.set_id(id.id.saturating_sub(1));
+            }
+        }
+    }
+
+    /// 多链模式下每条链一份配置(rpc_url 只保留这条链的地址), 否则只有自己一份
+    pub fn chain_configs(&self) -> Vec<Config> {
+        if !self.multichain {
+            return vec![self.clone()];
+        }
+        self.rpc_urls()
+            .into_iter()
+            .map(|rpc_url| Config {
+                rpc_url: rpc_url.to_string(),
+                ..self.clone()
+            })
+            .collect()
+    }
+
+    /// rpc_url 可以用逗号分隔填多个, 批量发送时按延迟加权选择
+    pub fn rpc_urls(&self) -> Vec<&str> {
+        let urls: Vec<&str> = self
+            .rpc_url
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .collect();
+        if urls.is_empty() {
+            vec![self.rpc_url.as_str()]
+        } else {
+            urls
+        }
+    }
+
+    /// 发送交易使用的 RPC, 设置了 private_rpc_url 时只用它
+    pub fn send_urls(&self) -> Vec<&str> {
+        match self.private_rpc_url.as_deref().map(str::trim) {
+            Some(url) if !url.is_empty() => vec![url],
+            _ => self.rpc_urls(),
+        }
+    }
+
+    /// 非空的资助钱包私钥
+    pub fn funding_key(&self) -> Option<&str> {
+        self.funding_private_key
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    }
+
+    /// expected_selector 解析出的 4 字节选择器, 没有设置时为 None
+    pub fn expected_selector(&self) -> Result<Option<[u8; 4]>, InkError> {
+        match self.expected_selector.as_deref().map(str::trim) {
+            Some(selector) if !selector.is_empty() => parse_selector(selector).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// 付费铭文设置, 没有设置 fee_contract 时为 None
+    pub fn paid_inscription(&self) -> Result<Option<PaidInscription>, InkError> {
+        let contract = match self.fee_contract.as_deref().map(str::trim) {
+            Some(contract) if !contract.is_empty() => Address::from_str(contract).map_err(|e| {
+                InkError::Config(format!("fee_contract 不是合法地址: {}: {}", contract, e))
+            })?,
+            _ => return Ok(None),
+        };
+        let calldata = match self.fee_calldata.as_deref().map(str::trim) {
+            Some(calldata) if !calldata.is_empty() => Some(Bytes::from(
+                decode_hex_bytes(calldata.trim_start_matches("0x"))
+                    .map_err(|e| InkError::Config(format!("fee_calldata 不合法: {}", e)))?,
+            )),
+            _ => None,
+        };
+        Ok(Some(PaidInscription {
+            contract,
+            value: to_wei("fee_value", self.fee_value, "ether")?,
+            calldata,
+        }))
+    }
+
+    /// 开打前的 approve 设置, 没有设置 approve_token 时为 None
+    pub fn approve(&self) -> Result<Option<Approve>, InkError> {
+        let token = match self.approve_token.as_deref().map(str::trim) {
+            Some(token) if !token.is_empty() => token,
+            _ => return Ok(None),
+        };
+        let parse_address = |name: &str, text: Option<&str>| {
+            let text = text.map(str::trim).unwrap_or("");
+            if text.is_empty() {
+                return Err(InkError::Config(format!("设置 approve_token 时需要设置 {}", name)));
+            }
+            Address::from_str(text)
+                .map_err(|e| InkError::Config(format!("{} 不是合法地址: {}: {}", name, text, e)))
+        };
+        Ok(Some(Approve {
+            token: parse_address("approve_token", Some(token))?,
+            spender: parse_address("approve_spender", self.approve_spender.as_deref())?,
+            amount: parse_approve_amount(self.approve_amount.as_deref().ok_or_else(|| {
+                InkError::Config("设置 approve_token 时需要设置 approve_amount".to_string())
+            })?)?,
+        }))
+    }
+
+    pub fn rpc_timeout(&self) -> Duration {
+        Duration::from_secs(self.rpc_timeout_secs)
+    }
+
+    /// wallet_start_jitter_secs 解析出的 (最小, 最大) 秒数, 没有设置时为 None
+    pub fn start_jitter_range(&self) -> Result<Option<(f64, f64)>, InkError> {
+        let text = match self.wallet_start_jitter_secs.as_deref().map(str::trim) {
+            Some(text) if !text.is_empty() => text,
+            _ => return Ok(None),
+        };
+        let invalid = || InkError::Config(format!("wallet_start_jitter_secs 不合法: {}", text));
+        let number = |text: &str| text.trim().parse::<f64>().map_err(|_| invalid());
+        let (min, max) = match text.split_once('-') {
+            Some((min, max)) => (number(min)?, number(max)?),
+            None => (0.0, number(text)?),
+        };
+        if !min.is_finite() || !max.is_finite() || min < 0.0 || min > max {
+            return Err(invalid());
+        }
+        Ok(Some((min, max)))
+    }
+
+    /// 这个钱包开始前随机等待的时间, 没有设置 wallet_start_jitter_secs 时为 0
+    pub fn start_jitter(&self) -> Duration {
+        match self.start_jitter_range() {
+            Ok(Some((min, max))) if min < max => {
+                Duration::from_secs_f64(rand::thread_rng().gen_range(min..=max))
             }
+            Ok(Some((min, _))) => Duration::from_secs_f64(min),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// 解析 send_window, 没有设置时为 None
+    pub fn send_window(&self) -> Result<Option<SendWindow>, InkError> {
+        match self.send_window.as_deref().map(str::trim) {
+            Some(text) if !text.is_empty() => text.parse().map(Some),
+            _ => Ok(None),
         }
     }
 
-    pub fn init_gas_price(&self) -> crate::GasPrice {
-        let max_fee_per_gas = U256::from(parse_units(self.max_fee_per_gas, "gwei").unwrap());
+    /// max_inflight 容量的信号量, 不限制时为 None
+    pub fn inflight_semaphore(&self) -> Option<Arc<Semaphore>> {
+        (self.max_inflight > 0).then(|| Arc::new(Semaphore::new(self.max_inflight)))
+    }
+
+    /// max_tps 的令牌桶, 不限制时为 None
+    pub fn rate_limiter(&self) -> Option<Arc<RateLimiter>> {
+        (self.max_tps > 0.0).then(|| Arc::new(RateLimiter::new(self.max_tps)))
+    }
+
+    /// count 为 0 时按余额自动计算笔数
+    pub fn is_auto_count(&self) -> bool {
+        self.count == 0
+    }
+
+    /// 发送错误命中的 error_policy, 关键字不区分大小写, 同时命中多个时取最长的关键字
+    pub fn error_action(&self, error: &str) -> Option<ErrorAction> {
+        let error = error.to_lowercase();
+        self.error_policy
+            .iter()
+            .filter(|(keyword, _)| !keyword.is_empty() && error.contains(&keyword.to_lowercase()))
+            .max_by_key(|(keyword, _)| keyword.len())
+            .map(|(_, action)| *action)
+    }
+
+    /// 加上 gas_limit_buffer_percent 余量后的手填 gas_limit
+    pub fn buffered_gas_limit(&self) -> u64 {
+        self.resolve_gas_limit(self.gas_limit)
+    }
+
+    /// 交易最终使用的 gas_limit: 手填或估算的值加上余量后 clamp 到 gas_limit_min..=gas_limit_max
+    pub fn resolve_gas_limit(&self, gas_limit: u64) -> u64 {
+        let gas_limit = apply_gas_buffer(gas_limit, self.gas_limit_buffer_percent);
+        let gas_limit = gas_limit.max(self.gas_limit_min.unwrap_or(0));
+        gas_limit.min(self.gas_limit_max.unwrap_or(u64::MAX))
+    }
+
+    pub fn reserve_wei(&self) -> Result<U256, InkError> {
+        to_wei("reserve_ether", self.reserve_ether, "ether")
+    }
+
+    pub fn init_gas_price(&self) -> Result<crate::GasPrice, InkError> {
+        let max_fee_per_gas = to_wei("max_fee_per_gas", self.max_fee_per_gas, "gwei")?;
         let max_priority_fee_per_gas = match self.max_priority_fee_per_gas {
-            Some(priority_fee) => U256::from(parse_units(priority_fee, "gwei").unwrap()),
+            _ if self.priority_equals_max => max_fee_per_gas,
+            Some(priority_fee) => to_wei("max_priority_fee_per_gas", priority_fee, "gwei")?,
             None => U256::from(0),
         };
-        let value = U256::from(parse_units(self.value, "ether").unwrap());
+        // 付费铭文使用 fee_value 作为每笔的 value, value 为表达式时按第一笔计算
+        let value = match self.paid_inscription()? {
+            Some(paid) => paid.value,
+            None => self.value.to_wei(&self.first_expr_vars())?,
+        };
 
-        crate::GasPrice {
-            eip1559: self.max_priority_fee_per_gas.is_some(),
+        Ok(crate::GasPrice {
+            eip1559: self.priority_equals_max || self.max_priority_fee_per_gas.is_some(),
             max_fee_per_gas,
             max_priority_fee_per_gas,
             value,
-        }
+        })
     }
 }
 
-pub fn execution_addresses(config: Config) -> Vec<Config> {
-    if let Some(wallets_file) = env::var("wallets_file").ok().filter(|s| !s.is_empty()) {
-        let wallets = std::fs::read_to_string(&wallets_file).unwrap_or_else(|_| {
-            error!("读取文件失败: {}", wallets_file);
-            process::exit(1);
-        });
+/// 把配置里的 ether/gwei 数值转换为 wei, 负数或超出 U256 范围时返回错误
+pub fn to_wei(name: &str, amount: f64, units: &str) -> Result<U256, InkError> {
+    if !amount.is_finite() || amount < 0.0 {
+        return Err(InkError::Config(format!("{} 不是合法的非负数: {}", name, amount)));
+    }
+    parse_units(amount, units)
+        .map(U256::from)
+        .map_err(|e| InkError::Config(format!("{} 过大无法转换为 wei: {} ({})", name, amount, e)))
+}
 
-        let wallets: Vec<Config> = wallets
-            .lines()
-            .map(|line| {
-                let parts: Vec<&str> = line.split("----").collect();
-                let private_key = parts[parts.len() - 1].trim();
-                let mut config = config.clone();
-                config.private_key = private_key.to_string();
-                config
-            })
-            .collect();
+/// gas_limit 乘上 (1 + percent / 100) 后向上取整, 溢出时取 u64::MAX
+pub fn apply_gas_buffer(gas_limit: u64, percent: f64) -> u64 {
+    if percent.is_nan() || percent <= 0.0 {
+        return gas_limit;
+    }
+    // 先把余量舍到 1e-6 再向上取整, 避免 0.1% 这类小数百分比的浮点误差多出 1
+    let buffer = (gas_limit as f64 * percent / 100.0 * 1e6).round() / 1e6;
+    gas_limit.saturating_add(buffer.ceil() as u64)
+}
 
-        wallets
+/// 按 EIP-2028 计算交易的固有 gas: 基础 21000 加上 calldata 每个零字节 4、非零字节 16
+///
+/// to 是 EOA 时交易不执行代码, 实际消耗的就是这个值
+pub fn calldata_intrinsic_gas(data: &[u8]) -> u64 {
+    let zeros = data.iter().filter(|byte| **byte == 0).count() as u64;
+    let non_zeros = data.len() as u64 - zeros;
+    21000 + zeros * 4 + non_zeros * 16
+}
+
+/// 余额扣除保留部分后 按每笔最大花费 gas_limit * max_fee + value 计算最多能打多少笔
+pub fn max_mint_count(balance: U256, reserve: U256, gas_limit: u64, gas_price: &GasPrice) -> u64 {
+    if balance <= reserve {
+        return 0;
+    }
+    let cost = U256::from(gas_limit) * gas_price.max_fee_per_gas + gas_price.value;
+    if cost.is_zero() {
+        return u64::MAX;
+    }
+    let count = (balance - reserve) / cost;
+    if count > U256::from(u64::MAX) {
+        u64::MAX
     } else {
-        vec![config]
+        count.as_u64()
     }
 }
 
-pub fn decode_hex(hex: &str) -> Result<String, Box<dyn std::error::Error>> {
-    let bytes = hex::decode(hex)?;
-    let text = String::from_utf8(bytes)?;
-    Ok(text)
+/// 开打前一个钱包的余额和按余额预估的可打笔数
+#[derive(Debug, Clone, PartialEq)]
+pub struct BalancePreview {
+    pub address: Address,
+    pub balance: U256,
+    /// 按 max_mint_count 预估最多能打的笔数
+    pub affordable: u64,
+    /// 计划打的笔数, 按余额自动计算时等于 affordable
+    pub planned: u64,
 }
 
-pub fn process_id(text: &str) -> (Option<Id>, Option<u64>, u64) {
-    let re = regex::Regex::new(r"\[(\d+)?-(\d+)?]").unwrap();
-    if let Some(caps) = re.captures(&text) {
-        let match_id = caps.get(0).unwrap().as_str().to_string();
-        let start_id: Option<u64> = caps.get(1).and_then(|m| m.as_str().parse().ok());
-        let end_id: Option<u64> = caps.get(2).and_then(|m| m.as_str().parse().ok());
-        // 如果 start_id 不为 None 设置id 为 start_id 否则设置 id 为 end_id
-        if start_id.is_none() {
-            (
-                Some(Id {
-                    id: end_id.unwrap(),
-                    start_id,
-                    end_id,
-                    match_id,
-                }),
-                end_id,
-                u64::MAX,
-            )
+impl BalancePreview {
+    /// 按钱包自己的 gas 设置、value 和 reserve_ether 预估
+    pub fn new(config: &Config, address: Address, balance: U256) -> Result<Self, InkError> {
+        let affordable = max_mint_count(
+            balance,
+            config.reserve_wei()?,
+            config.buffered_gas_limit(),
+            &config.init_gas_price()?,
+        );
+        let planned = if config.is_auto_count() {
+            affordable
         } else {
-            (
-                Some(Id {
-                    id: start_id.unwrap(),
-                    start_id,
-                    end_id,
-                    match_id,
-                }),
-                start_id,
-                if end_id.is_none() {
-                    u64::MAX
-                } else {
-                    end_id.unwrap() - start_id.unwrap() + 1
-                },
-            )
-        }
-    } else {
-        (None, None, u64::MAX)
+            config.count
+        };
+        Ok(BalancePreview {
+            address,
+            balance,
+            affordable,
+            planned,
+        })
+    }
+
+    /// 余额是否够打完计划的笔数
+    pub fn is_enough(&self) -> bool {
+        self.affordable >= self.planned
+    }
+}
+
+/// 在代码里构造 Config, build 时填充默认值并校验必填项
+///
+/// ```no_run
+/// let config = evm_ink_rs::Config::builder()
+///     .rpc_url("https://ethereum-goerli.publicnode.com")
+///     .private_key("0x...")
+///     .max_fee_per_gas(130.0)
+///     .data(r#"{"p":"erc-20","op":"mint","tick":"eths","amt":"1000"}"#)
+///     .count(1)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+    config: Config,
+    count: Option<u64>,
+    max_fee_per_gas: Option<f64>,
+}
+
+impl ConfigBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rpc_url(mut self, rpc_url: impl Into<String>) -> Self {
+        self.config.rpc_url = rpc_url.into();
+        self
+    }
+
+    pub fn private_key(mut self, private_key: impl Into<String>) -> Self {
+        self.config.private_key = private_key.into();
+        self
+    }
+
+    pub fn data(mut self, data: impl Into<String>) -> Self {
+        self.config.data = data.into();
+        self
+    }
+
+    /// 按任务条目逐条发送, count 由条目数量决定
+    pub fn items(mut self, items: Vec<MintItem>) -> Self {
+        self.count = Some(items.len() as u64);
+        self.config.set_items(items);
+        self
+    }
+
+    pub fn count(mut self, count: u64) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    pub fn max_fee_per_gas(mut self, max_fee_per_gas: f64) -> Self {
+        self.max_fee_per_gas = Some(max_fee_per_gas);
+        self
+    }
+
+    pub fn max_priority_fee_per_gas(mut self, max_priority_fee_per_gas: f64) -> Self {
+        self.config.max_priority_fee_per_gas = Some(max_priority_fee_per_gas);
+        self
+    }
+
+    pub fn to_address(mut self, to_address: impl Into<String>) -> Self {
+        self.config.to_address = Some(to_address.into());
+        self
+    }
+
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.config.prefix = prefix.into();
+        self
+    }
+
+    pub fn gas_limit(mut self, gas_limit: u64) -> Self {
+        self.config.gas_limit = gas_limit;
+        self
+    }
+
+    pub fn value(mut self, value: impl Into<TxValue>) -> Self {
+        self.config.value = value.into();
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: u64) -> Self {
+        self.config.batch_size = batch_size;
+        self
+    }
+
+    pub fn interval(mut self, interval: impl Into<IntervalStrategy>) -> Self {
+        self.config.interval = interval.into();
+        self
+    }
+
+    pub fn address_case(mut self, address_case: AddressCase) -> Self {
+        self.config.address_case = address_case;
+        self
+    }
+
+    pub fn placeholder(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.placeholders.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn strict_utf8(mut self, strict_utf8: bool) -> Self {
+        self.config.strict_utf8 = strict_utf8;
+        self
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.config.concurrency = concurrency;
+        self
+    }
+
+    pub fn rpc_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.config.rpc_headers.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn adaptive_batch(mut self, adaptive_batch: bool) -> Self {
+        self.config.adaptive_batch = adaptive_batch;
+        self
+    }
+
+    pub fn wait_receipt(mut self, wait_receipt: bool) -> Self {
+        self.config.wait_receipt = wait_receipt;
+        self
+    }
+
+    pub fn compress(mut self, compress: Compress) -> Self {
+        self.config.compress = compress;
+        self
+    }
+
+    pub fn encoding(mut self, encoding: impl Into<String>) -> Self {
+        self.config.encoding = Some(encoding.into());
+        self
+    }
+
+    pub fn build(self) -> Result<Config, InkError> {
+        let mut config = self.config;
+        let mut missing = Vec::new();
+        if config.rpc_url.is_empty() {
+            missing.push("rpc_url");
+        }
+        if config.private_key.is_empty() {
+            missing.push("private_key");
+        }
+        if config.data.is_empty() {
+            missing.push("data");
+        }
+        if self.count.is_none() {
+            missing.push("count");
+        }
+        if self.max_fee_per_gas.is_none() {
+            missing.push("max_fee_per_gas");
+        }
+        if !missing.is_empty() {
+            return Err(InkError::Config(format!("缺少必填项: {}", missing.join(", "))));
+        }
+        config.count = self.count.unwrap();
+        config.max_fee_per_gas = self.max_fee_per_gas.unwrap();
+        config
+            .validate()
+            .map_err(|errors| InkError::Config(errors.join("; ")))?;
+        Ok(config)
+    }
+}
+
+impl Config {
+    pub fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+
+    /// 集中检查配置, 一次返回所有问题
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        let paid = self.paid_inscription();
+        if let Err(e) = &paid {
+            errors.push(e.to_string());
+        }
+        let has_calldata = paid.is_ok_and(|paid| paid.is_some_and(|paid| paid.calldata.is_some()));
+        if self.data.is_empty() && !has_calldata {
+            errors.push("data 不能为空".to_string());
+        } else if let Err(e) = process_id(&self.data) {
+            errors.push(format!("data 中的 id 范围不合法: {}", e));
+        }
+        if let Err(e) = replace_random_tokens(&self.data) {
+            errors.push(e.to_string());
+        }
+        if !self.items.is_empty() {
+            if has_calldata {
+                errors.push("设置 items_file 时不能同时设置 fee_calldata".to_string());
+            }
+            for (index, item) in self.items.iter().enumerate() {
+                if item.data.is_empty() {
+                    errors.push(format!("第 {} 条任务的 data 不能为空", index + 1));
+                }
+                if let Err(e) = item.value_wei() {
+                    errors.push(format!("第 {} 条任务的 value 不合法: {}", index + 1, e));
+                }
+            }
+        }
+        let selector = self.expected_selector();
+        if let Err(e) = &selector {
+            errors.push(e.to_string());
+        }
+        if self.data.starts_with("0x") {
+            let checked = validate_hex_data(&self.data).and_then(|_| match selector {
+                Ok(Some(selector)) => check_selector(&self.data, selector),
+                _ => Ok(()),
+            });
+            if let Err(e) = checked {
+                errors.push(e.to_string());
+            }
+        }
+        for rpc_url in self.rpc_urls() {
+            if let Err(e) = Url::parse(rpc_url) {
+                errors.push(format!("rpc_url 不合法: {}: {}", rpc_url, e));
+            }
+        }
+        if let Some(private_rpc_url) = self.private_rpc_url.as_deref().filter(|s| !s.is_empty()) {
+            if let Err(e) = Url::parse(private_rpc_url.trim()) {
+                errors.push(format!("private_rpc_url 不合法: {}: {}", private_rpc_url, e));
+            }
+        }
+        if let Err(e) = build_headers(&self.rpc_headers) {
+            errors.push(e.to_string());
+        }
+        if self.gas_limit == 0 {
+            errors.push("gas_limit 必须大于 0".to_string());
+        }
+        if self.gas_limit_buffer_percent.is_nan() || self.gas_limit_buffer_percent < 0.0 {
+            errors.push(format!(
+                "gas_limit_buffer_percent 不能小于 0: {}",
+                self.gas_limit_buffer_percent
+            ));
+        }
+        if let (Some(min), Some(max)) = (self.gas_limit_min, self.gas_limit_max) {
+            if min > max {
+                errors.push(format!(
+                    "gas_limit_min({}) 不能大于 gas_limit_max({})",
+                    min, max
+                ));
+            }
+        }
+        if self.gas_limit_max == Some(0) {
+            errors.push("gas_limit_max 必须大于 0".to_string());
+        }
+        if self.batch_size == 0 {
+            errors.push("batch_size 必须大于 0".to_string());
+        }
+        if self.concurrency == 0 {
+            errors.push("concurrency 必须大于 0".to_string());
+        }
+        if self.nonce_step == 0 {
+            errors.push("nonce_step 必须大于 0".to_string());
+        }
+        if self.rpc_timeout_secs == 0 {
+            errors.push("rpc_timeout_secs 必须大于 0".to_string());
+        }
+        if self.max_fee_per_gas.is_nan() || self.max_fee_per_gas < 0.0 {
+            errors.push(format!("max_fee_per_gas 不能小于 0: {}", self.max_fee_per_gas));
+        }
+        if let Some(priority_fee) = self.max_priority_fee_per_gas {
+            if priority_fee.is_nan() || priority_fee < 0.0 {
+                errors.push(format!("max_priority_fee_per_gas 不能小于 0: {}", priority_fee));
+            } else if priority_fee > self.max_fee_per_gas {
+                errors.push(format!(
+                    "max_priority_fee_per_gas({}) 不能大于 max_fee_per_gas({})",
+                    priority_fee, self.max_fee_per_gas
+                ));
+            }
+        }
+        match self.encoding_steps() {
+            Ok(Some(_)) if self.compress != Compress::None => {
+                errors.push(
+                    "设置 encoding 时不能同时设置 compress, 可以在 encoding 里加 gzip".to_string(),
+                );
+            }
+            Err(e) => errors.push(e.to_string()),
+            _ => {}
+        }
+        if self.max_tps.is_nan() || self.max_tps < 0.0 {
+            errors.push(format!("max_tps 不能小于 0: {}", self.max_tps));
+        }
+        if let Some(mul) = self.base_fee_clamp_mul {
+            if mul.is_nan() || mul <= 0.0 {
+                errors.push(format!("base_fee_clamp_mul 必须大于 0: {}", mul));
+            }
+        }
+        if let Some(gate) = self.max_gas_gate_gwei {
+            if gate.is_nan() || gate < 0.0 {
+                errors.push(format!("max_gas_gate_gwei 不能小于 0: {}", gate));
+            }
+            if self.gate_poll_secs == 0 {
+                errors.push("gate_poll_secs 必须大于 0".to_string());
+            }
+        }
+        if self.send_on_new_block && self.ws_url.as_deref().unwrap_or("").trim().is_empty() {
+            errors.push("send_on_new_block 需要设置 ws_url".to_string());
+        }
+        if self.bundle_mode {
+            if self.private_rpc_url.as_deref().unwrap_or("").is_empty() {
+                errors.push("bundle_mode 需要设置 private_rpc_url(bundle 节点)".to_string());
+            }
+            if !matches!(self.bundle_method.as_str(), "eth_sendBundle" | "mev_sendBundle") {
+                errors.push(format!(
+                    "bundle_method 只支持 eth_sendBundle 或 mev_sendBundle: {}",
+                    self.bundle_method
+                ));
+            }
+        }
+        if self.watch_mode {
+            if self.max_gas_gate_gwei.is_none() {
+                errors.push("watch_mode 需要设置 max_gas_gate_gwei".to_string());
+            }
+            if self.watch_batch_size == 0 {
+                errors.push("watch_batch_size 必须大于 0".to_string());
+            }
+        }
+        if self.reserve_ether.is_nan() || self.reserve_ether < 0.0 {
+            errors.push(format!("reserve_ether 不能小于 0: {}", self.reserve_ether));
+        }
+        match &self.value {
+            TxValue::Fixed(value) if value.is_nan() || *value < 0.0 => {
+                errors.push(format!("value 不能小于 0: {}", value));
+            }
+            TxValue::Expr(_) => {
+                if let Err(e) = self.value.to_wei(&self.first_expr_vars()) {
+                    errors.push(format!("value 表达式不合法: {}", e));
+                }
+            }
+            _ => {}
+        }
+        if let Err(e) = self.interval.validate() {
+            errors.push(e);
+        }
+        if self.id_stride > 0 && !self.is_auto_count() && self.id_stride < self.count {
+            errors.push(format!(
+                "id_stride({}) 小于 count({}) 时相邻钱包的 id 段会重叠",
+                self.id_stride, self.count
+            ));
+        }
+        if self.error_policy.keys().any(|keyword| keyword.trim().is_empty()) {
+            errors.push("error_policy 的关键字不能为空".to_string());
+        }
+        if self.network_backoff_secs.is_nan() || self.network_backoff_secs < 0.0 {
+            errors.push(format!(
+                "network_backoff_secs 不能小于 0: {}",
+                self.network_backoff_secs
+            ));
+        }
+        if let Err(e) = self.start_jitter_range() {
+            errors.push(e.to_string());
+        }
+        if let Err(e) = self.send_window() {
+            errors.push(e.to_string());
+        }
+        if self.wallet_interval.is_nan() || self.wallet_interval < 0.0 {
+            errors.push(format!("wallet_interval 不能小于 0: {}", self.wallet_interval));
+        }
+        if let Some(funding_private_key) = self.funding_key() {
+            if funding_private_key.parse::<LocalWallet>().is_err() {
+                errors.push("funding_private_key 不是合法的私钥".to_string());
+            }
+            if self.min_gas_ether.is_nan() || self.min_gas_ether <= 0.0 {
+                errors.push(format!(
+                    "设置 funding_private_key 时 min_gas_ether 必须大于 0: {}",
+                    self.min_gas_ether
+                ));
+            }
+        }
+        if let Err(e) = self.approve() {
+            errors.push(e.to_string());
+        }
+        if let Some(to_address) = self.to_address.as_deref().filter(|s| !s.is_empty()) {
+            if let Err(e) = parse_recipients(to_address) {
+                errors.push(e.to_string());
+            }
+        }
+        if self.fee_value.is_nan() || self.fee_value < 0.0 {
+            errors.push(format!("fee_value 不能小于 0: {}", self.fee_value));
+        }
+        if self.multichain && !self.private_rpc_url.as_deref().unwrap_or("").is_empty() {
+            errors.push(
+                "multichain 模式下每条链都用自己的 rpc_url 发送 不能设置 private_rpc_url"
+                    .to_string(),
+            );
+        }
+        if errors.is_empty() {
+            // 前面已经检查过负数 这里只会剩下超出 wei 范围的情况
+            if let Err(e) = self.init_gas_price().and_then(|_| self.reserve_wei()) {
+                errors.push(e.to_string());
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// 替换 [timestamp](秒) 和 [timestamp_ms](毫秒) 为 now 对应的 Unix 时间
+///
+/// 每笔交易生成 data 时各自取当下时间, 所以同一笔交易重新生成(比如重试)时时间戳会变化
+pub fn replace_timestamp_tokens(text: &str, now: SystemTime) -> String {
+    if !text.contains("[timestamp") {
+        return text.to_string();
+    }
+    let since_epoch = now.duration_since(UNIX_EPOCH).unwrap_or_default();
+    text.replace("[timestamp_ms]", &since_epoch.as_millis().to_string())
+        .replace("[timestamp]", &since_epoch.as_secs().to_string())
+}
+
+/// [random] 默认生成的随机字节数
+pub const DEFAULT_RANDOM_BYTES: usize = 8;
+/// [random:N] 的 N 最大值
+pub const MAX_RANDOM_BYTES: usize = 1024;
+
+/// 替换 [random:N] 为 N 字节的随机十六进制串(不带 0x), [random] 使用默认长度
+///
+/// N 超过 MAX_RANDOM_BYTES 时返回 Err
+pub fn replace_random_tokens(text: &str) -> Result<String, InkError> {
+    if !text.contains("[random") {
+        return Ok(text.to_string());
+    }
+    let re = regex::Regex::new(r"\[random(?::(\d+))?]").unwrap();
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for caps in re.captures_iter(text) {
+        let token = caps.get(0).unwrap();
+        let len = match caps.get(1) {
+            Some(m) => m
+                .as_str()
+                .parse::<usize>()
+                .ok()
+                .filter(|len| *len <= MAX_RANDOM_BYTES)
+                .ok_or_else(|| {
+                    InkError::Data(format!(
+                        "{} 的字节数不能超过 {}",
+                        token.as_str(),
+                        MAX_RANDOM_BYTES
+                    ))
+                })?,
+            None => DEFAULT_RANDOM_BYTES,
+        };
+        let mut bytes = vec![0u8; len];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        out.push_str(&text[last..token.start()]);
+        out.push_str(&hex::encode(bytes));
+        last = token.end();
+    }
+    out.push_str(&text[last..]);
+    Ok(out)
+}
+
+/// 一次扫描替换所有自定义占位符, 替换进来的值不会再被其它占位符替换
+///
+/// 多个 key 能同时匹配同一位置时 更长的 key 优先
+pub fn replace_placeholders(text: &str, placeholders: &BTreeMap<String, String>) -> String {
+    if placeholders.is_empty() {
+        return text.to_string();
+    }
+    let mut keys: Vec<&String> = placeholders.keys().filter(|k| !k.is_empty()).collect();
+    if keys.is_empty() {
+        return text.to_string();
+    }
+    keys.sort_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)));
+    let pattern = keys
+        .iter()
+        .map(|k| regex::escape(k))
+        .collect::<Vec<_>>()
+        .join("|");
+    let re = regex::Regex::new(&pattern).unwrap();
+    re.replace_all(text, |caps: &regex::Captures| placeholders[&caps[0]].clone())
+        .into_owned()
+}
+
+/// 检查 0x 开头的原始 data: 0x 之后必须是偶数个十六进制字符, 出错时指出问题位置
+pub fn validate_hex_data(data: &str) -> Result<(), InkError> {
+    let hex = data.strip_prefix("0x").unwrap_or(data);
+    if let Some((index, c)) = hex.char_indices().find(|(_, c)| !c.is_ascii_hexdigit()) {
+        // 前面都是十六进制字符 可以直接按字节截取
+        let near = &hex[index.saturating_sub(8)..index];
+        return Err(InkError::Data(format!(
+            "0x data 第 {} 个字符 '{}' 不是十六进制字符(0-9 a-f), 位于 ...{}{} 处",
+            index + 1,
+            c,
+            near,
+            c
+        )));
+    }
+    if !hex.len().is_multiple_of(2) {
+        return Err(InkError::Data(format!(
+            "0x data 长度为奇数({} 个字符), 每个字节需要 2 个十六进制字符, 可能漏复制了第一位或最后一位",
+            hex.len()
+        )));
+    }
+    Ok(())
+}
+
+/// 解析 4 字节的方法选择器, 如 0xa9059cbb
+pub fn parse_selector(text: &str) -> Result<[u8; 4], InkError> {
+    let hex = text.strip_prefix("0x").unwrap_or(text);
+    let bytes = hex::decode(hex)
+        .map_err(|e| InkError::Config(format!("expected_selector 不合法: {}: {}", text, e)))?;
+    bytes.try_into().map_err(|_| {
+        InkError::Config(format!(
+            "expected_selector 必须是 4 字节(8 个十六进制字符): {}",
+            text
+        ))
+    })
+}
+
+/// 检查 0x data 的前 4 字节是否为 selector
+pub fn check_selector(data: &str, selector: [u8; 4]) -> Result<(), InkError> {
+    let hex = data.strip_prefix("0x").unwrap_or(data);
+    let actual = hex.get(..8).unwrap_or(hex);
+    if actual.eq_ignore_ascii_case(&hex::encode(selector)) {
+        return Ok(());
+    }
+    Err(InkError::Data(format!(
+        "0x data 的方法选择器 0x{} 与 expected_selector 0x{} 不符, 请检查 data 是否改错",
+        actual,
+        hex::encode(selector)
+    )))
+}
+
+pub fn decode_hex(hex: &str) -> Result<String, InkError> {
+    let bytes = decode_hex_bytes(hex)?;
+    let text = String::from_utf8(bytes)
+        .map_err(|e| InkError::Data(format!("数据不是合法的 UTF-8 文本: {}", e)))?;
+    Ok(text)
+}
+
+fn decode_hex_bytes(hex: &str) -> Result<Vec<u8>, InkError> {
+    hex::decode(hex).map_err(|e| InkError::Data(format!("十六进制数据解码失败: {}", e)))
+}
+
+/// 读取 data 来源
+///
+/// - `@file:路径` 文件中每行一条 data
+/// - `-` 从 stdin 逐行读取
+/// - 其它 直接作为一条 data
+///
+/// 文件或 stdin 中没有任何非空行时报错, 避免把 `-` 或 `@file:路径` 本身当作 data 发送
+pub fn read_data_source(data: &str) -> Result<Vec<String>, InkError> {
+    if let Some(path) = data.strip_prefix("@file:") {
+        let file = std::fs::File::open(path.trim())
+            .map_err(|e| InkError::Config(format!("读取 data 文件失败: {}: {}", path, e)))?;
+        read_data_reader(BufReader::new(file), "data 文件")
+    } else if data == "-" {
+        read_data_reader(std::io::stdin().lock(), "stdin")
+    } else {
+        Ok(vec![data.to_string()])
+    }
+}
+
+fn read_data_reader<R: BufRead>(reader: R, source: &str) -> Result<Vec<String>, InkError> {
+    let lines = read_data_lines(reader)?;
+    if lines.is_empty() {
+        return Err(InkError::Config(format!("{} 中没有任何 data", source)));
+    }
+    Ok(lines)
+}
+
+/// 读取 JSON 数组格式的任务条目文件, 如 [{"data":"...","to":"0x...","value":0.001}]
+pub fn read_mint_items(path: &str) -> Result<Vec<MintItem>, InkError> {
+    let text = std::fs::read_to_string(path.trim())
+        .map_err(|e| InkError::Config(format!("读取任务条目文件失败: {}: {}", path, e)))?;
+    parse_mint_items(&text)
+}
+
+pub fn parse_mint_items(text: &str) -> Result<Vec<MintItem>, InkError> {
+    let items: Vec<MintItem> = serde_json::from_str(text)
+        .map_err(|e| InkError::Config(format!("任务条目文件解析失败: {}", e)))?;
+    if items.is_empty() {
+        return Err(InkError::Config("任务条目文件不能为空".to_string()));
+    }
+    Ok(items)
+}
+
+/// 每行一条 data, 忽略空行
+pub fn read_data_lines<R: BufRead>(reader: R) -> std::io::Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim();
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    Ok(lines)
+}
+
+pub fn gzip_compress(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(bytes)?;
+    encoder.finish()
+}
+
+pub fn gzip_decompress(bytes: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut decoder = GzDecoder::new(bytes);
+    let mut out = Vec::new();
+    decoder.read_to_end(&mut out)?;
+    Ok(out)
+}
+
+/// 解码 prefix + gzip 压缩文本 的十六进制数据, 返回 prefix + 原文 用于显示
+pub fn decode_hex_gzip(hex: &str, prefix: &str) -> Result<String, InkError> {
+    let bytes = decode_hex_bytes(hex)?;
+    let compressed = bytes
+        .strip_prefix(prefix.as_bytes())
+        .ok_or_else(|| InkError::Data("压缩数据缺少 prefix".to_string()))?;
+    let text = String::from_utf8(gzip_decompress(compressed)?)
+        .map_err(|e| InkError::Data(format!("解压后的数据不是合法的 UTF-8 文本: {}", e)))?;
+    Ok(format!("{}{}", prefix, text))
+}
+
+/// 和 decode_hex 一样 但不是合法 UTF-8 时不报错, 返回 lossy 文本和 false
+pub fn decode_hex_lossy(hex: &str) -> Result<(String, bool), InkError> {
+    let bytes = decode_hex_bytes(hex)?;
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok((text, true)),
+        Err(e) => Ok((String::from_utf8_lossy(e.as_bytes()).into_owned(), false)),
+    }
+}
+
+/// 从交易 input 解码出的铭文
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedInscription {
+    /// data:, 或 data:application/json, 这类前缀
+    pub prefix: Option<String>,
+    /// 前缀之后的内容, 不是合法 UTF-8 时为 lossy 文本
+    pub body: String,
+    pub valid_utf8: bool,
+    /// body 是 JSON 对象或数组时的解析结果
+    pub json: Option<serde_json::Value>,
+}
+
+/// 把十六进制的交易 input 解码成铭文, 识别 data: 前缀和 JSON 内容
+pub fn decode_inscription(hex: &str) -> Result<DecodedInscription, InkError> {
+    let (text, valid_utf8) = decode_hex_lossy(hex.trim())?;
+    let (prefix, body) = match text.strip_prefix("data:").and_then(|rest| rest.find(',')) {
+        Some(index) => {
+            let end = "data:".len() + index + 1;
+            (Some(text[..end].to_string()), text[end..].to_string())
+        }
+        None => (None, text),
+    };
+    let json = if valid_utf8 {
+        serde_json::from_str::<serde_json::Value>(body.trim())
+            .ok()
+            .filter(|value| value.is_object() || value.is_array())
+    } else {
+        None
+    };
+    Ok(DecodedInscription {
+        prefix,
+        body,
+        valid_utf8,
+        json,
+    })
+}
+
+/// 解析 data 中的 id 范围
+///
+/// 老语法: `[1000-2000]` `[1000-]` 递增, `[-2000]` 从 2000 递减
+/// 新语法: `[1-2000:asc]` `[2000-1:desc]` 显式指定方向, 前者为起点后者为终点
+/// 十六进制: `[hex:1-ff]` 起止按十六进制解析并以十六进制填入, 起点带前导零时(如 `[hex:0001-ffff]`)按起点的位数补零
+pub fn process_id(
+    text: &str,
+) -> Result<(Option<Id>, Option<u64>, u64), InkError> {
+    let re = regex::Regex::new(concat!(
+        r"\[(?:hex:(?P<hex_start>[0-9a-fA-F]+)?-(?P<hex_end>[0-9a-fA-F]+)?",
+        r"|(?P<start>\d+)?-(?P<end>\d+)?)(?::(?P<direction>asc|desc))?]"
+    ))
+    .unwrap();
+    if let Some(caps) = re.captures(text) {
+        let match_id = caps.get(0).unwrap().as_str().to_string();
+        let is_hex = match_id.starts_with("[hex:");
+        let (start_id, end_id, format) = if is_hex {
+            let parse = |name: &str| {
+                caps.name(name)
+                    .and_then(|m| u64::from_str_radix(m.as_str(), 16).ok())
+            };
+            // 起点写了前导零时按起点的位数补零
+            let width = caps
+                .name("hex_start")
+                .map(|m| m.as_str())
+                .filter(|start| start.len() > 1 && start.starts_with('0'))
+                .map_or(0, str::len);
+            (parse("hex_start"), parse("hex_end"), IdFormat::Hex { width })
+        } else {
+            let parse = |name: &str| caps.name(name).and_then(|m| m.as_str().parse().ok());
+            (parse("start"), parse("end"), IdFormat::Decimal)
+        };
+        // first 为第一笔使用的 id, last 为最后一笔的边界
+        let (direction, first, last) = match caps.name("direction").map(|m| m.as_str()) {
+            Some("desc") => (Direction::Desc, start_id, end_id),
+            Some(_) => (Direction::Asc, start_id, end_id),
+            // 老语法 只有终点时 从终点递减
+            None if start_id.is_none() => (Direction::Desc, end_id, None),
+            None => (Direction::Asc, start_id, end_id),
+        };
+        let first = match first {
+            Some(first) => first,
+            None => return Err(InkError::Config(format!("id 范围缺少起点: {}", match_id))),
+        };
+        let count = match direction {
+            Direction::Asc => match last {
+                Some(last) if first > last => {
+                    return Err(InkError::Config(format!(
+                        "id 范围起点 {} 大于终点 {}: {}",
+                        first, last, match_id
+                    )));
+                }
+                Some(last) => (last - first).saturating_add(1),
+                None => u64::MAX,
+            },
+            Direction::Desc => {
+                // 递减最多到 0 为止
+                let last = last.unwrap_or(0);
+                if first < last {
+                    return Err(InkError::Config(format!(
+                        "递减 id 范围起点 {} 小于终点 {}: {}",
+                        first, last, match_id
+                    )));
+                }
+                (first - last).saturating_add(1)
+            }
+        };
+        Ok((
+            Some(Id {
+                id: first,
+                start_id,
+                end_id,
+                match_id,
+                direction,
+                format,
+            }),
+            Some(first),
+            count,
+        ))
+    } else {
+        Ok((None, None, u64::MAX))
+    }
+}
+
+/// 按 data 模板缓存 estimate_gas 的结果, 模板不变就复用
+///
+/// sample_interval 为 0 时同一模板只估算一次, 否则每使用 N 次重新估算一次;
+/// to 是 EOA 时不调用 estimate_gas, 直接按 calldata 计算固有 gas
+#[derive(Debug, Default)]
+pub struct GasEstimator {
+    sample_interval: u64,
+    // 模板指纹 -> (gas, 已使用次数)
+    cache: HashMap<u64, (u64, u64)>,
+    // 地址 -> 是否为合约
+    contracts: HashMap<Address, bool>,
+}
+
+impl GasEstimator {
+    pub fn new(sample_interval: u64) -> Self {
+        GasEstimator {
+            sample_interval,
+            cache: HashMap::new(),
+            contracts: HashMap::new(),
+        }
+    }
+
+    /// 用 eth_getCode 判断地址是否为合约, 每个地址只查询一次
+    pub async fn is_contract<M: Middleware>(
+        &mut self,
+        provider: &M,
+        address: Address,
+        timeout: Duration,
+        retries: u32,
+    ) -> Result<bool, InkError> {
+        if let Some(is_contract) = self.contracts.get(&address) {
+            return Ok(*is_contract);
+        }
+        let code = retry_on_timeout(timeout, retries, "get_code", || {
+            provider.get_code(address, None)
+        })
+        .await?;
+        self.contracts.insert(address, !code.is_empty());
+        Ok(!code.is_empty())
+    }
+
+    pub fn fingerprint(template: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        template.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// 命中缓存时返回 gas 并计一次使用, 未缓存或需要重新估算时返回 None
+    pub fn lookup(&mut self, template: &str) -> Option<u64> {
+        let sample_interval = self.sample_interval;
+        match self.cache.get_mut(&Self::fingerprint(template)) {
+            Some((gas, uses)) if sample_interval == 0 || *uses < sample_interval => {
+                *uses += 1;
+                Some(*gas)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn insert(&mut self, template: &str, gas: u64) {
+        self.cache.insert(Self::fingerprint(template), (gas, 1));
+    }
+
+    pub async fn estimate<M: Middleware>(
+        &mut self,
+        provider: &M,
+        template: &str,
+        tx: &TypedTransaction,
+        timeout: Duration,
+        retries: u32,
+    ) -> Result<u64, InkError> {
+        // 纯铭文转账发到 EOA 不执行代码, 部分 RPC 的 estimate_gas 会失败, 固有 gas 就是准确值
+        if let Some(to) = tx.to_addr().copied() {
+            if !self.is_contract(provider, to, timeout, retries).await? {
+                let data = tx.data().map(|data| data.as_ref()).unwrap_or_default();
+                return Ok(calldata_intrinsic_gas(data));
+            }
+        }
+        if let Some(gas) = self.lookup(template) {
+            return Ok(gas);
+        }
+        let gas = retry_on_timeout(timeout, retries, "estimate_gas", || {
+            provider.estimate_gas(tx, None)
+        })
+        .await?;
+        let gas = gas.min(U256::from(u64::MAX)).as_u64();
+        self.insert(template, gas);
+        Ok(gas)
+    }
+}
+
+/// 按每批间隔的期望值估算剩余批次还要多久, 每批发送完都会等待一次间隔
+pub fn estimate_eta(expected_interval: f64, remaining_batches: u64) -> Duration {
+    if expected_interval.is_nan() || expected_interval <= 0.0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(expected_interval * remaining_batches as f64)
+}
+
+/// 轮询回执的间隔
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 一笔已发送交易的确认状态
+#[derive(Debug, Clone)]
+pub enum TxStatus {
+    /// 已上链
+    Confirmed(Box<TransactionReceipt>),
+    /// 等待超时仍没有回执 但节点还能查到这笔交易
+    Pending,
+    /// 没有回执 节点也查不到交易(被替换或超时移出 mempool)
+    Dropped,
+}
+
+/// 等待一批交易的回执, 超过 timeout 仍没有回执的再查一次交易本身判断是否被丢弃
+pub async fn confirm_batch<M: Middleware>(
+    provider: &M,
+    tx_hashes: &[H256],
+    timeout: Duration,
+    rpc_timeout: Duration,
+    retries: u32,
+) -> Result<Vec<TxStatus>, InkError> {
+    let deadline = Instant::now() + timeout;
+    let mut receipts: Vec<Option<TransactionReceipt>> = vec![None; tx_hashes.len()];
+    loop {
+        for (receipt, tx_hash) in receipts.iter_mut().zip(tx_hashes) {
+            if receipt.is_none() {
+                *receipt = retry_on_timeout(rpc_timeout, retries, "获取交易回执", || {
+                    provider.get_transaction_receipt(*tx_hash)
+                })
+                .await?;
+            }
+        }
+        if receipts.iter().all(Option::is_some) || Instant::now() >= deadline {
+            break;
+        }
+        tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+    }
+
+    let mut statuses = Vec::with_capacity(tx_hashes.len());
+    for (receipt, tx_hash) in receipts.into_iter().zip(tx_hashes) {
+        let status = match receipt {
+            Some(receipt) => TxStatus::Confirmed(Box::new(receipt)),
+            None => {
+                let tx = retry_on_timeout(rpc_timeout, retries, "查询交易", || {
+                    provider.get_transaction(*tx_hash)
+                })
+                .await?;
+                if tx.is_some() {
+                    TxStatus::Pending
+                } else {
+                    TxStatus::Dropped
+                }
+            }
+        };
+        statuses.push(status);
+    }
+    Ok(statuses)
+}
+
+/// verify_in_mempool 发送后等待多久再确认
+pub const MEMPOOL_CHECK_DELAY: Duration = Duration::from_secs(1);
+
+/// 节点返回了 hash 但交易没有进入 mempool 时记录的错误
+pub const NOT_IN_MEMPOOL: &str =
+    "节点返回了交易哈希但交易没有进入 mempool(可能被静默丢弃), 需要重发";
+
+/// txpool_content 响应里 pending 和 queued 的所有交易 hash
+pub fn txpool_hashes(content: &serde_json::Value) -> HashSet<H256> {
+    ["pending", "queued"]
+        .iter()
+        .filter_map(|key| content.get(key).and_then(|txs| txs.as_object()))
+        .flat_map(|by_address| by_address.values())
+        .filter_map(|by_nonce| by_nonce.as_object())
+        .flat_map(|by_nonce| by_nonce.values())
+        .filter_map(|tx| tx.get("hash").and_then(|hash| hash.as_str()))
+        .filter_map(|hash| hash.parse().ok())
+        .collect()
+}
+
+/// 确认每笔交易是否已被节点接受, 结果按 tx_hashes 的顺序返回
+///
+/// 先查一次 txpool, 不在 txpool 里的(可能已经上链)再逐笔 get_transaction;
+/// 节点不支持 txpool 命名空间时全部用 get_transaction 确认
+pub async fn check_in_mempool<P, PFut, G, GFut>(
+    tx_hashes: &[H256],
+    txpool_content: P,
+    mut get_transaction: G,
+) -> Result<Vec<bool>, InkError>
+where
+    P: FnOnce() -> PFut,
+    PFut: Future<Output = Result<serde_json::Value, InkError>>,
+    G: FnMut(H256) -> GFut,
+    GFut: Future<Output = Result<bool, InkError>>,
+{
+    let in_txpool = match txpool_content().await {
+        Ok(content) => txpool_hashes(&content),
+        Err(e) => {
+            debug!("txpool_content 不可用, 改用 get_transaction 确认: {}", e);
+            HashSet::new()
+        }
+    };
+    let mut accepted = Vec::with_capacity(tx_hashes.len());
+    for tx_hash in tx_hashes {
+        accepted.push(in_txpool.contains(tx_hash) || get_transaction(*tx_hash).await?);
+    }
+    Ok(accepted)
+}
+
+/// 等待 MEMPOOL_CHECK_DELAY 后确认发送成功的交易, 没有进入 mempool 的改为 NOT_IN_MEMPOOL 错误
+async fn verify_in_mempool<M: Middleware>(
+    provider: &M,
+    responses: &mut [Result<H256, String>],
+    rpc_timeout: Duration,
+    retries: u32,
+) -> Result<(), InkError> {
+    let tx_hashes: Vec<H256> = responses
+        .iter()
+        .filter_map(|response| response.as_ref().ok().copied())
+        .collect();
+    if tx_hashes.is_empty() {
+        return Ok(());
+    }
+    tokio::time::sleep(MEMPOOL_CHECK_DELAY).await;
+    let accepted = check_in_mempool(
+        &tx_hashes,
+        || {
+            retry_on_timeout(rpc_timeout, retries, "txpool_content", || {
+                provider
+                    .provider()
+                    .request::<_, serde_json::Value>("txpool_content", ())
+            })
+        },
+        |tx_hash| async move {
+            let tx = retry_on_timeout(rpc_timeout, retries, "查询交易", || {
+                provider.get_transaction(tx_hash)
+            })
+            .await?;
+            Ok(tx.is_some())
+        },
+    )
+    .await?;
+    let mut accepted = accepted.into_iter();
+    for response in responses.iter_mut().filter(|response| response.is_ok()) {
+        if accepted.next() == Some(false) {
+            *response = Err(NOT_IN_MEMPOOL.to_string());
+        }
+    }
+    Ok(())
+}
+/// mint 过程中的事件, 调用方可以据此打印日志或统计
+#[derive(Debug)]
+pub enum MintEvent {
+    /// count 为 auto 时按余额算出的数量
+    AutoCount { balance: U256, count: u64 },
+    /// 不在 send_window 内, 等待 delay 后窗口开启
+    SendWindowWait { window: SendWindow, delay: Duration },
+    /// 钱包开始前按 wallet_start_jitter_secs 随机等待 delay
+    StartJitter { delay: Duration },
+    /// 钱包单独指定了 rpc_url, run_all 为它另外建立连接
+    WalletRpc { rpc_url: String },
+    /// 钱包准备完成即将开始发送, data 为第一笔的十六进制数据
+    WalletReady {
+        chain_id: U256,
+        address: Address,
+        /// 收款地址和权重
+        recipients: Vec<(Address, u64)>,
+        nonce: U256,
+        count: u64,
+        data: String,
+    },
+    /// 开始发送第 index 轮(从 1 开始), 共 total 轮
+    BatchStart {
+        index: u64,
+        total: u64,
+        size: u64,
+        /// mint 开始到现在的用时
+        elapsed: Duration,
+        /// 预计剩余时间
+        eta: Duration,
+    },
+    /// 第 index 轮发送(以及 wait_receipt 时的确认)结束
+    BatchDone { index: u64 },
+    /// 第 index 笔交易签名完成, tx_hash 由已签名交易离线算出, 和节点返回的一致
+    TxSigned {
+        index: u64,
+        nonce: U256,
+        id: Option<u64>,
+        tx_hash: H256,
+    },
+    /// 第 index 笔交易发送成功(从 1 开始)
+    TxSent {
+        index: u64,
+        nonce: U256,
+        tx_hash: H256,
+    },
+    /// 第 index 笔交易发送失败(从 1 开始), data 为这笔交易的 input
+    TxFailed {
+        index: u64,
+        nonce: U256,
+        /// data 里 id 范围这笔使用的 id
+        id: Option<u64>,
+        data: Bytes,
+        /// 0x 开头的已签名原始交易, 可以用 eth_sendRawTransaction 手动复现
+        raw_tx: String,
+        error: String,
+    },
+    /// 第 index 笔交易的错误命中 error_policy 的 retry, 第 attempt 次重发(从 1 开始)
+    TxRetry {
+        index: u64,
+        attempt: u32,
+        error: String,
+    },
+    /// 第 index 笔交易的错误命中 error_policy 的 skip, 不计入失败
+    TxSkipped {
+        index: u64,
+        nonce: U256,
+        error: String,
+    },
+    /// 节点不支持 EIP-1559 交易, 该钱包剩余交易改用 legacy 发送
+    LegacyFallback { error: String },
+    /// 开打前的 approve 已完成, tx_hash 为 None 表示已有足额授权没有发送交易
+    Approved {
+        token: Address,
+        spender: Address,
+        amount: U256,
+        tx_hash: Option<H256>,
+    },
+    /// adaptive_batch 开启时节点提示 batch 过大, 每次发送的笔数降为 limit
+    BatchLimitReduced { limit: usize },
+    /// 按 base fee 收紧后这一批实际使用的 max_fee(legacy 交易为 gas_price)
+    FeeClamp { base_fee: U256, max_fee: U256 },
+    /// gas 门控检查结果, passed 为 false 时会等待 gate_poll_secs 后重查
+    GasGate {
+        current: U256,
+        gate: U256,
+        passed: bool,
+    },
+    /// bundle 已提交, attempt 从 0 开始
+    BundleSent {
+        bundle_hash: String,
+        target_block: u64,
+        attempt: u32,
+    },
+    /// bundle 被拒绝或没有在目标区块上链
+    BundleMissed {
+        target_block: u64,
+        attempt: u32,
+        error: String,
+    },
+    /// 监控模式结束, remaining 为没有打完的数量, timed_out 表示因 watch_timeout_secs 到期退出
+    WatchFinished { remaining: u64, timed_out: bool },
+    /// wait_receipt 开启时第 index 轮的确认结果, 被丢弃的交易会重新广播 resent 为重发成功的数量
+    BatchConfirmed {
+        index: u64,
+        confirmed: u64,
+        pending: u64,
+        dropped: u64,
+        resent: u64,
+    },
+    /// 回执确认后链上 nonce(latest) 的增长少于成功发送的笔数, missing 为差值
+    NonceShortfall {
+        before: U256,
+        after: U256,
+        success: u64,
+        missing: u64,
+    },
+    /// run_all 中一个钱包执行结束, error 为失败原因
+    WalletFinished { error: Option<String> },
+    /// run_all 中一个钱包结束后按 wallet_interval 等待 delay 再进入下一个钱包
+    WalletInterval { delay: Duration },
+}
+
+/// 一个钱包 mint 的结果汇总
+#[derive(Debug, Default, Clone)]
+pub struct RunSummary {
+    pub address: Address,
+    pub sent: u64,
+    pub success: u64,
+    pub failed: u64,
+    pub tx_hashes: Vec<H256>,
+    /// 已拿到回执的交易数量
+    pub confirmed: u64,
+    /// 回执里累计的 gas_used
+    pub gas_used: U256,
+    /// 累计 gas 消耗(wei) gas_used * effective_gas_price
+    pub gas_cost: U256,
+    /// 等待超时仍未上链的交易数量
+    pub pending: u64,
+    /// 被节点丢弃的交易数量
+    pub dropped: u64,
+    /// 发送失败的原因, 每笔一条
+    pub errors: Vec<String>,
+    /// 发送失败的交易, 可以导出到 failed.json 之后回放
+    pub failures: Vec<FailedTx>,
+    /// mint 前后链上 nonce 的增长, 没有做 nonce 自检时为 None
+    pub nonce_advanced: Option<u64>,
+}
+
+impl RunSummary {
+    /// 累加一笔回执的 gas 消耗, 回执没有 effective_gas_price(legacy 交易)时使用 gas_price
+    pub fn add_receipt(&mut self, receipt: &TransactionReceipt, gas_price: U256) {
+        let gas_used = receipt.gas_used.unwrap_or_default();
+        let price = receipt.effective_gas_price.unwrap_or(gas_price);
+        self.confirmed += 1;
+        self.gas_used += gas_used;
+        self.gas_cost += gas_used * price;
+    }
+
+    /// 按确认状态计入统计, 已上链的累加 gas 消耗
+    pub fn record_status(&mut self, status: &TxStatus, gas_price: U256) {
+        match status {
+            TxStatus::Confirmed(receipt) => self.add_receipt(receipt, gas_price),
+            TxStatus::Pending => self.pending += 1,
+            TxStatus::Dropped => self.dropped += 1,
+        }
+    }
+
+    /// 成功发送但链上 nonce 没有增长的笔数, 大于 0 说明部分交易可能未上链
+    pub fn missing_nonces(&self) -> u64 {
+        match self.nonce_advanced {
+            Some(advanced) => self.success.saturating_sub(advanced),
+            None => 0,
+        }
+    }
+
+    /// 平均每笔的 gas 消耗(wei)
+    pub fn average_gas_cost(&self) -> U256 {
+        if self.confirmed == 0 {
+            U256::zero()
+        } else {
+            self.gas_cost / self.confirmed
+        }
+    }
+}
+
+/// 机器可读的单个钱包结果
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct WalletReport {
+    /// 私钥不合法时没有地址
+    pub address: Option<Address>,
+    pub sent: u64,
+    pub success: u64,
+    pub failed: u64,
+    pub tx_hashes: Vec<H256>,
+    pub errors: Vec<String>,
+}
+
+impl WalletReport {
+    /// 钱包整体执行失败时 错误也记到 errors 里
+    pub fn new(address: Option<Address>, result: &Result<RunSummary, InkError>) -> Self {
+        match result {
+            Ok(summary) => WalletReport {
+                address: Some(summary.address),
+                sent: summary.sent,
+                success: summary.success,
+                failed: summary.failed,
+                tx_hashes: summary.tx_hashes.clone(),
+                errors: summary.errors.clone(),
+            },
+            Err(e) => WalletReport {
+                address,
+                errors: vec![e.to_string()],
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// --output json 时打印到 stdout 的整体结果
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct RunReport {
+    pub sent: u64,
+    pub success: u64,
+    pub failed: u64,
+    pub wallets: Vec<WalletReport>,
+}
+
+impl RunReport {
+    pub fn new(wallets: Vec<WalletReport>) -> Self {
+        RunReport {
+            sent: wallets.iter().map(|wallet| wallet.sent).sum(),
+            success: wallets.iter().map(|wallet| wallet.success).sum(),
+            failed: wallets.iter().map(|wallet| wallet.failed).sum(),
+            wallets,
+        }
+    }
+}
+
+/// 按钱包地址处理配置: 解析 id 范围并修正 count, 填充 address 和 to_address
+///
+/// 返回铭文接收地址和起始 id
+pub fn prepare_config(
+    config: &mut Config,
+    address: Address,
+) -> Result<(Recipients, Option<u64>), InkError> {
+    let paid = config.paid_inscription()?;
+    let has_calldata = paid.as_ref().is_some_and(|paid| paid.calldata.is_some());
+    if config.data.is_empty() && !has_calldata {
+        return Err(InkError::Config("data 不能为空".to_string()));
+    }
+    let (mut id, mut current_id, mut id_count) = process_id(&config.data)?;
+    if let Some(id) = id.as_mut().filter(|_| config.id_offset > 0) {
+        id_count = id.skip(config.id_offset, id_count)?;
+        current_id = Some(id.id);
+    }
+    config.id = id;
+    config.count = min(config.count, id_count);
+    config.address = format!("{:?}", address);
+    let recipients = match (&paid, config.to_address.as_deref()) {
+        // 付费铭文的交易都发到合约
+        (Some(paid), _) => vec![(paid.contract, 1)],
+        (None, Some(str)) if !str.is_empty() => parse_recipients(str)?,
+        _ => vec![(address, 1)],
+    };
+    if let [(to_address, _)] = recipients.as_slice() {
+        config.to_address = Some(format!("{:?}", to_address));
+    }
+    Ok((recipients, current_id))
+}
+
+/// 收款地址和权重
+pub type Recipients = Vec<(Address, u64)>;
+
+/// 解析收款地址, 支持 `0xA:70,0xB:30` 的权重写法, 不写权重时为 1
+pub fn parse_recipients(text: &str) -> Result<Recipients, InkError> {
+    let mut recipients = Vec::new();
+    for part in text.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        let (address, weight) = match part.split_once(':') {
+            Some((address, weight)) => {
+                let weight: u64 = weight.trim().parse().map_err(|_| {
+                    InkError::Config(format!("to_address 权重不合法: {}", part))
+                })?;
+                (address.trim(), weight)
+            }
+            None => (part, 1),
+        };
+        if weight == 0 {
+            return Err(InkError::Config(format!("to_address 权重必须大于 0: {}", part)));
+        }
+        let address: Address = address.parse().map_err(|e| {
+            InkError::Config(format!("to_address 不是合法地址: {}: {}", address, e))
+        })?;
+        recipients.push((address, weight));
+    }
+    if recipients.is_empty() {
+        return Err(InkError::Config(format!("to_address 不是合法地址: {}", text)));
+    }
+    Ok(recipients)
+}
+
+/// 按权重轮流选取, 每 total_weight 次选取中各项出现的次数正好等于权重 并且尽量均匀穿插
+#[derive(Debug, Clone)]
+pub struct WeightedPicker<T> {
+    items: Vec<(T, u64)>,
+    current: Vec<i128>,
+    total: i128,
+}
+
+impl<T: Clone> WeightedPicker<T> {
+    pub fn new(items: Vec<(T, u64)>) -> Self {
+        let total = items.iter().map(|(_, weight)| *weight as i128).sum();
+        WeightedPicker {
+            current: vec![0; items.len()],
+            items,
+            total,
+        }
+    }
+}
+
+impl<T: Clone> Iterator for WeightedPicker<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.items.is_empty() {
+            return None;
+        }
+        // 平滑加权轮询: 每次所有项加上自身权重, 选出最大的一项后减去总权重
+        let mut best = 0;
+        for (i, (_, weight)) in self.items.iter().enumerate() {
+            self.current[i] += *weight as i128;
+            if self.current[i] > self.current[best] {
+                best = i;
+            }
+        }
+        self.current[best] -= self.total;
+        Some(self.items[best].0.clone())
+    }
+}
+
+/// 多个钱包共享的 RPC 连接和 gas 设置
+pub struct MintContext {
+    /// 建立连接时配置的 rpc_url, 钱包单独指定了其他 RPC 时 run_all 为它另外建立连接
+    pub rpc_url: String,
+    /// 查询 nonce、回执等使用第一个 rpc_url
+    pub provider: Provider<Http>,
+    /// 发送 batch 的 RPC 池, 设置了 private_rpc_url 时只包含私有 RPC
+    pub pool: Arc<RpcPool>,
+    /// 发送交易使用的 sender, connect 时就是 pool, 单测可以换成 mock
+    pub sender: Arc<dyn TxSender>,
+    /// send_on_new_block 开启时的新区块通知
+    pub new_blocks: Option<watch::Receiver<u64>>,
+    pub chain_id: U256,
+    /// 按 rpc_url 缓存的 chain_id, 用 connect_with_cache 建立的多个上下文可以共用
+    pub chain_ids: Arc<ChainIdCache>,
+    /// 按全局配置计算的 gas 设置, 用于转账等; mint 时按每个钱包自己的 max_fee/value 重新计算
+    pub gas_price: GasPrice,
+    /// max_inflight 的信号量, 多个上下文共用同一个才能限制总的在途请求数
+    pub inflight: Option<Arc<Semaphore>>,
+    /// max_tps 的令牌桶, 和 inflight 一样多个上下文共用
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+}
+
+impl MintContext {
+    pub async fn connect(config: &Config) -> Result<Self, InkError> {
+        let mut ctx = Self::connect_with_cache(config, Arc::new(ChainIdCache::new()))
+            .await?
+            .with_inflight(config.inflight_semaphore());
+        ctx.rate_limiter = config.rate_limiter();
+        Ok(ctx)
+    }
+
+    /// 和 parent 共用在途请求数和发送速率的限制
+    pub fn share_limits(mut self, parent: &MintContext) -> Self {
+        self.rate_limiter = parent.rate_limiter.clone();
+        self.with_inflight(parent.inflight.clone())
+    }
+
+    /// 通过 semaphore 发送: 每次 send_batch 先取得一个许可, 超过容量的请求排队等待
+    pub fn with_inflight(mut self, semaphore: Option<Arc<Semaphore>>) -> Self {
+        self.sender = match &semaphore {
+            Some(semaphore) => Arc::new(LimitedSender::new(self.pool.clone(), semaphore.clone())),
+            None => self.pool.clone(),
+        };
+        self.inflight = semaphore;
+        self
+    }
+
+    /// 和 connect 相同, chain_id 先查 chain_ids 缓存, 多个钱包使用同一个 RPC 时只查询一次
+    ///
+    /// 不限制在途请求数和发送速率, 需要时用 share_limits 和其它上下文共用限制
+    pub async fn connect_with_cache(
+        config: &Config,
+        chain_ids: Arc<ChainIdCache>,
+    ) -> Result<Self, InkError> {
+        let client = build_http_client(config)?;
+        let mut senders = Vec::new();
+        for send_url in config.send_urls() {
+            let sender = BatchSender::new(client.clone(), parse_rpc_url(send_url)?);
+            senders.push(match sender.health_check().await {
+                Ok(true) => sender,
+                Ok(false) => {
+                    warn!(
+                        "{} 不支持 JSON-RPC batch, 降级为逐笔 eth_sendRawTransaction",
+                        send_url
+                    );
+                    sender.without_batch()
+                }
+                Err(e) => {
+                    warn!("{} batch 探测失败: {}, 仍按 batch 发送", send_url, e);
+                    sender
+                }
+            });
+        }
+        let rpc_url = config.rpc_urls()[0];
+        let provider = Provider::new(Http::new_with_client(parse_rpc_url(rpc_url)?, client));
+        let chain_id = chain_ids
+            .get_or_fetch(rpc_url, || {
+                retry_on_timeout(
+                    config.rpc_timeout(),
+                    config.max_retries,
+                    "get_chainid",
+                    || provider.get_chainid(),
+                )
+            })
+            .await?;
+        let ws_url = config.ws_url.clone().filter(|url| !url.is_empty());
+        let new_blocks = match (config.send_on_new_block, ws_url) {
+            (true, Some(ws_url)) => Some(spawn_new_block_watcher(ws_url)),
+            (true, None) => {
+                warn!("send_on_new_block 需要设置 ws_url, 回退到 interval 模式");
+                None
+            }
+            _ => None,
+        };
+        let pool = Arc::new(RpcPool::new(senders));
+        Ok(MintContext {
+            rpc_url: config.rpc_url.clone(),
+            provider,
+            sender: pool.clone(),
+            pool,
+            new_blocks,
+            chain_id,
+            chain_ids,
+            gas_price: config.init_gas_price()?,
+            inflight: None,
+            rate_limiter: None,
+        })
+    }
+}
+
+/// 从获取 nonce 到发送完成的全流程, 只处理 config 中的一个钱包
+pub async fn run(config: Config) -> Result<RunSummary, InkError> {
+    let ctx = MintContext::connect(&config).await?;
+    run_wallet(&ctx, config, |_| {}).await
+}
+
+/// 多链模式下为 config.chain_configs() 的每条链建立连接, first 是已经为第一条链建立的连接
+///
+/// 后面的链和 first 共用 chain_id 缓存、max_inflight 和 max_tps 限制, 结果按 chains 的顺序返回
+pub async fn connect_chains(
+    first: Arc<MintContext>,
+    chains: &[Config],
+) -> Result<Vec<Arc<MintContext>>, InkError> {
+    let mut ctxs = vec![first.clone()];
+    for chain in chains.iter().skip(1) {
+        let ctx = MintContext::connect_with_cache(chain, first.chain_ids.clone())
+            .await?
+            .share_limits(&first);
+        ctxs.push(Arc::new(ctx));
+    }
+    Ok(ctxs)
+}
+
+/// 用 concurrency 个并发任务跑多个钱包, 结果按 configs 的顺序返回
+///
+/// on_event 收到钱包在 configs 里的序号和它的事件; replay 不为空时每个钱包只重发其中属于自己的失败交易
+pub async fn run_all<F>(
+    ctx: Arc<MintContext>,
+    configs: Vec<Config>,
+    concurrency: usize,
+    replay: Option<&[FailedTx]>,
+    on_event: F,
+) -> Vec<Result<RunSummary, InkError>>
+where
+    F: Fn(usize, MintEvent),
+{
+    let total = configs.len();
+    let on_event = &on_event;
+    let mut results: Vec<(usize, Result<RunSummary, InkError>)> =
+        stream::iter(configs.into_iter().enumerate())
+            .map(|(index, config)| {
+                let ctx = ctx.clone();
+                async move {
+                    let on_event = |event| on_event(index, event);
+                    // 最后一个钱包之后不再等待
+                    let last = index + 1 == total;
+                    (index, run_task(ctx, config, replay, last, on_event).await)
+                }
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// run_all 里的单个钱包, 结束后发送 WalletFinished 并按 wallet_interval 等待
+async fn run_task<F>(
+    ctx: Arc<MintContext>,
+    config: Config,
+    replay: Option<&[FailedTx]>,
+    last: bool,
+    mut on_event: F,
+) -> Result<RunSummary, InkError>
+where
+    F: FnMut(MintEvent),
+{
+    let wallet_interval = config.wallet_interval;
+    let result = async {
+        // 钱包单独指定了 rpc_url 时为它另外建立连接
+        let ctx = if config.rpc_url != ctx.rpc_url {
+            on_event(MintEvent::WalletRpc {
+                rpc_url: config.rpc_url.clone(),
+            });
+            let wallet_ctx = MintContext::connect_with_cache(&config, ctx.chain_ids.clone()).await?;
+            Arc::new(wallet_ctx.share_limits(&ctx))
+        } else {
+            ctx
+        };
+        match replay {
+            Some(failed) => replay_wallet(&ctx, &config, failed, &mut on_event).await,
+            None => run_wallet(&ctx, config, &mut on_event).await,
+        }
+    }
+    .await;
+    on_event(MintEvent::WalletFinished {
+        error: result.as_ref().err().map(ToString::to_string),
+    });
+    if wallet_interval > 0.0 && !last {
+        let delay = Duration::from_secs_f64(wallet_interval);
+        on_event(MintEvent::WalletInterval { delay });
+        tokio::time::sleep(delay).await;
+    }
+    result
+}
+
+/// 单个钱包的准备和发送
+pub async fn run_wallet<F>(
+    ctx: &MintContext,
+    mut config: Config,
+    mut on_event: F,
+) -> Result<RunSummary, InkError>
+where
+    F: FnMut(MintEvent),
+{
+    let wallet = config
+        .private_key
+        .parse::<LocalWallet>()
+        .map_err(|e| InkError::Wallet(e.to_string()))?
+        .with_chain_id(ctx.chain_id.as_u64());
+    let address = wallet.address();
+    let jitter = config.start_jitter();
+    if !jitter.is_zero() {
+        on_event(MintEvent::StartJitter { delay: jitter });
+        tokio::time::sleep(jitter).await;
+    }
+    let timeout = config.rpc_timeout();
+    // wallets_file 可以按钱包覆盖 max_fee 等, 所以 gas 按钱包自己的配置计算
+    let gas_price = config.init_gas_price()?;
+    if let Some(approve) = config.approve()? {
+        let tx_hash = ensure_approved(ctx, &config, &wallet, &approve).await?;
+        on_event(MintEvent::Approved {
+            token: approve.token,
+            spender: approve.spender,
+            amount: approve.amount,
+            tx_hash,
+        });
+    }
+    let nonce = retry_on_timeout(timeout, config.max_retries, "get_transaction_count", || {
+        ctx.provider.get_transaction_count(address, Some(BlockNumber::Latest.into()))
+    })
+    .await?;
+    if config.is_auto_count() {
+        let balance = retry_on_timeout(timeout, config.max_retries, "get_balance", || {
+            ctx.provider.get_balance(address, None)
+        })
+        .await?;
+        config.count = max_mint_count(
+            balance,
+            config.reserve_wei()?,
+            config.buffered_gas_limit(),
+            &gas_price,
+        );
+        on_event(MintEvent::AutoCount {
+            balance,
+            count: config.count,
+        });
+        if config.count == 0 {
+            return Ok(RunSummary {
+                address,
+                ..Default::default()
+            });
+        }
+    }
+    let (recipients, current_id) = prepare_config(&mut config, address)?;
+    // 预览第一笔的数据 然后恢复 id 和 data 的位置
+    let data = config.get_hex_text(&TxContext { nonce })?;
+    if let Some(id) = current_id {
+        config.set_id(id);
+    }
+    config.data_index = 0;
+    let (nonce_step, max_retries, wait_receipt) =
+        (config.nonce_step, config.max_retries, config.wait_receipt);
+    on_event(MintEvent::WalletReady {
+        chain_id: ctx.chain_id,
+        address,
+        recipients: recipients.clone(),
+        nonce,
+        count: config.count,
+        data,
+    });
+    let mut summary = mint(
+        ctx,
+        &wallet,
+        config,
+        &gas_price,
+        nonce,
+        &recipients,
+        &mut on_event,
+    )
+    .await?;
+    summary.address = address;
+    // 发送前的 nonce 按 latest 查询, 回执确认后再按 latest 查一次, 两次的差值才是真正上链的笔数
+    if summary.success > 0 && nonce_step > 1 {
+        // 中间有空号时链上 nonce 不会连续增长, 算不出应有的增长
+        info!("nonce_step 为 {}, 链上 nonce 不连续增长, 跳过 nonce 自检", nonce_step);
+    } else if summary.success > 0 && !wait_receipt {
+        debug!("没有开启 wait_receipt, 交易可能还没上链, 跳过 nonce 自检");
+    } else if summary.success > 0 {
+        let latest = Some(BlockNumber::Latest.into());
+        match retry_on_timeout(timeout, max_retries, "get_transaction_count", || {
+            ctx.provider.get_transaction_count(address, latest)
+        })
+        .await
+        {
+            Ok(after) => {
+                let advanced = after.saturating_sub(nonce);
+                summary.nonce_advanced = Some(advanced.min(U256::from(u64::MAX)).as_u64());
+                let missing = summary.missing_nonces();
+                if missing > 0 {
+                    on_event(MintEvent::NonceShortfall {
+                        before: nonce,
+                        after,
+                        success: summary.success,
+                        missing,
+                    });
+                }
+            }
+            Err(e) => warn!("mint 结束后查询 nonce 失败, 跳过 nonce 自检: {}", e),
+        }
+    }
+    Ok(summary)
+}
+
+/// 节点返回的错误是否表示不支持 EIP-1559(type-2) 交易, 按关键字大小写不敏感匹配
+///
+/// mint 发送 type-2 交易后有一笔命中时, 整批用同样的 nonce 改成 legacy 交易重发
+pub fn is_tx_type_unsupported(error: &str) -> bool {
+    const PATTERNS: [&str; 5] = [
+        "transaction type not supported",
+        "invalid transaction type",
+        "tx type not supported",
+        "eip-1559 not supported",
+        "eip1559 not supported",
+    ];
+    let error = error.to_lowercase();
+    PATTERNS.iter().any(|pattern| error.contains(pattern))
+}
+
+/// 常见节点错误的关键字(小写)和中文提示, 按顺序取第一个匹配的, 更具体的关键字放在前面
+const ERROR_HINTS: [(&str, &str); 17] = [
+    (
+        "replacement transaction underpriced",
+        "同一个 nonce 已有一笔交易在交易池里, 替换它需要更高的 gas, 建议把 max_fee 和优先费都提高 10% 以上",
+    ),
+    (
+        "transaction underpriced",
+        "gas 填太低, 节点不接受, 建议提高 max_fee_per_gas 和 max_priority_fee_per_gas",
+    ),
+    (
+        "less than block base fee",
+        "max_fee 低于当前区块的 base fee, 建议提高 max_fee_per_gas",
+    ),
+    (
+        "max priority fee per gas higher than max fee per gas",
+        "优先费不能高于 max_fee, 建议调低 max_priority_fee_per_gas",
+    ),
+    (
+        "nonce too low",
+        "这个 nonce 已经被用过了(之前的交易已上链或有其它程序在用这个钱包), 重新运行会从链上最新的 nonce 开始",
+    ),
+    (
+        "nonce too high",
+        "nonce 跳号了, 前面还有 nonce 没有发出去, 检查 nonce_step 或先补齐前面的交易",
+    ),
+    (
+        "insufficient funds",
+        "钱包余额不够支付 gas_limit * max_fee + value, 请充值或调低 gas、value",
+    ),
+    (
+        "intrinsic gas too low",
+        "gas_limit 太低, 连交易本身的基础消耗都不够, 建议提高 gas_limit 或开启 estimate_gas",
+    ),
+    (
+        "exceeds block gas limit",
+        "gas_limit 超过了区块的 gas 上限, 建议调低 gas_limit",
+    ),
+    (
+        "exceeds the configured cap",
+        "手续费超过了节点允许的上限(txfeecap), 建议调低 max_fee 或 gas_limit",
+    ),
+    ("already known", "这笔交易已经在节点的交易池里了, 一般不用处理"),
+    ("known transaction", "这笔交易已经在节点的交易池里了, 一般不用处理"),
+    ("txpool is full", "节点的交易池满了, 稍后重试或换一个 RPC"),
+    (
+        "too many requests",
+        "请求太频繁被 RPC 限速了, 建议调小 batch_size、设置 max_tps 或换一个 RPC",
+    ),
+    (
+        "rate limit",
+        "请求太频繁被 RPC 限速了, 建议调小 batch_size、设置 max_tps 或换一个 RPC",
+    ),
+    (
+        "execution reverted",
+        "合约执行回滚, 检查 data、to_address 和 value 是否符合合约要求",
+    ),
+    (
+        "invalid sender",
+        "签名使用的 chain_id 和节点不一致, 检查 rpc_url 是不是目标链",
+    ),
+];
+
+/// 把常见的节点错误翻译成中文提示和建议, 不认识的错误返回 None
+pub fn humanize_error(error: &str) -> Option<String> {
+    let error = error.to_lowercase();
+    ERROR_HINTS
+        .iter()
+        .find(|(pattern, _)| error.contains(pattern))
+        .map(|(_, hint)| hint.to_string())
+}
+
+fn build_tx(
+    from: Address,
+    chain_id: u64,
+    to_address: Address,
+    gas_price: &GasPrice,
+    nonce: U256,
+    data: Bytes,
+) -> TypedTransaction {
+    if gas_price.eip1559 {
+        Eip1559TransactionRequest::new()
+            .chain_id(chain_id)
+            .from(from)
+            .to(to_address)
+            .value(gas_price.value)
+            .max_fee_per_gas(gas_price.max_fee_per_gas)
+            .max_priority_fee_per_gas(gas_price.max_priority_fee_per_gas)
+            .nonce(nonce)
+            .data(data)
+            .access_list(vec![])
+            .into()
+    } else {
+        TransactionRequest::new()
+            .chain_id(chain_id)
+            .from(from)
+            .to(to_address)
+            .value(gas_price.value)
+            .nonce(nonce)
+            .data(data)
+            .gas_price(gas_price.max_fee_per_gas)
+            .into()
+    }
+}
+
+fn sign_tx(wallet: &Wallet<SigningKey>, tx: &TypedTransaction) -> Result<String, InkError> {
+    let signature = wallet
+        .sign_transaction_sync(tx)
+        .map_err(|e| InkError::Wallet(e.to_string()))?;
+    Ok(format!("0x{}", hex::encode(tx.rlp_signed(&signature))))
+}
+
+/// 并行签名一批交易, 结果按 txs 的顺序返回
+///
+/// 签名是 CPU 密集且互相独立的, 交给 rayon 线程池后耗时大致按 CPU 核数缩短;
+/// 调用前 nonce、data、gas 都要确定好, 签名之后不能再改
+pub fn sign_txs(wallet: &LocalWallet, txs: &[TypedTransaction]) -> Result<Vec<String>, InkError> {
+    txs.par_iter().map(|tx| sign_tx(wallet, tx)).collect()
+}
+
+/// 查询当前 gas, 高于 gate 时等待 poll 后重查, 直到不高于 gate 返回当前 gas
+///
+/// 设置了 deadline 时到期仍高于 gate 返回 None
+pub async fn wait_for_gas_gate<F, Fut, C>(
+    gate: U256,
+    poll: Duration,
+    deadline: Option<Instant>,
+    mut fetch: F,
+    mut on_check: C,
+) -> Result<Option<U256>, InkError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<U256, InkError>>,
+    C: FnMut(U256, bool),
+{
+    loop {
+        let current = fetch().await?;
+        let passed = current <= gate;
+        on_check(current, passed);
+        if passed {
+            return Ok(Some(current));
+        }
+        if deadline.is_some_and(|deadline| Instant::now() + poll > deadline) {
+            return Ok(None);
+        }
+        tokio::time::sleep(poll).await;
+    }
+}
+
+/// 按 base fee 收紧 max_fee: min(max_fee, base_fee * clamp_mul + priority)
+///
+/// EIP-1559 交易本来只收 base fee + priority, 收紧后可以防止 legacy 节点或个别实现按 max_fee 收费
+pub fn clamp_max_fee(max_fee: U256, base_fee: U256, clamp_mul: f64, priority: U256) -> U256 {
+    // 倍数按千分之一精度换成整数运算
+    let per_mille = U256::from((clamp_mul * 1000.0).round() as u64);
+    let cap = base_fee * per_mille / 1000 + priority;
+    max_fee.min(cap)
+}
+
+/// 用 eth_feeHistory 取下一个区块的 base fee
+pub async fn next_base_fee<M: Middleware>(
+    provider: &M,
+    timeout: Duration,
+    retries: u32,
+) -> Result<U256, InkError> {
+    let history = retry_on_timeout(timeout, retries, "eth_feeHistory", || {
+        provider.fee_history(1u64, BlockNumber::Latest, &[])
+    })
+    .await?;
+    // base_fee_per_gas 比请求的区块多一个, 最后一个就是下一个区块的 base fee
+    history
+        .base_fee_per_gas
+        .last()
+        .copied()
+        .ok_or_else(|| InkError::Rpc("eth_feeHistory 没有返回 base fee".to_string()))
+}
+
+/// 等待发送下一批: 有新区块通知时等到下一个新块, 否则(或订阅已放弃)等待 interval 秒
+pub async fn wait_next_batch(new_blocks: &mut Option<watch::Receiver<u64>>, interval: f64) {
+    if let Some(receiver) = new_blocks.as_mut() {
+        if receiver.changed().await.is_ok() {
+            return;
+        }
+        *new_blocks = None;
+    }
+    // 倍数增长的间隔可能超出 Duration 的范围
+    let interval = Duration::try_from_secs_f64(interval).unwrap_or(Duration::MAX);
+    tokio::time::sleep(interval).await;
+}
+
+/// 已签名交易的 hash
+pub fn raw_tx_hash(raw_tx: &str) -> Result<H256, InkError> {
+    let bytes = hex::decode(raw_tx.trim_start_matches("0x"))
+        .map_err(|e| InkError::Data(format!("已签名交易不是合法的十六进制: {}", e)))?;
+    Ok(H256::from(keccak256(bytes)))
+}
+
+/// 重发的交易被节点提示已经发送过, 说明上一次没拿到响应的发送其实已经到达节点
+pub fn is_resent_duplicate(error: &str) -> bool {
+    const PATTERNS: [&str; 3] = ["already known", "known transaction", "nonce too low"];
+    let error = error.to_lowercase();
+    PATTERNS.iter().any(|pattern| error.contains(pattern))
+}
+
+/// 整批重发后, 把提示已经发送过的交易改成成功, hash 由已签名交易算出
+fn accept_resent(raw_txs: &[String], responses: &mut [Result<H256, String>]) {
+    for (raw_tx, response) in raw_txs.iter().zip(responses.iter_mut()) {
+        if matches!(response, Err(e) if is_resent_duplicate(e)) {
+            if let Ok(hash) = raw_tx_hash(raw_tx) {
+                *response = Ok(hash);
+            }
+        }
+    }
+}
+
+/// 签名后预先算出的一笔交易 hash
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedTxHash {
+    pub index: u64,
+    pub nonce: U256,
+    pub id: Option<u64>,
+    pub tx_hash: H256,
+}
+
+/// 按 CSV 格式追加到 path, 文件为空时先写表头 wallet,index,nonce,id,tx_hash
+///
+/// 一批写一次, 多个钱包写同一个文件时行不会交错
+pub fn append_tx_hashes(
+    path: &str,
+    wallet: Address,
+    hashes: &[SignedTxHash],
+) -> Result<(), InkError> {
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| InkError::Config(format!("打开文件失败: {}: {}", path, e)))?;
+    let mut text = String::new();
+    if file.metadata()?.len() == 0 {
+        text.push_str("wallet,index,nonce,id,tx_hash\n");
+    }
+    for hash in hashes {
+        let id = hash.id.map(|id| id.to_string()).unwrap_or_default();
+        text.push_str(&format!(
+            "{:?},{},{},{},{:?}\n",
+            wallet, hash.index, hash.nonce, id, hash.tx_hash
+        ));
+    }
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+/// 发送前预先算出一批已签名交易的 hash, 每笔发出 TxSigned
+fn record_signed_txs<F>(
+    first_index: u64,
+    txs: &[TypedTransaction],
+    batch: &[String],
+    ids: &[Option<u64>],
+    on_event: &mut F,
+) -> Result<Vec<SignedTxHash>, InkError>
+where
+    F: FnMut(MintEvent),
+{
+    let mut hashes = Vec::with_capacity(batch.len());
+    for (count, (tx, raw_tx)) in txs.iter().zip(batch).enumerate() {
+        let hash = SignedTxHash {
+            index: first_index + count as u64,
+            nonce: tx.nonce().copied().unwrap_or_default(),
+            id: ids[count],
+            tx_hash: raw_tx_hash(raw_tx)?,
+        };
+        on_event(MintEvent::TxSigned {
+            index: hash.index,
+            nonce: hash.nonce,
+            id: hash.id,
+            tx_hash: hash.tx_hash,
+        });
+        hashes.push(hash);
+    }
+    Ok(hashes)
+}
+
+/// 设置了 tx_hashes_file 时把一批交易 hash 追加导出
+fn export_tx_hashes(
+    config: &Config,
+    wallet: Address,
+    hashes: &[SignedTxHash],
+) -> Result<(), InkError> {
+    match config
+        .tx_hashes_file
+        .as_deref()
+        .filter(|path| !path.is_empty())
+    {
+        Some(path) => append_tx_hashes(path, wallet, hashes),
+        None => Ok(()),
+    }
+}
+
+/// 以下一个区块为目标发送 bundle, 被拒或目标区块出块后仍未上链时以新的下一个区块重投
+///
+/// 最多投 bundle_retries + 1 次, 全部失败时每笔都记为失败
+async fn send_bundle<F>(
+    ctx: &MintContext,
+    config: &Config,
+    wallet: &Wallet<SigningKey>,
+    raw_txs: &[String],
+    on_event: &mut F,
+) -> Result<Vec<Result<H256, String>>, InkError>
+where
+    F: FnMut(MintEvent),
+{
+    let timeout = config.rpc_timeout();
+    let provider = &ctx.provider;
+    let retries = config.max_retries;
+    let block_number = move || async move {
+        retry_on_timeout(timeout, retries, "get_block_number", move || {
+            provider.get_block_number()
+        })
+        .await
+        .map(|number| number.as_u64())
+    };
+    let tx_hashes = raw_txs
+        .iter()
+        .map(|raw_tx| raw_tx_hash(raw_tx))
+        .collect::<Result<Vec<H256>, InkError>>()?;
+    let mut last_error = String::new();
+    for attempt in 0..=config.bundle_retries {
+        let target_block = block_number().await? + 1;
+        let sent = ctx
+            .pool
+            .primary()
+            .send_bundle(&config.bundle_method, raw_txs, target_block, wallet)
+            .await;
+        match sent {
+            Ok(bundle_hash) => on_event(MintEvent::BundleSent {
+                bundle_hash,
+                target_block,
+                attempt,
+            }),
+            Err(e) => {
+                last_error = e.to_string();
+                on_event(MintEvent::BundleMissed {
+                    target_block,
+                    attempt,
+                    error: last_error.clone(),
+                });
+                continue;
+            }
+        }
+        // 等目标区块出块后 查第一笔的回执判断整个 bundle 是否上链
+        let deadline = Instant::now() + Duration::from_secs(config.receipt_timeout_secs);
+        while block_number().await? < target_block && Instant::now() < deadline {
+            tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+        }
+        let receipt = retry_on_timeout(timeout, retries, "获取交易回执", || {
+            provider.get_transaction_receipt(tx_hashes[0])
+        })
+        .await?;
+        if receipt.is_some() {
+            return Ok(tx_hashes.into_iter().map(Ok).collect());
+        }
+        last_error = format!("bundle 没有在区块 {} 上链", target_block);
+        on_event(MintEvent::BundleMissed {
+            target_block,
+            attempt,
+            error: last_error.clone(),
+        });
+    }
+    Ok(tx_hashes.iter().map(|_| Err(last_error.clone())).collect())
+}
+
+/// 检查 nonce 严格按 1 递增, 有重复或跳号时返回错误并指出冲突的 nonce
+pub fn assert_nonce_sequence(nonces: &[U256]) -> Result<(), InkError> {
+    assert_nonce_sequence_step(nonces, 1)
+}
+
+/// 同 assert_nonce_sequence, 相邻 nonce 之差应为 step
+pub fn assert_nonce_sequence_step(nonces: &[U256], step: u64) -> Result<(), InkError> {
+    for pair in nonces.windows(2) {
+        let (prev, next) = (pair[0], pair[1]);
+        if next == prev {
+            return Err(InkError::Send(format!("nonce 自检失败: nonce {} 重复", next)));
+        }
+        if next != prev + step {
+            return Err(InkError::Send(format!(
+                "nonce 自检失败: nonce {} 之后是 {}, 应为 {}",
+                prev,
+                next,
+                prev + step
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// 发送一批已签名的交易, 请求过大时拆分重发
+///
+/// adaptive_batch 开启时按 limit 分段, 过大时减半并沿用到后续批次
+async fn send_batch<F>(
+    ctx: &MintContext,
+    config: &Config,
+    raw_txs: &[String],
+    limit: &mut usize,
+    on_event: &mut F,
+) -> Result<Vec<Result<H256, String>>, InkError>
+where
+    F: FnMut(MintEvent),
+{
+    if let Some(rate_limiter) = &ctx.rate_limiter {
+        rate_limiter.acquire(raw_txs.len()).await;
+    }
+    let timeout = config.rpc_timeout();
+    let backoff = Duration::from_secs_f64(config.network_backoff_secs);
+    // 超时和连不上节点都整批退避后重试, 共用 network_retries 次;
+    // 没拿到响应的那次发送可能已经到达节点, 重发时提示已经发送过的交易按成功处理
+    let send = |chunk: Vec<String>| async move {
+        let mut attempt = 0;
+        retry_on_transport(config.network_retries, backoff, "发送 batch", || {
+            let resend = attempt > 0;
+            attempt += 1;
+            let chunk = &chunk;
+            async move {
+                let mut responses =
+                    match tokio::time::timeout(timeout, ctx.sender.send_batch(chunk)).await {
+                        Ok(responses) => responses?,
+                        Err(_) => {
+                            return Err(InkError::Timeout(format!("发送 batch 超时 {:?}", timeout)))
+                        }
+                    };
+                if resend {
+                    accept_resent(chunk, &mut responses);
+                }
+                Ok(responses)
+            }
+        })
+        .await
+    };
+    if !config.adaptive_batch {
+        return send_with_split(raw_txs, send).await;
+    }
+    let before = *limit;
+    let responses = send_adaptive(raw_txs, limit, send).await?;
+    if *limit < before {
+        on_event(MintEvent::BatchLimitReduced { limit: *limit });
+    }
+    Ok(responses)
+}
+
+/// 重发 responses 里错误命中 error_policy retry 的交易, 最多 max_retries 轮, 结果写回 responses
+///
+/// first_index 为这一批第一笔是第几笔, 用于事件里的 index
+async fn retry_by_policy<F>(
+    ctx: &MintContext,
+    config: &Config,
+    batch: &[String],
+    responses: &mut [Result<H256, String>],
+    first_index: u64,
+    limit: &mut usize,
+    on_event: &mut F,
+) -> Result<(), InkError>
+where
+    F: FnMut(MintEvent),
+{
+    for attempt in 1..=config.max_retries {
+        let retry: Vec<usize> = responses
+            .iter()
+            .enumerate()
+            .filter(|(_, response)| {
+                matches!(response, Err(e) if config.error_action(e) == Some(ErrorAction::Retry))
+            })
+            .map(|(count, _)| count)
+            .collect();
+        if retry.is_empty() {
+            break;
+        }
+        for &count in &retry {
+            if let Err(error) = &responses[count] {
+                on_event(MintEvent::TxRetry {
+                    index: first_index + count as u64,
+                    attempt,
+                    error: error.clone(),
+                });
+            }
+        }
+        let raw_txs: Vec<String> = retry.iter().map(|&count| batch[count].clone()).collect();
+        let retried = send_batch(ctx, config, &raw_txs, limit, on_event).await?;
+        for (&count, response) in retry.iter().zip(retried) {
+            responses[count] = response;
+        }
+    }
+    Ok(())
+}
+
+/// 从 nonce 开始分批发送 config.count 笔交易, 过程中的进度通过 on_event 通知
+///
+/// 某一批中途出错返回时会补发这一批的 BatchDone, 按 BatchStart/BatchDone 计数的指标不会残留
+pub async fn mint<F>(
+    ctx: &MintContext,
+    wallet: &Wallet<SigningKey>,
+    config: Config,
+    gas_price: &GasPrice,
+    nonce: U256,
+    recipients: &[(Address, u64)],
+    mut on_event: F,
+) -> Result<RunSummary, InkError>
+where
+    F: FnMut(MintEvent),
+{
+    let mut open_batch = None;
+    let result = mint_batches(ctx, wallet, config, gas_price, nonce, recipients, |event| {
+        match &event {
+            MintEvent::BatchStart { index, .. } => open_batch = Some(*index),
+            MintEvent::BatchDone { .. } => open_batch = None,
+            _ => {}
+        }
+        on_event(event);
+    })
+    .await;
+    if let Some(index) = open_batch {
+        on_event(MintEvent::BatchDone { index });
+    }
+    result
+}
+
+async fn mint_batches<F>(
+    ctx: &MintContext,
+    wallet: &Wallet<SigningKey>,
+    mut config: Config,
+    gas_price: &GasPrice,
+    mut nonce: U256,
+    recipients: &[(Address, u64)],
+    mut on_event: F,
+) -> Result<RunSummary, InkError>
+where
+    F: FnMut(MintEvent),
+{
+    let chain_id = wallet.chain_id();
+    let mut gas_price = gas_price.clone();
+    // 配置的 max_fee 上限, base_fee_clamp_mul 每批都从它重新收紧
+    let max_fee = gas_price.max_fee_per_gas;
+    let mut recipients = WeightedPicker::new(recipients.to_vec());
+    let mut new_blocks = ctx.new_blocks.clone();
+    if let Some(new_blocks) = new_blocks.as_mut() {
+        // 只等之后到达的新区块
+        new_blocks.borrow_and_update();
+    }
+    let mut summary = RunSummary::default();
+    let mut estimator = GasEstimator::new(config.estimate_sample_interval);
+    let timeout = config.rpc_timeout();
+    let started = Instant::now();
+    //每 100 为 一组 生成 100 个 tx
+    // 监控模式每批只打 watch_batch_size, 由 gas 门控决定什么时候打
+    let batch_size = if config.watch_mode {
+        min(config.count, config.watch_batch_size).max(1)
+    } else {
+        config.batch_size
+    };
+    let watch_deadline = (config.watch_mode && config.watch_timeout_secs > 0)
+        .then(|| started + Duration::from_secs(config.watch_timeout_secs));
+    let mut batch_limit = batch_size as usize;
+    let mut last_nonce: Option<U256> = None;
+    // 节点是否已经接受过本钱包的 type-2 交易, 接受后不会再改发 legacy
+    let mut type2_accepted = false;
+    let batch_count = config.count.div_ceil(batch_size);
+    let gas_gate = match config.max_gas_gate_gwei {
+        Some(gate) => Some(to_wei("max_gas_gate_gwei", gate, "gwei")?),
+        None => None,
+    };
+    let send_window = config.send_window()?;
+    // value 为表达式时每笔单独计算, 付费铭文仍使用 fee_value
+    let value_expr = config.value.is_expr() && config.paid_inscription()?.is_none();
+    for i in 0..batch_count {
+        if let Some(window) = send_window {
+            let delay = window.wait_time(chrono::Local::now().time());
+            if !delay.is_zero() {
+                on_event(MintEvent::SendWindowWait { window, delay });
+                tokio::time::sleep(delay).await;
+            }
+        }
+        if let Some(gate) = gas_gate {
+            let provider = &ctx.provider;
+            let retries = config.max_retries;
+            let current = wait_for_gas_gate(
+                gate,
+                Duration::from_secs(config.gate_poll_secs),
+                watch_deadline,
+                move || {
+                    retry_on_timeout(timeout, retries, "get_gas_price", move || {
+                        provider.get_gas_price()
+                    })
+                },
+                |current, passed| {
+                    on_event(MintEvent::GasGate {
+                        current,
+                        gate,
+                        passed,
+                    })
+                },
+            )
+            .await?;
+            if current.is_none() {
+                on_event(MintEvent::WatchFinished {
+                    remaining: config.count - i * batch_size,
+                    timed_out: true,
+                });
+                return Ok(summary);
+            }
+        }
+        if let Some(clamp_mul) = config.base_fee_clamp_mul {
+            let base_fee = next_base_fee(&ctx.provider, timeout, config.max_retries).await?;
+            let priority = if gas_price.eip1559 {
+                gas_price.max_priority_fee_per_gas
+            } else {
+                U256::zero()
+            };
+            gas_price.max_fee_per_gas = clamp_max_fee(max_fee, base_fee, clamp_mul, priority);
+            on_event(MintEvent::FeeClamp {
+                base_fee,
+                max_fee: gas_price.max_fee_per_gas,
+            });
+        }
+        let start = i * batch_size;
+        let end = min((i + 1) * batch_size, config.count);
+        let current_batch_size = end - start; // 计算当前批次的实际大小
+        on_event(MintEvent::BatchStart {
+            index: i + 1,
+            total: batch_count,
+            size: current_batch_size,
+            elapsed: started.elapsed(),
+            eta: config.interval.eta(i, batch_count - i),
+        });
+        let mut txs = Vec::with_capacity(current_batch_size as usize);
+        // 和 txs 一一对应的 id, 失败时和原始交易一起打印
+        let mut ids = Vec::with_capacity(current_batch_size as usize);
+        for index in start..end {
+            // 任务条目模式下每笔使用条目自己的 data/to/value
+            let item = config.items.get(index as usize).cloned();
+            let mut tx_gas_price = gas_price.clone();
+            if value_expr {
+                let vars = ExprVars {
+                    id: config.id.as_ref().map(|id| id.id),
+                    nonce: nonce.low_u64(),
+                };
+                tx_gas_price.value = config.value.to_wei(&vars)?;
+            }
+            if let Some(item) = &item {
+                config.data = item.data.clone();
+                if let Some(value) = item.value_wei()? {
+                    tx_gas_price.value = value;
+                }
+            }
+            ids.push(config.id.as_ref().map(|id| id.id));
+            let data = config.get_hex_text(&TxContext { nonce })?;
+            let data = Bytes::from_str(&data).map_err(|e| InkError::Data(e.to_string()))?;
+            let to_address = match item.and_then(|item| item.to) {
+                Some(to_address) => to_address,
+                None => recipients.next().unwrap_or_else(|| wallet.address()),
+            };
+            let mut tx = build_tx(
+                wallet.address(),
+                chain_id,
+                to_address,
+                &tx_gas_price,
+                nonce,
+                data,
+            );
+            let gas_limit = if config.estimate_gas {
+                estimator
+                    .estimate(&ctx.provider, &config.data, &tx, timeout, config.max_retries)
+                    .await?
+            } else {
+                config.gas_limit
+            };
+            tx.set_gas(config.resolve_gas_limit(gas_limit));
+            txs.push(tx);
+            nonce = nonce + config.nonce_step;
+        }
+        let mut batch = sign_txs(wallet, &txs)?;
+        let mut hashes = record_signed_txs(start + 1, &txs, &batch, &ids, &mut on_event)?;
+        // 节点还没有接受过 type-2 交易时可能要改发 legacy, hash 会变, 等本批结果出来再导出
+        let exported = !gas_price.eip1559 || type2_accepted;
+        if exported {
+            export_tx_hashes(&config, wallet.address(), &hashes)?;
+        }
+        // 发送前自检 nonce, 包含上一批最后一笔以检查是否接续
+        let mut nonces: Vec<U256> = last_nonce.into_iter().collect();
+        nonces.extend(txs.iter().filter_map(|tx| tx.nonce().copied()));
+        assert_nonce_sequence_step(&nonces, config.nonce_step)?;
+        last_nonce = nonces.last().copied();
+
+        let mut responses = if config.bundle_mode {
+            send_bundle(ctx, &config, wallet, &batch, &mut on_event).await?
+        } else {
+            send_batch(ctx, &config, &batch, &mut batch_limit, &mut on_event).await?
+        };
+
+        let unsupported = if gas_price.eip1559 {
+            responses.iter().find_map(|response| match response {
+                Err(e) if is_tx_type_unsupported(e) => Some(e.clone()),
+                _ => None,
+            })
+        } else {
+            None
+        };
+        if let Some(error) = unsupported {
+            // type-2 交易被拒绝时 nonce 没有被占用, 用同样的 nonce 和 gas 改成 legacy 重发
+            on_event(MintEvent::LegacyFallback { error });
+            gas_price.eip1559 = false;
+            for tx in txs.iter_mut() {
+                // 任务条目的 value 各不相同, 沿用原交易的 value
+                let gas_price = GasPrice {
+                    value: tx.value().copied().unwrap_or_default(),
+                    ..gas_price.clone()
+                };
+                let mut legacy = build_tx(
+                    wallet.address(),
+                    chain_id,
+                    tx.to_addr().copied().unwrap_or_else(|| wallet.address()),
+                    &gas_price,
+                    *tx.nonce().unwrap_or(&U256::zero()),
+                    tx.data().cloned().unwrap_or_default(),
+                );
+                if let Some(gas) = tx.gas() {
+                    legacy.set_gas(*gas);
+                }
+                *tx = legacy;
+            }
+            batch = sign_txs(wallet, &txs)?;
+            // legacy 交易的 hash 和原来不同, 重新记录, 只导出最终发送的这一份
+            hashes = record_signed_txs(start + 1, &txs, &batch, &ids, &mut on_event)?;
+            responses = send_batch(ctx, &config, &batch, &mut batch_limit, &mut on_event).await?;
+        } else if gas_price.eip1559 {
+            type2_accepted = true;
+        }
+        if !exported {
+            export_tx_hashes(&config, wallet.address(), &hashes)?;
+        }
+        // bundle 要整体上链, 不单独重发其中的交易
+        if !config.bundle_mode && !config.error_policy.is_empty() {
+            retry_by_policy(
+                ctx,
+                &config,
+                &batch,
+                &mut responses,
+                i * batch_size + 1,
+                &mut batch_limit,
+                &mut on_event,
+            )
+            .await?;
+        }
+        // bundle 不经过公开的 mempool
+        if config.verify_in_mempool && !config.bundle_mode {
+            verify_in_mempool(&ctx.provider, &mut responses, timeout, config.max_retries).await?;
+        }
+
+        let confirmed_from = summary.tx_hashes.len();
+        // 和本批 tx_hashes 一一对应的已签名交易, 用于重发被丢弃的交易
+        let mut sent_txs = Vec::new();
+        // 命中 error_policy abort 的第一笔错误, 本批结果记录完后停止
+        let mut aborted = None;
+        // 响应已经按 request id 对应回请求顺序, 第 count 个响应就是 txs[count] 的结果
+        // batch[count] 是它的已签名原始交易
+        for (count, (tx_response, tx)) in responses.into_iter().zip(&txs).enumerate() {
+            let index = i * batch_size + count as u64 + 1;
+            let nonce = tx.nonce().copied().unwrap_or_default();
+            match tx_response {
+                Ok(tx_hash) => {
+                    summary.success += 1;
+                    summary.tx_hashes.push(tx_hash);
+                    sent_txs.push(batch[count].clone());
+                    on_event(MintEvent::TxSent {
+                        index,
+                        nonce,
+                        tx_hash,
+                    });
+                }
+                Err(error) if config.error_action(&error) == Some(ErrorAction::Skip) => {
+                    on_event(MintEvent::TxSkipped {
+                        index,
+                        nonce,
+                        error,
+                    });
+                }
+                Err(error) => {
+                    let message = format!("第 {} 笔(nonce {}): {}", index, nonce, error);
+                    if config.error_action(&error) == Some(ErrorAction::Abort) {
+                        aborted.get_or_insert_with(|| message.clone());
+                    }
+                    summary.failed += 1;
+                    summary.errors.push(message);
+                    summary.failures.push(FailedTx {
+                        chain_id,
+                        wallet: wallet.address(),
+                        index,
+                        nonce,
+                        to: tx.to_addr().copied().unwrap_or_else(|| wallet.address()),
+                        value: tx.value().copied().unwrap_or_default(),
+                        data: tx.data().cloned().unwrap_or_default(),
+                        error: error.clone(),
+                    });
+                    on_event(MintEvent::TxFailed {
+                        index,
+                        nonce,
+                        id: ids[count],
+                        data: tx.data().cloned().unwrap_or_default(),
+                        raw_tx: batch[count].clone(),
+                        error,
+                    });
+                }
+            }
+            summary.sent += 1;
+        }
+        if let Some(error) = aborted {
+            return Err(InkError::Send(format!(
+                "命中 error_policy 的 abort, 停止发送: {}",
+                error
+            )));
+        }
+
+        if config.wait_receipt {
+            let batch_hashes = &summary.tx_hashes[confirmed_from..];
+            let statuses = confirm_batch(
+                &ctx.provider,
+                batch_hashes,
+                Duration::from_secs(config.receipt_timeout_secs),
+                timeout,
+                config.max_retries,
+            )
+            .await?;
+            let mut batch_summary = RunSummary::default();
+            for status in &statuses {
+                batch_summary.record_status(status, gas_price.max_fee_per_gas);
+            }
+            // 被丢弃的交易 nonce 还没被占用, 原样重新广播一次
+            let dropped_txs: Vec<String> = statuses
+                .iter()
+                .zip(&sent_txs)
+                .filter(|(status, _)| matches!(status, TxStatus::Dropped))
+                .map(|(_, raw_tx)| raw_tx.clone())
+                .collect();
+            let mut resent = 0;
+            if !dropped_txs.is_empty() {
+                let responses = send_batch(
+                    ctx,
+                    &config,
+                    &dropped_txs,
+                    &mut batch_limit,
+                    &mut on_event,
+                )
+                .await?;
+                resent = responses.iter().filter(|response| response.is_ok()).count() as u64;
+            }
+            summary.confirmed += batch_summary.confirmed;
+            summary.gas_used += batch_summary.gas_used;
+            summary.gas_cost += batch_summary.gas_cost;
+            summary.pending += batch_summary.pending;
+            summary.dropped += batch_summary.dropped;
+            on_event(MintEvent::BatchConfirmed {
+                index: i + 1,
+                confirmed: batch_summary.confirmed,
+                pending: batch_summary.pending,
+                dropped: batch_summary.dropped,
+                resent,
+            });
+        }
+        on_event(MintEvent::BatchDone { index: i + 1 });
+        // 监控模式由 gas 门控轮询控制节奏
+        if i + 1 < batch_count && !config.watch_mode {
+            wait_next_batch(&mut new_blocks, config.interval.at(i)).await;
+        }
+    }
+    if config.watch_mode {
+        on_event(MintEvent::WatchFinished {
+            remaining: 0,
+            timed_out: false,
+        });
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    /// 按环境变量解析配置, extra 覆盖或追加到 test_config 的基础变量上
+    fn test_config_with(extra: &[(&str, &str)]) -> Config {
+        let mut vars = vec![
+            ("rpc_url", "http://127.0.0.1:8545"),
+            ("private_key", ""),
+            ("max_fee_per_gas", "0"),
+            ("count", "1"),
+        ];
+        for &(key, value) in extra {
+            vars.retain(|(name, _)| *name != key);
+            vars.push((key, value));
+        }
+        envy::from_iter::<_, Config>(
+            vars.into_iter().map(|(key, value)| (key.to_string(), value.to_string())),
+        )
+        .unwrap()
+    }
+
+    fn test_config(data: &str) -> Config {
+        let mut config = test_config_with(&[("data", data)]);
+        let (id, _, _) = process_id(data).unwrap();
+        config.id = id;
+        config
+    }
+
+    #[test]
+    fn process_id_range() {
+        let (id, current_id, count) = process_id(r#"{"id":"[1200-2000]"}"#).unwrap();
+        let id = id.unwrap();
+        assert_eq!(id.id, 1200);
+        assert_eq!(id.match_id, "[1200-2000]");
+        assert_eq!(current_id, Some(1200));
+        assert_eq!(count, 801);
+    }
+
+    #[test]
+    fn process_id_hex() {
+        let (id, current_id, count) = process_id(r#"{"id":"[hex:1-ff]"}"#).unwrap();
+        let id = id.unwrap();
+        assert_eq!(id.match_id, "[hex:1-ff]");
+        assert_eq!((id.start_id, id.end_id), (Some(1), Some(255)));
+        assert_eq!(id.format, IdFormat::Hex { width: 0 });
+        assert_eq!(current_id, Some(1));
+        assert_eq!(count, 255);
+
+        let (id, _, count) = process_id(r#"{"id":"[hex:00FE-0100]"}"#).unwrap();
+        assert_eq!(id.unwrap().format, IdFormat::Hex { width: 4 });
+        assert_eq!(count, 3);
+        let (id, current_id, count) = process_id(r#"{"id":"[hex:ff-a:desc]"}"#).unwrap();
+        let id = id.unwrap();
+        assert_eq!(id.direction, Direction::Desc);
+        assert_eq!(id.format, IdFormat::Hex { width: 0 });
+        assert_eq!(current_id, Some(255));
+        assert_eq!(count, 246);
+        assert!(process_id(r#"{"id":"[hex:ff-1]"}"#).is_err());
+        // 十进制语法不受影响
+        let (id, _, _) = process_id(r#"{"id":"[10-20]"}"#).unwrap();
+        assert_eq!(id.unwrap().format, IdFormat::Decimal);
+
+        let mut config = test_config(r#"{"id":"[hex:9-ff]"}"#);
+        let texts: Vec<String> =
+            (0..3).map(|_| config.process_text(&TxContext::default()).unwrap()).collect();
+        assert_eq!(texts, [r#"{"id":"9"}"#, r#"{"id":"a"}"#, r#"{"id":"b"}"#]);
+
+        let mut config = test_config(r#"{"id":"[hex:00ff-ffff]"}"#);
+        assert_eq!(config.process_text(&TxContext::default()).unwrap(), r#"{"id":"00ff"}"#);
+        assert_eq!(config.process_text(&TxContext::default()).unwrap(), r#"{"id":"0100"}"#);
+        // 超出 width 位时不截断
+        config.set_id(0x12345);
+        assert_eq!(config.process_text(&TxContext::default()).unwrap(), r#"{"id":"12345"}"#);
+    }
+
+    #[test]
+    fn id_stride_splits_wallets() {
+        let address = Address::from_low_u64_be(1);
+        let wallet_ids = |data: &str, count: u64, stride: u64, wallets: usize| {
+            let mut configs: Vec<Config> = (0..wallets)
+                .map(|_| {
+                    let mut config = test_config(data);
+                    config.count = count;
+                    config.id_stride = stride;
+                    config
+                })
+                .collect();
+            assign_id_offsets(&mut configs);
+            configs
+                .into_iter()
+                .map(|mut config| {
+                    let (_, current_id) = prepare_config(&mut config, address)?;
+                    let ids: Vec<String> = (0..config.count)
+                        .map(|_| config.process_text(&TxContext::default()).unwrap())
+                        .collect();
+                    Ok((current_id, ids))
+                })
+                .collect::<Result<Vec<_>, InkError>>()
+        };
+
+        let wallets = wallet_ids("[1-100]", 3, 10, 3).unwrap();
+        assert_eq!(
+            wallets.iter().map(|(current_id, _)| *current_id).collect::<Vec<_>>(),
+            vec![Some(1), Some(11), Some(21)]
+        );
+        assert_eq!(wallets[1].1, ["11", "12", "13"]);
+        let mut all: Vec<&String> = wallets.iter().flat_map(|(_, ids)| ids).collect();
+        all.sort();
+        all.dedup();
+        assert_eq!(all.len(), 9);
+
+        // 递减范围往小的方向错开, 最后一段受范围限制
+        let wallets = wallet_ids("[-25]", 10, 10, 3).unwrap();
+        assert_eq!(wallets[1].1.first().unwrap(), "15");
+        assert_eq!(wallets[2].1, ["5", "4", "3", "2", "1", "0"]);
+        assert!(wallet_ids("[1-20]", 10, 10, 3).is_err());
+
+        let mut config = valid_config();
+        config.count = 10;
+        config.id_stride = 5;
+        assert_invalid(config, "id_stride");
+    }
+
+    #[test]
+    fn process_id_start_equals_end() {
+        let (id, current_id, count) = process_id(r#"{"id":"[1000-1000]"}"#).unwrap();
+        assert_eq!(id.unwrap().id, 1000);
+        assert_eq!(current_id, Some(1000));
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn process_id_start_greater_than_end() {
+        assert!(matches!(
+            process_id(r#"{"id":"[2000-1000]"}"#),
+            Err(InkError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn process_id_one_side() {
+        let (id, current_id, count) = process_id(r#"{"id":"[1200-]"}"#).unwrap();
+        assert_eq!(id.unwrap().start_id, Some(1200));
+        assert_eq!(current_id, Some(1200));
+        assert_eq!(count, u64::MAX);
+
+        let (id, current_id, count) = process_id(r#"{"id":"[-2000]"}"#).unwrap();
+        assert_eq!(id.unwrap().end_id, Some(2000));
+        assert_eq!(current_id, Some(2000));
+        assert_eq!(count, 2001);
+
+        let (_, _, count) = process_id(r#"{"id":"[-0]"}"#).unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn process_id_legacy_direction() {
+        let (id, _, _) = process_id(r#"{"id":"[1200-2000]"}"#).unwrap();
+        assert_eq!(id.unwrap().direction, Direction::Asc);
+        let (id, _, _) = process_id(r#"{"id":"[1200-]"}"#).unwrap();
+        assert_eq!(id.unwrap().direction, Direction::Asc);
+        let (id, _, _) = process_id(r#"{"id":"[-2000]"}"#).unwrap();
+        assert_eq!(id.unwrap().direction, Direction::Desc);
+    }
+
+    #[test]
+    fn process_id_explicit_desc() {
+        let (id, current_id, count) = process_id(r#"{"id":"[2000-1:desc]"}"#).unwrap();
+        let id = id.unwrap();
+        assert_eq!(id.direction, Direction::Desc);
+        assert_eq!(id.match_id, "[2000-1:desc]");
+        assert_eq!(current_id, Some(2000));
+        assert_eq!(count, 2000);
+
+        let (_, current_id, count) = process_id(r#"{"id":"[2000-:desc]"}"#).unwrap();
+        assert_eq!(current_id, Some(2000));
+        assert_eq!(count, 2001);
+
+        assert!(process_id(r#"{"id":"[1-2000:desc]"}"#).is_err());
+        assert!(process_id(r#"{"id":"[-2000:desc]"}"#).is_err());
+    }
+
+    #[test]
+    fn process_id_explicit_asc() {
+        let (id, current_id, count) = process_id(r#"{"id":"[1-2000:asc]"}"#).unwrap();
+        assert_eq!(id.unwrap().direction, Direction::Asc);
+        assert_eq!(current_id, Some(1));
+        assert_eq!(count, 2000);
+
+        let (_, _, count) = process_id(r#"{"id":"[1-:asc]"}"#).unwrap();
+        assert_eq!(count, u64::MAX);
+
+        assert!(process_id(r#"{"id":"[2000-1:asc]"}"#).is_err());
+    }
+
+    #[test]
+    fn auto_set_id_follows_direction() {
+        let mut config = test_config(r#"{"id":"[2000-1:desc]"}"#);
+        assert_eq!(config.process_text(&TxContext::default()).unwrap(), r#"{"id":"2000"}"#);
+        assert_eq!(config.process_text(&TxContext::default()).unwrap(), r#"{"id":"1999"}"#);
+
+        let mut config = test_config(r#"{"id":"[1-2000:asc]"}"#);
+        assert_eq!(config.process_text(&TxContext::default()).unwrap(), r#"{"id":"1"}"#);
+        assert_eq!(config.process_text(&TxContext::default()).unwrap(), r#"{"id":"2"}"#);
+
+        let mut config = test_config(r#"{"id":"[-2000]"}"#);
+        assert_eq!(config.process_text(&TxContext::default()).unwrap(), r#"{"id":"2000"}"#);
+        assert_eq!(config.process_text(&TxContext::default()).unwrap(), r#"{"id":"1999"}"#);
+    }
+
+    #[test]
+    fn address_case_formats() {
+        let mut config = test_config(r#"{"to":"[address]"}"#);
+        config.address = "0x5AAEB6053f3e94c9b9a09f33669435e7ef1beaed".to_string();
+
+        config.address_case = AddressCase::Raw;
+        assert_eq!(
+            config.process_text(&TxContext::default()).unwrap(),
+            r#"{"to":"0x5AAEB6053f3e94c9b9a09f33669435e7ef1beaed"}"#
+        );
+
+        config.address_case = AddressCase::Lower;
+        assert_eq!(
+            config.process_text(&TxContext::default()).unwrap(),
+            r#"{"to":"0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"}"#
+        );
+
+        config.address_case = AddressCase::Checksum;
+        assert_eq!(
+            config.process_text(&TxContext::default()).unwrap(),
+            r#"{"to":"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"}"#
+        );
+    }
+
+    #[test]
+    fn timestamp_tokens() {
+        let now = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+        assert_eq!(
+            replace_timestamp_tokens(r#"{"t":"[timestamp]","ms":"[timestamp_ms]"}"#, now),
+            r#"{"t":"1700000000","ms":"1700000000123"}"#
+        );
+        assert_eq!(replace_timestamp_tokens(r#"{"id":"1"}"#, now), r#"{"id":"1"}"#);
+    }
+
+    #[test]
+    fn random_tokens() {
+        let text = replace_random_tokens(r#"{"r":"[random:4]","d":"[random]"}"#).unwrap();
+        let re = regex::Regex::new(r#"^\{"r":"([0-9a-f]+)","d":"([0-9a-f]+)"}$"#).unwrap();
+        let caps = re.captures(&text).unwrap();
+        assert_eq!(caps[1].len(), 8);
+        assert_eq!(caps[2].len(), DEFAULT_RANDOM_BYTES * 2);
+
+        assert_ne!(
+            replace_random_tokens("[random:16]").unwrap(),
+            replace_random_tokens("[random:16]").unwrap()
+        );
+        assert_eq!(replace_random_tokens("[random:1024]").unwrap().len(), 2048);
+
+        // 过大或超出 usize 范围的 N 报错, 不再静默改用默认长度
+        let too_large = [
+            "[random:1025]",
+            "[random:1000000000000]",
+            "[random:99999999999999999999999]",
+        ];
+        for text in too_large {
+            match replace_random_tokens(text) {
+                Err(InkError::Data(e)) => assert!(e.contains("不能超过 1024"), "{}", e),
+                other => panic!("{:?}", other),
+            }
+            assert_invalid(test_config(text), "不能超过 1024");
+        }
+    }
+
+    #[test]
+    fn nonce_token() {
+        let mut config = test_config(r#"{"id":"[1-:asc]","nonce":"[nonce]"}"#);
+        let ctx = TxContext {
+            nonce: U256::from(7),
+        };
+        assert_eq!(config.process_text(&ctx).unwrap(), r#"{"id":"1","nonce":"7"}"#);
+        let ctx = TxContext {
+            nonce: U256::from(8),
+        };
+        assert_eq!(config.process_text(&ctx).unwrap(), r#"{"id":"2","nonce":"8"}"#);
+        assert_eq!(
+            config.get_hex_text(&ctx).unwrap(),
+            hex::encode_prefixed(r#"data:,{"id":"3","nonce":"8"}"#)
+        );
+    }
+
+    #[test]
+    fn expr_token() {
+        let mut config = test_config(r#"{"id":"[5-:asc]","amt":"[expr:id*10]"}"#);
+        assert_eq!(
+            config.process_text(&TxContext::default()).unwrap(),
+            r#"{"id":"5","amt":"50"}"#
+        );
+        assert_eq!(
+            config.process_text(&TxContext::default()).unwrap(),
+            r#"{"id":"6","amt":"60"}"#
+        );
+
+        let mut config = test_config(r#"{"amt":"[expr:1000/(nonce-7)]"}"#);
+        let ctx = TxContext {
+            nonce: U256::from(7),
+        };
+        let error = config.get_hex_text(&ctx).unwrap_err();
+        assert!(error.to_string().contains("除数为 0"), "{}", error);
+    }
+
+    #[test]
+    fn custom_placeholders() {
+        let mut config = test_config(r#"{"tick":"[tick]","net":"[net]","to":"[address]"}"#);
+        config.address = "0xabc".to_string();
+        config.placeholders = serde_json::from_str(r#"{"[tick]":"[net]","[net]":"eth"}"#).unwrap();
+        // [tick] 替换出来的 [net] 不会被再次替换
+        assert_eq!(
+            config.process_text(&TxContext::default()).unwrap(),
+            r#"{"tick":"[net]","net":"eth","to":"0xabc"}"#
+        );
+    }
+
+    #[test]
+    fn custom_placeholders_longest_first() {
+        let placeholders: BTreeMap<String, String> =
+            serde_json::from_str(r#"{"[a]":"1","[a]]":"2"}"#).unwrap();
+        assert_eq!(replace_placeholders("[a]] [a]", &placeholders), "2 1");
+        assert_eq!(replace_placeholders("[b]", &BTreeMap::new()), "[b]");
+    }
+
+    #[test]
+    fn placeholders_from_env() {
+        let config =
+            test_config_with(&[("data", "[tick]"), ("placeholders", r#"{"[tick]":"pi"}"#)]);
+        assert_eq!(config.placeholders["[tick]"], "pi");
+    }
+
+    #[test]
+    fn validate_hex_data_checks() {
+        assert!(validate_hex_data("0x").is_ok());
+        assert!(validate_hex_data("0x646174613a2c7B7D").is_ok());
+
+        match validate_hex_data("0x646174613a2c7") {
+            Err(InkError::Data(e)) => assert!(e.contains("长度为奇数(13 个字符)"), "{}", e),
+            other => panic!("{:?}", other),
+        }
+        match validate_hex_data("0x646174613g2c") {
+            Err(InkError::Data(e)) => {
+                assert!(e.contains("第 10 个字符 'g'"), "{}", e);
+                assert!(e.contains("...46174613g"), "{}", e);
+            }
+            other => panic!("{:?}", other),
+        }
+        match validate_hex_data("0x6461 74") {
+            Err(InkError::Data(e)) => assert!(e.contains("第 5 个字符 ' '"), "{}", e),
+            other => panic!("{:?}", other),
+        }
+
+        let mut config = test_config("0x6461746");
+        assert!(matches!(
+            config.get_hex_text(&TxContext::default()),
+            Err(InkError::Data(_))
+        ));
+        let mut config = valid_config();
+        config.data = "0x64zz".to_string();
+        assert_invalid(config, "不是十六进制字符");
+    }
+
+    #[test]
+    fn expected_selector_checks() {
+        // transfer(address,uint256)
+        let transfer = "0xa9059cbb000000000000000000000000000000000000000000000000000000000000000a";
+        assert_eq!(
+            parse_selector("0xA9059CBB").unwrap(),
+            [0xa9, 0x05, 0x9c, 0xbb]
+        );
+        assert_eq!(
+            parse_selector("a9059cbb").unwrap(),
+            [0xa9, 0x05, 0x9c, 0xbb]
+        );
+        assert!(parse_selector("0xa9059c").is_err());
+        assert!(parse_selector("0xa9059cbbcc").is_err());
+        assert!(parse_selector("0xzz059cbb").is_err());
+        assert!(check_selector(transfer, [0xa9, 0x05, 0x9c, 0xbb]).is_ok());
+        match check_selector("0x095ea7b3", [0xa9, 0x05, 0x9c, 0xbb]) {
+            Err(InkError::Data(e)) => {
+                assert!(
+                    e.contains("0x095ea7b3 与 expected_selector 0xa9059cbb"),
+                    "{}",
+                    e
+                );
+            }
+            other => panic!("{:?}", other),
+        }
+        // 不足 4 字节的 data 也算不符
+        assert!(check_selector("0xa905", [0xa9, 0x05, 0x9c, 0xbb]).is_err());
+
+        let mut config = test_config(transfer);
+        config.expected_selector = Some("0xa9059cbb".to_string());
+        assert_eq!(
+            config.get_hex_text(&TxContext::default()).unwrap(),
+            transfer
+        );
+        config.data = transfer.replacen("a9", "a8", 1);
+        assert!(matches!(
+            config.get_hex_text(&TxContext::default()),
+            Err(InkError::Data(_))
+        ));
+        // 文本铭文不检查选择器
+        config.data = "hello".to_string();
+        assert!(config.get_hex_text(&TxContext::default()).is_ok());
+
+        let mut config = valid_config();
+        config.data = transfer.replacen("a9", "a8", 1);
+        config.expected_selector = Some("0xa9059cbb".to_string());
+        assert_invalid(config.clone(), "与 expected_selector 0xa9059cbb 不符");
+        config.expected_selector = Some("0x1234".to_string());
+        assert_invalid(config, "expected_selector 必须是 4 字节");
+    }
+
+    #[test]
+    fn decode_inscription_kinds() {
+        let decoded = decode_inscription(&hex::encode_prefixed("data:,hello world")).unwrap();
+        assert_eq!(decoded.prefix.as_deref(), Some("data:,"));
+        assert_eq!(decoded.body, "hello world");
+        assert!(decoded.valid_utf8);
+        assert_eq!(decoded.json, None);
+
+        let text = r#"data:application/json,{"p":"erc-20","op":"mint","amt":"1000"}"#;
+        let decoded = decode_inscription(&hex::encode_prefixed(text)).unwrap();
+        assert_eq!(decoded.prefix.as_deref(), Some("data:application/json,"));
+        let json = decoded.json.unwrap();
+        assert_eq!(json["op"], "mint");
+        // 保持原来的字段顺序
+        let keys: Vec<&String> = json.as_object().unwrap().keys().collect();
+        assert_eq!(keys, ["p", "op", "amt"]);
+
+        // 没有前缀的纯文本
+        let decoded = decode_inscription(&hex::encode_prefixed("hello")).unwrap();
+        assert_eq!(decoded.prefix, None);
+        assert_eq!(decoded.body, "hello");
+
+        // 二进制数据 lossy 显示, 不尝试解析 JSON
+        let decoded = decode_inscription("0x1249c58bff").unwrap();
+        assert!(!decoded.valid_utf8);
+        assert_eq!(decoded.prefix, None);
+        assert!(decoded.body.contains('\u{FFFD}'));
+        assert_eq!(decoded.json, None);
+
+        assert!(decode_inscription("0x123").is_err());
+    }
+
+    #[test]
+    fn decode_hex_utf8() {
+        let data = hex::encode_prefixed("data:,hello");
+        assert_eq!(decode_hex(&data).unwrap(), "data:,hello");
+        assert_eq!(
+            decode_hex_lossy(&data).unwrap(),
+            ("data:,hello".to_string(), true)
+        );
+
+        let data = "0x68ff69";
+        assert!(matches!(decode_hex(data), Err(InkError::Data(_))));
+        assert_eq!(
+            decode_hex_lossy(data).unwrap(),
+            ("h\u{FFFD}i".to_string(), false)
+        );
+
+        assert!(decode_hex_lossy("0xzz").is_err());
+    }
+
+    #[test]
+    fn base64_data() {
+        let mut config = test_config("base64:aGVsbG8=");
+        assert_eq!(
+            config.get_hex_text(&TxContext::default()).unwrap(),
+            "0x68656c6c6f"
+        );
+
+        let mut config = test_config("base64:AP8Q");
+        assert_eq!(
+            config.get_hex_text(&TxContext::default()).unwrap(),
+            "0x00ff10"
+        );
+
+        let mut config = test_config("base64:not base64!");
+        assert!(config.get_hex_text(&TxContext::default()).is_err());
+    }
+
+    #[test]
+    fn gzip_round_trip() {
+        let text = r#"{"p":"erc-20","op":"mint","tick":"eths","amt":"1000"}"#.repeat(10);
+        let compressed = gzip_compress(text.as_bytes()).unwrap();
+        assert!(compressed.len() < text.len());
+        assert_eq!(gzip_decompress(&compressed).unwrap(), text.as_bytes());
+
+        let mut config = test_config(&text);
+        config.compress = Compress::Gzip;
+        config.prefix = "data:application/gzip,".to_string();
+        assert!(config.is_gzip_text());
+        let data = config.get_hex_text(&TxContext::default()).unwrap();
+        assert_eq!(
+            decode_hex_gzip(&data, &config.prefix).unwrap(),
+            format!("{}{}", config.prefix, text)
+        );
+        assert!(decode_hex_gzip(&data, "data:,").is_err());
+    }
+
+    #[test]
+    fn encoding_pipeline_data() {
+        let mut config = test_config("hello [nonce]");
+        config.encoding = Some("template,prefix,hex".to_string());
+        let ctx = TxContext {
+            nonce: U256::from(3),
+        };
+        assert_eq!(
+            config.get_hex_text(&ctx).unwrap(),
+            hex::encode_prefixed("data:,hello 3")
+        );
+        // 没有 template 时占位符原样保留
+        config.encoding = Some("prefix".to_string());
+        assert_eq!(
+            config.get_hex_text(&ctx).unwrap(),
+            hex::encode_prefixed("data:,hello [nonce]")
+        );
+        config.encoding = Some("[template, gzip, base64]".to_string());
+        let data =
+            decode_hex_bytes(config.get_hex_text(&ctx).unwrap().trim_start_matches("0x")).unwrap();
+        let gzipped = general_purpose::STANDARD.decode(data).unwrap();
+        assert_eq!(gzip_decompress(&gzipped).unwrap(), b"hello 3");
+        assert!(!config.is_gzip_text());
+
+        let mut config = valid_config();
+        config.encoding = Some("template,rot13".to_string());
+        assert_invalid(config.clone(), "encoding 不支持的步骤: rot13");
+        config.encoding = Some("template,gzip".to_string());
+        config.compress = Compress::Gzip;
+        assert_invalid(config, "不能同时设置 compress");
+    }
+
+    #[test]
+    fn data_source_literal() {
+        assert_eq!(
+            read_data_source(r#"{"p":"erc-20"}"#).unwrap(),
+            vec![r#"{"p":"erc-20"}"#.to_string()]
+        );
+    }
+
+    #[test]
+    fn data_source_lines() {
+        let input = "0x01\n\n  0x02  \n0x03";
+        assert_eq!(
+            read_data_lines(std::io::Cursor::new(input)).unwrap(),
+            vec!["0x01", "0x02", "0x03"]
+        );
+        match read_data_reader(std::io::Cursor::new("\n  \n"), "stdin") {
+            Err(InkError::Config(e)) => assert!(e.contains("没有任何 data")),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn data_source_file() {
+        let path = env::temp_dir().join("evm_ink_rs_data_source_test.txt");
+        std::fs::write(&path, "0x01\n0x02\n").unwrap();
+        let data = read_data_source(&format!("@file:{}", path.display())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(data, vec!["0x01", "0x02"]);
+
+        let empty = env::temp_dir().join("evm_ink_rs_data_source_empty.txt");
+        std::fs::write(&empty, "\n\n").unwrap();
+        let result = read_data_source(&format!("@file:{}", empty.display()));
+        std::fs::remove_file(&empty).unwrap();
+        assert!(matches!(result, Err(InkError::Config(_))));
+
+        assert!(read_data_source("@file:/not/exist/evm_ink_rs").is_err());
+    }
+
+    #[test]
+    fn data_list_rotates() {
+        let mut config = test_config("0x01");
+        config.data_list = vec!["0x01".to_string(), "0x02".to_string()];
+        let ctx = TxContext::default();
+        assert_eq!(config.get_hex_text(&ctx).unwrap(), "0x01");
+        assert_eq!(config.get_hex_text(&ctx).unwrap(), "0x02");
+        assert_eq!(config.get_hex_text(&ctx).unwrap(), "0x01");
+    }
+
+    #[test]
+    fn builder_fills_defaults() {
+        let config = Config::builder()
+            .rpc_url("http://127.0.0.1:8545")
+            .private_key("0x01")
+            .data("data")
+            .count(3)
+            .max_fee_per_gas(10.0)
+            .build()
+            .unwrap();
+        assert_eq!(config.rpc_url, "http://127.0.0.1:8545");
+        assert_eq!(config.count, 3);
+        assert_eq!(config.max_fee_per_gas, 10.0);
+        assert_eq!(config.prefix, "data:,");
+        assert_eq!(config.gas_limit, 50000);
+        assert_eq!(config.batch_size, 100);
+        assert_eq!(config.value, TxValue::Fixed(0.0));
+        assert_eq!(config.interval, IntervalStrategy::Fixed(0.0));
+        assert!(!config.quiet_banner);
+        assert_eq!(config.gas_limit_buffer_percent, 0.0);
+        assert!(!config.priority_equals_max);
+        assert!(config.strict_utf8);
+        assert_eq!(config.max_priority_fee_per_gas, None);
+
+        let config = Config::builder()
+            .rpc_url("http://127.0.0.1:8545")
+            .private_key("0x01")
+            .data("data")
+            .count(3)
+            .max_fee_per_gas(10.0)
+            .gas_limit(60000)
+            .placeholder("[tick]", "pi")
+            .build()
+            .unwrap();
+        assert_eq!(config.gas_limit, 60000);
+        assert_eq!(config.placeholders["[tick]"], "pi");
+    }
+
+    #[test]
+    fn builder_missing_required() {
+        let err = Config::builder().data("data").build().unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("rpc_url"));
+        assert!(message.contains("private_key"));
+        assert!(message.contains("count"));
+        assert!(message.contains("max_fee_per_gas"));
+        assert!(!message.contains("data"));
+    }
+
+    #[test]
+    fn builder_invalid_rpc_url() {
+        let result = Config::builder()
+            .rpc_url("not a url")
+            .private_key("0x01")
+            .data("data")
+            .count(1)
+            .max_fee_per_gas(1.0)
+            .build();
+        assert!(matches!(result, Err(InkError::Config(_))));
+    }
+
+    fn valid_config() -> Config {
+        Config::builder()
+            .rpc_url("http://127.0.0.1:8545")
+            .private_key("0x01")
+            .data(r#"{"id":"[1-10]"}"#)
+            .count(1)
+            .max_fee_per_gas(10.0)
+            .max_priority_fee_per_gas(1.0)
+            .build()
+            .unwrap()
+    }
+
+    fn assert_invalid(config: Config, expected: &str) {
+        let errors = config.validate().unwrap_err();
+        assert!(
+            errors.iter().any(|e| e.contains(expected)),
+            "{:?} 中没有 {}",
+            errors,
+            expected
+        );
+    }
+
+    #[test]
+    fn validate_ok() {
+        assert!(valid_config().validate().is_ok());
+    }
+
+    #[test]
+    fn validate_failures() {
+        let mut config = valid_config();
+        config.data = String::new();
+        assert_invalid(config, "data 不能为空");
+
+        let mut config = valid_config();
+        config.data = "[20-10]".to_string();
+        assert_invalid(config, "id 范围");
+
+        let mut config = valid_config();
+        config.rpc_url = "not a url".to_string();
+        assert_invalid(config, "rpc_url");
+
+        let mut config = valid_config();
+        config.private_rpc_url = Some("not a url".to_string());
+        assert_invalid(config, "private_rpc_url");
+
+        let mut config = valid_config();
+        config.reserve_ether = -1.0;
+        assert_invalid(config, "reserve_ether");
+
+        let mut config = valid_config();
+        config.gas_limit = 0;
+        assert_invalid(config, "gas_limit");
+
+        let mut config = valid_config();
+        config.concurrency = 0;
+        assert_invalid(config, "concurrency");
+
+        let mut config = valid_config();
+        config.batch_size = 0;
+        assert_invalid(config, "batch_size");
+
+        let mut config = valid_config();
+        config.max_fee_per_gas = -1.0;
+        assert_invalid(config, "max_fee_per_gas 不能小于 0");
+
+        let mut config = valid_config();
+        config.max_priority_fee_per_gas = Some(20.0);
+        assert_invalid(config, "不能大于 max_fee_per_gas");
+
+        let mut config = valid_config();
+        config.value = TxValue::Fixed(-0.1);
+        assert_invalid(config, "value");
+
+        let mut config = valid_config();
+        config.interval = IntervalStrategy::Fixed(-1.0);
+        assert_invalid(config, "interval");
+
+        let mut config = valid_config();
+        config.to_address = Some("0x1234".to_string());
+        assert_invalid(config, "to_address");
+
+        let mut config = valid_config();
+        config.funding_private_key = Some("0x1234zz".to_string());
+        config.min_gas_ether = 0.01;
+        assert_invalid(config, "funding_private_key 不是合法的私钥");
+
+        let mut config = valid_config();
+        config.funding_private_key = Some("0x02".to_string());
+        assert_invalid(config, "min_gas_ether 必须大于 0");
+
+        let mut config = valid_config();
+        config.send_on_new_block = true;
+        assert_invalid(config.clone(), "send_on_new_block 需要设置 ws_url");
+        config.ws_url = Some("wss://ethereum-holesky-rpc.publicnode.com".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn approve_config() {
+        let mut config = valid_config();
+        assert_eq!(config.approve().unwrap(), None);
+        config.approve_token = Some("0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf".to_string());
+        assert_invalid(config.clone(), "需要设置 approve_spender");
+        config.approve_spender = Some("0x0000000000000000000000000000000000000001".to_string());
+        assert_invalid(config.clone(), "需要设置 approve_amount");
+        config.approve_amount = Some("1.5".to_string());
+        assert_invalid(config.clone(), "approve_amount 不合法");
+        config.approve_amount = Some("max".to_string());
+        assert!(config.validate().is_ok());
+        let approve = config.approve().unwrap().unwrap();
+        assert_eq!(approve.spender, Address::from_low_u64_be(1));
+        assert_eq!(approve.amount, U256::MAX);
+
+        config.approve_spender = Some("0x1234".to_string());
+        assert_invalid(config, "approve_spender 不是合法地址");
+    }
+
+    #[test]
+    fn validate_collects_all_errors() {
+        let mut config = valid_config();
+        config.batch_size = 0;
+        config.gas_limit = 0;
+        config.rpc_url = String::new();
+        assert_eq!(config.validate().unwrap_err().len(), 3);
+    }
+
+    #[test]
+    fn auto_count_from_env() {
+        let mut config = test_config("data");
+        assert!(!config.is_auto_count());
+        config = test_config_with(&[("count", "auto"), ("data", "data")]);
+        assert!(config.is_auto_count());
+        assert!(config.validate().is_ok());
+    }
+
+    fn gas_price(max_fee_gwei: u64, value_wei: u64) -> GasPrice {
+        GasPrice {
+            eip1559: false,
+            max_fee_per_gas: U256::from(max_fee_gwei) * U256::exp10(9),
+            max_priority_fee_per_gas: U256::zero(),
+            value: U256::from(value_wei),
+        }
+    }
+
+    #[test]
+    fn clamp_max_fee_by_base_fee() {
+        let gwei = U256::exp10(9);
+        // base fee 低时 上限收紧到 base * 2 + priority
+        assert_eq!(clamp_max_fee(gwei * 100, gwei * 10, 2.0, gwei * 2), gwei * 22);
+        // base fee 高时 不超过配置的 max_fee
+        assert_eq!(clamp_max_fee(gwei * 100, gwei * 60, 2.0, gwei * 2), gwei * 100);
+        assert_eq!(clamp_max_fee(gwei * 100, gwei * 10, 1.25, U256::zero()), gwei * 25 / 2);
+        assert_eq!(clamp_max_fee(gwei * 100, U256::zero(), 2.0, gwei), gwei);
+
+        let mut config = valid_config();
+        config.base_fee_clamp_mul = Some(0.0);
+        assert_invalid(config, "base_fee_clamp_mul");
+    }
+
+    #[test]
+    fn gas_buffer_rounds_up() {
+        assert_eq!(apply_gas_buffer(50000, 0.0), 50000);
+        assert_eq!(apply_gas_buffer(50000, 10.0), 55000);
+        // 2100.1 向上取整为 2101
+        assert_eq!(apply_gas_buffer(21001, 10.0), 23102);
+        assert_eq!(apply_gas_buffer(50000, 0.1), 50050);
+        assert_eq!(apply_gas_buffer(3, 50.0), 5);
+        assert_eq!(apply_gas_buffer(u64::MAX, 10.0), u64::MAX);
+
+        let mut config = valid_config();
+        config.gas_limit = 60000;
+        config.gas_limit_buffer_percent = 25.0;
+        assert_eq!(config.buffered_gas_limit(), 75000);
+        config.gas_limit_buffer_percent = -1.0;
+        assert_invalid(config, "gas_limit_buffer_percent");
+    }
+
+    #[test]
+    fn resolve_gas_limit_clamps() {
+        let mut config = valid_config();
+        config.gas_limit_min = Some(30000);
+        config.gas_limit_max = Some(200000);
+        // 低于下限
+        assert_eq!(config.resolve_gas_limit(21000), 30000);
+        // 高于上限, 估算抽风时也不会构造出离谱的交易
+        assert_eq!(config.resolve_gas_limit(10_000_000), 200000);
+        // 区间内不变
+        assert_eq!(config.resolve_gas_limit(50000), 50000);
+        // 先加余量再 clamp
+        config.gas_limit_buffer_percent = 10.0;
+        assert_eq!(config.resolve_gas_limit(190000), 200000);
+        assert_eq!(config.resolve_gas_limit(100000), 110000);
+        config.gas_limit = 500000;
+        assert_eq!(config.buffered_gas_limit(), 200000);
+
+        config.gas_limit_max = None;
+        assert_eq!(config.resolve_gas_limit(10_000_000), 11_000_000);
+        config.gas_limit_max = Some(20000);
+        assert_invalid(config, "gas_limit_min(30000) 不能大于 gas_limit_max(20000)");
+    }
+
+    #[test]
+    fn max_mint_count_by_balance() {
+        // 每笔最多花费 50000 * 10 gwei = 0.0005 ether
+        let price = gas_price(10, 0);
+        let cost = U256::from(50000u64) * U256::exp10(10);
+        assert_eq!(max_mint_count(cost - 1, U256::zero(), 50000, &price), 0);
+        assert_eq!(max_mint_count(cost, U256::zero(), 50000, &price), 1);
+        assert_eq!(max_mint_count(cost * 3 + 1, U256::zero(), 50000, &price), 3);
+        // 保留余额不参与计算
+        assert_eq!(max_mint_count(cost * 3, cost, 50000, &price), 2);
+        assert_eq!(max_mint_count(cost, cost * 2, 50000, &price), 0);
+
+        // value 也计入每笔花费
+        let gas_price_with_value = gas_price(10, 1);
+        assert_eq!(
+            max_mint_count(cost * 3, U256::zero(), 50000, &gas_price_with_value),
+            2
+        );
+    }
+
+    #[test]
+    fn balance_preview_counts() {
+        let mut config = valid_config();
+        config.gas_limit = 50000;
+        config.max_fee_per_gas = 10.0;
+        config.value = TxValue::Fixed(0.0);
+        config.count = 3;
+        let cost = U256::from(50000u64) * U256::exp10(10);
+        let address = Address::from_low_u64_be(1);
+
+        let preview = BalancePreview::new(&config, address, cost * 5).unwrap();
+        assert_eq!(preview.affordable, 5);
+        assert_eq!(preview.planned, 3);
+        assert!(preview.is_enough());
+        let preview = BalancePreview::new(&config, address, cost * 2).unwrap();
+        assert!(!preview.is_enough());
+
+        // 按余额自动计算笔数时总是够的
+        config.count = 0;
+        let preview = BalancePreview::new(&config, address, cost * 2).unwrap();
+        assert_eq!(preview.planned, 2);
+        assert!(preview.is_enough());
+    }
+
+    #[test]
+    fn gas_estimator_cache_hit() {
+        let mut estimator = GasEstimator::new(0);
+        assert_eq!(estimator.lookup("a"), None);
+        estimator.insert("a", 30000);
+        assert_eq!(estimator.lookup("a"), Some(30000));
+        assert_eq!(estimator.lookup("a"), Some(30000));
+        // 不同模板不共享缓存
+        assert_eq!(estimator.lookup("b"), None);
+    }
+
+    #[test]
+    fn gas_estimator_sample_interval() {
+        let mut estimator = GasEstimator::new(3);
+        estimator.insert("a", 30000);
+        // insert 本身算一次使用
+        assert_eq!(estimator.lookup("a"), Some(30000));
+        assert_eq!(estimator.lookup("a"), Some(30000));
+        assert_eq!(estimator.lookup("a"), None);
+        estimator.insert("a", 31000);
+        assert_eq!(estimator.lookup("a"), Some(31000));
+    }
+
+    #[test]
+    fn intrinsic_gas_by_calldata() {
+        assert_eq!(calldata_intrinsic_gas(&[]), 21000);
+        assert_eq!(calldata_intrinsic_gas(&[0, 0]), 21008);
+        assert_eq!(calldata_intrinsic_gas(&[1, 0xff]), 21032);
+        let data = format!(
+            "data:,{}",
+            r#"{"p":"erc-20","op":"mint","tick":"eths","amt":"1000"}"#
+        );
+        assert_eq!(
+            calldata_intrinsic_gas(data.as_bytes()),
+            21000 + 16 * data.len() as u64
+        );
+        assert_eq!(
+            calldata_intrinsic_gas(&[0, 1, 0, 2, 3]),
+            21000 + 4 * 2 + 16 * 3
+        );
+    }
+
+    #[tokio::test]
+    async fn gas_estimator_skips_eoa() {
+        let (provider, mock) = Provider::mocked();
+        let timeout = Duration::from_secs(1);
+        let mut estimator = GasEstimator::new(0);
+        let eoa = Address::from_low_u64_be(1);
+        let tx: TypedTransaction = TransactionRequest::new().to(eoa).data(vec![0, 1, 2]).into();
+        // eth_getCode 返回空代码
+        mock.push::<Bytes, _>(Bytes::default()).unwrap();
+        let gas = estimator
+            .estimate(&provider, "a", &tx, timeout, 0)
+            .await
+            .unwrap();
+        assert_eq!(gas, 21000 + 4 + 16 * 2);
+        // 已经知道是 EOA 不再请求节点, mock 没有响应时请求会失败
+        let gas = estimator
+            .estimate(&provider, "a", &tx, timeout, 0)
+            .await
+            .unwrap();
+        assert_eq!(gas, 21036);
+
+        let contract = Address::from_low_u64_be(2);
+        mock.push::<Bytes, _>(Bytes::from(vec![0x60, 0x80])).unwrap();
+        assert!(estimator
+            .is_contract(&provider, contract, timeout, 0)
+            .await
+            .unwrap());
+        let tx: TypedTransaction = TransactionRequest::new().to(contract).data(vec![1]).into();
+        mock.push(U256::from(45000)).unwrap();
+        let gas = estimator
+            .estimate(&provider, "b", &tx, timeout, 0)
+            .await
+            .unwrap();
+        assert_eq!(gas, 45000);
+    }
+
+    #[test]
+    fn eta_from_interval() {
+        assert_eq!(estimate_eta(0.0, 10), Duration::ZERO);
+        assert_eq!(estimate_eta(1.5, 4), Duration::from_secs(6));
+        assert_eq!(estimate_eta(3.0, 0), Duration::ZERO);
+    }
+
+    #[test]
+    fn interval_strategies() {
+        assert_eq!("0.5".parse(), Ok(IntervalStrategy::Fixed(0.5)));
+        assert_eq!("1e-3".parse(), Ok(IntervalStrategy::Fixed(0.001)));
+        assert_eq!("0.5-1.5".parse(), Ok(IntervalStrategy::Range(0.5, 1.5)));
+        assert_eq!(
+            " 0.1 * 1.5 ".parse(),
+            Ok(IntervalStrategy::Geometric {
+                initial: 0.1,
+                ratio: 1.5
+            })
+        );
+        let negative: IntervalStrategy = "-1".parse().unwrap();
+        assert!(negative.validate().is_err());
+        assert!("fast".parse::<IntervalStrategy>().is_err());
+        assert!("0.1*".parse::<IntervalStrategy>().is_err());
+        assert!(IntervalStrategy::Range(2.0, 1.0).validate().is_err());
+        assert!(IntervalStrategy::Geometric {
+            initial: 1.0,
+            ratio: 0.0
+        }
+        .validate()
+        .is_err());
+
+        let fixed = IntervalStrategy::Fixed(1.5);
+        assert_eq!(fixed.at(0), 1.5);
+        assert_eq!(fixed.at(9), 1.5);
+        assert_eq!(fixed.eta(3, 4), Duration::from_secs(6));
+
+        let range = IntervalStrategy::Range(0.5, 1.5);
+        for batch in 0..20 {
+            let interval = range.at(batch);
+            assert!((0.5..=1.5).contains(&interval), "{}", interval);
+        }
+        assert_eq!(range.eta(0, 4), Duration::from_secs(4));
+        assert_eq!(IntervalStrategy::Range(2.0, 2.0).at(0), 2.0);
+
+        // 每批乘 2: 1, 2, 4, 8
+        let grow = IntervalStrategy::Geometric {
+            initial: 1.0,
+            ratio: 2.0,
+        };
+        assert_eq!(grow.at(0), 1.0);
+        assert_eq!(grow.at(3), 8.0);
+        assert_eq!(grow.eta(0, 4), Duration::from_secs(15));
+        assert_eq!(grow.eta(2, 2), Duration::from_secs(12));
+        assert_eq!(grow.eta(0, 5000), Duration::MAX);
+        // 倍数小于 1 时逐渐加速
+        let decay = IntervalStrategy::Geometric {
+            initial: 8.0,
+            ratio: 0.5,
+        };
+        assert_eq!(decay.at(3), 1.0);
+        assert_eq!(decay.eta(0, 4), Duration::from_secs(15));
+        let flat = IntervalStrategy::Geometric {
+            initial: 2.0,
+            ratio: 1.0,
+        };
+        assert_eq!(flat.eta(5, 3), Duration::from_secs(6));
+
+        let config = test_config_with(&[("interval", "0.1*1.5")]);
+        assert_eq!(
+            config.interval,
+            IntervalStrategy::Geometric {
+                initial: 0.1,
+                ratio: 1.5
+            }
+        );
+        let wallet: WalletOverride = serde_json::from_str(r#"{"interval": 2}"#).unwrap();
+        assert_eq!(wallet.interval, Some(IntervalStrategy::Fixed(2.0)));
+    }
+
+    #[test]
+    fn parse_number_formats() {
+        assert_eq!(parse_number("1e-6", "ether").unwrap(), 1e-6);
+        assert_eq!(parse_number("1_000", "gwei").unwrap(), 1000.0);
+        assert_eq!(parse_number("0.000001", "ether").unwrap(), 0.000001);
+        assert_eq!(parse_number(" 2.5E3 ", "gwei").unwrap(), 2500.0);
+        assert_eq!(parse_number("5gwei", "gwei").unwrap(), 5.0);
+        assert_eq!(parse_number("5 Gwei", "gwei").unwrap(), 5.0);
+        assert_eq!(parse_number("1ether", "gwei").unwrap(), 1e9);
+        assert_eq!(parse_number("5gwei", "ether").unwrap(), 5e-9);
+        assert!(parse_number("abc", "gwei").is_err());
+        assert!(parse_number("5btc", "gwei").is_err());
+    }
+
+    #[test]
+    fn config_accepts_number_formats() {
+        let config = test_config_with(&[
+            ("max_fee_per_gas", "1_000"),
+            ("max_priority_fee_per_gas", "1e9wei"),
+            ("gas_limit", "1e5"),
+            ("value", "1e-6"),
+            ("data", "0x00"),
+        ]);
+        assert_eq!(config.max_fee_per_gas, 1000.0);
+        assert_eq!(config.max_priority_fee_per_gas, Some(1.0));
+        assert_eq!(config.gas_limit, 100_000);
+        assert_eq!(config.value, TxValue::Fixed(1e-6));
+    }
+
+    #[test]
+    fn init_gas_price_converts_units() {
+        let mut config = test_config("0x00");
+        config.max_fee_per_gas = 1.5;
+        config.max_priority_fee_per_gas = Some(0.1);
+        config.value = TxValue::Fixed(0.01);
+        let gas_price = config.init_gas_price().unwrap();
+        assert!(gas_price.eip1559);
+        assert_eq!(gas_price.max_fee_per_gas, U256::from(1_500_000_000u64));
+        assert_eq!(gas_price.max_priority_fee_per_gas, U256::from(100_000_000u64));
+        assert_eq!(gas_price.value, U256::from(10_000_000_000_000_000u64));
+    }
+
+    #[test]
+    fn init_gas_price_priority_equals_max() {
+        let mut config = test_config("0x00");
+        config.max_fee_per_gas = 1.5;
+        config.priority_equals_max = true;
+        let gas_price = config.init_gas_price().unwrap();
+        assert!(gas_price.eip1559);
+        assert_eq!(gas_price.max_fee_per_gas, U256::from(1_500_000_000u64));
+        assert_eq!(gas_price.max_priority_fee_per_gas, gas_price.max_fee_per_gas);
+
+        // 同时设置时忽略 max_priority_fee_per_gas
+        config.max_priority_fee_per_gas = Some(0.1);
+        let gas_price = config.init_gas_price().unwrap();
+        assert_eq!(gas_price.max_priority_fee_per_gas, U256::from(1_500_000_000u64));
+    }
+
+    #[test]
+    fn wallet_start_jitter_range() {
+        let mut config = valid_config();
+        assert_eq!(config.start_jitter_range().unwrap(), None);
+        assert_eq!(config.start_jitter(), Duration::ZERO);
+
+        config.wallet_start_jitter_secs = Some("0.5-2".to_string());
+        assert_eq!(config.start_jitter_range().unwrap(), Some((0.5, 2.0)));
+        for _ in 0..100 {
+            let jitter = config.start_jitter();
+            assert!(jitter >= Duration::from_millis(500) && jitter <= Duration::from_secs(2));
+        }
+        config.wallet_start_jitter_secs = Some("3".to_string());
+        assert_eq!(config.start_jitter_range().unwrap(), Some((0.0, 3.0)));
+        assert!(config.start_jitter() <= Duration::from_secs(3));
+        config.wallet_start_jitter_secs = Some("1-1".to_string());
+        assert_eq!(config.start_jitter(), Duration::from_secs(1));
+
+        for text in ["2-1", "-1", "a-b", "inf"] {
+            config.wallet_start_jitter_secs = Some(text.to_string());
+            assert!(config.start_jitter_range().is_err(), "{}", text);
+            assert_eq!(config.start_jitter(), Duration::ZERO);
+        }
+        assert_invalid(config, "wallet_start_jitter_secs");
+    }
+
+    #[test]
+    fn send_window_per_wallet() {
+        let mut config = valid_config();
+        assert_eq!(config.send_window().unwrap(), None);
+        let text = r#"[{"private_key": "0x02"}, {"private_key": "0x03", "send_window": "22:00-06:00"}]"#;
+        let wallets = parse_wallets_json(text, &config).unwrap();
+        assert_eq!(wallets[0].send_window().unwrap(), None);
+        let window = wallets[1].send_window().unwrap().unwrap();
+        assert_eq!(window.to_string(), "22:00-06:00");
+
+        config.send_window = Some("22:00".to_string());
+        assert_invalid(config, "send_window");
+    }
+
+    #[test]
+    fn init_gas_price_zero() {
+        let config = test_config("0x00");
+        let gas_price = config.init_gas_price().unwrap();
+        assert!(!gas_price.eip1559);
+        assert!(gas_price.max_fee_per_gas.is_zero());
+        assert!(gas_price.max_priority_fee_per_gas.is_zero());
+        assert!(gas_price.value.is_zero());
+        assert!(config.reserve_wei().unwrap().is_zero());
+    }
+
+    #[test]
+    fn init_gas_price_overflow() {
+        let mut config = test_config("0x00");
+        config.value = TxValue::Fixed(1e80);
+        match config.init_gas_price() {
+            Err(InkError::Config(e)) => assert!(e.contains("value 过大无法转换为 wei"), "{}", e),
+            other => panic!("应当返回配置错误: {:?}", other),
+        }
+        assert!(config.validate().is_err());
+
+        let mut config = test_config("0x00");
+        config.max_fee_per_gas = f64::MAX;
+        assert!(config.init_gas_price().is_err());
+
+        config.max_fee_per_gas = f64::INFINITY;
+        assert!(config.init_gas_price().is_err());
+    }
+
+    #[test]
+    fn run_summary_sums_receipts() {
+        let gwei = U256::exp10(9);
+        let mut summary = RunSummary::default();
+        let eip1559 = TransactionReceipt {
+            gas_used: Some(U256::from(21000)),
+            effective_gas_price: Some(gwei * 2),
+            ..Default::default()
+        };
+        // legacy 回执没有 effective_gas_price 使用交易的 gas_price
+        let legacy = TransactionReceipt {
+            gas_used: Some(U256::from(30000)),
+            effective_gas_price: None,
+            ..Default::default()
+        };
+        summary.add_receipt(&eip1559, gwei * 10);
+        summary.add_receipt(&legacy, gwei * 3);
+        assert_eq!(summary.confirmed, 2);
+        assert_eq!(summary.gas_used, U256::from(51000));
+        assert_eq!(summary.gas_cost, gwei * (21000 * 2 + 30000 * 3));
+        assert_eq!(summary.average_gas_cost(), gwei * 66000);
+        assert!(RunSummary::default().average_gas_cost().is_zero());
+    }
+
+    #[test]
+    fn run_summary_missing_nonces() {
+        let mut summary = RunSummary {
+            success: 10,
+            ..Default::default()
+        };
+        // 没有做 nonce 自检
+        assert_eq!(summary.missing_nonces(), 0);
+        summary.nonce_advanced = Some(10);
+        assert_eq!(summary.missing_nonces(), 0);
+        summary.nonce_advanced = Some(7);
+        assert_eq!(summary.missing_nonces(), 3);
+        // 期间钱包还有别的交易, nonce 增长比成功数多
+        summary.nonce_advanced = Some(12);
+        assert_eq!(summary.missing_nonces(), 0);
+    }
+
+    #[test]
+    fn run_report_is_single_json_object() {
+        let address = Address::from_low_u64_be(1);
+        let ok = Ok(RunSummary {
+            address,
+            sent: 3,
+            success: 2,
+            failed: 1,
+            tx_hashes: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+            errors: vec!["第 3 笔: nonce too low".to_string()],
+            ..Default::default()
+        });
+        let err = Err(InkError::Wallet("invalid key".to_string()));
+        let report = RunReport::new(vec![
+            WalletReport::new(Some(address), &ok),
+            WalletReport::new(None, &err),
+        ]);
+        let text = serde_json::to_string(&report).unwrap();
+        assert!(!text.contains('\n'));
+
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(json["sent"], 3);
+        assert_eq!(json["success"], 2);
+        assert_eq!(json["failed"], 1);
+        let wallets = json["wallets"].as_array().unwrap();
+        assert_eq!(wallets.len(), 2);
+        assert_eq!(wallets[0]["address"], "0x0000000000000000000000000000000000000001");
+        assert_eq!(wallets[0]["tx_hashes"].as_array().unwrap().len(), 2);
+        assert_eq!(wallets[0]["errors"][0], "第 3 笔: nonce too low");
+        assert!(wallets[1]["address"].is_null());
+        assert_eq!(wallets[1]["sent"], 0);
+        assert_eq!(wallets[1]["errors"][0], "钱包错误: invalid key");
+    }
+
+    #[test]
+    fn run_summary_records_statuses() {
+        let mut summary = RunSummary::default();
+        let receipt = TransactionReceipt {
+            gas_used: Some(U256::from(21000)),
+            effective_gas_price: Some(U256::from(5)),
+            ..Default::default()
+        };
+        let statuses = vec![
+            TxStatus::Confirmed(Box::new(receipt)),
+            TxStatus::Pending,
+            TxStatus::Dropped,
+            TxStatus::Dropped,
+        ];
+        for status in &statuses {
+            summary.record_status(status, U256::from(10));
+        }
+        assert_eq!(summary.confirmed, 1);
+        assert_eq!(summary.pending, 1);
+        assert_eq!(summary.dropped, 2);
+        assert_eq!(summary.gas_cost, U256::from(21000 * 5));
+    }
+
+    #[test]
+    fn parse_weighted_recipients() {
+        let a = "0x00000000000000000000000000000000000000aa";
+        let b = "0x00000000000000000000000000000000000000bb";
+        let recipients = parse_recipients(&format!("{}:70, {}:30", a, b)).unwrap();
+        assert_eq!(
+            recipients,
+            vec![(a.parse().unwrap(), 70), (b.parse().unwrap(), 30)]
+        );
+        assert_eq!(parse_recipients(a).unwrap(), vec![(a.parse().unwrap(), 1)]);
+        assert!(parse_recipients(&format!("{}:0", a)).is_err());
+        assert!(parse_recipients(&format!("{}:x", a)).is_err());
+        assert!(parse_recipients("0x1234:10").is_err());
+    }
+
+    #[test]
+    fn weighted_picker_distribution() {
+        let mut picker = WeightedPicker::new(vec![("a", 70), ("b", 30)]);
+        let picks: Vec<&str> = picker.by_ref().take(100).collect();
+        assert_eq!(picks.iter().filter(|pick| **pick == "a").count(), 70);
+        assert_eq!(picks.iter().filter(|pick| **pick == "b").count(), 30);
+        // 穿插分布 不会连续取完一个再取另一个
+        assert_eq!(&picks[..4], &["a", "b", "a", "a"]);
+
+        let picker = WeightedPicker::new(vec![("a", 1), ("b", 1), ("c", 1)]);
+        let picks: Vec<&str> = picker.take(6).collect();
+        assert_eq!(picks, vec!["a", "b", "c", "a", "b", "c"]);
+
+        assert!(WeightedPicker::<&str>::new(Vec::new()).next().is_none());
+    }
+
+    #[tokio::test]
+    async fn gas_gate_waits_until_low() {
+        let gwei = U256::exp10(9);
+        let prices = std::cell::RefCell::new(vec![gwei * 50, gwei * 30, gwei * 10, gwei * 5]);
+        let mut checks = Vec::new();
+        let current = wait_for_gas_gate(
+            gwei * 20,
+            Duration::from_millis(1),
+            None,
+            || {
+                let price = prices.borrow_mut().remove(0);
+                async move { Ok(price) }
+            },
+            |current, passed| checks.push((current, passed)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(current, Some(gwei * 10));
+        assert_eq!(
+            checks,
+            vec![(gwei * 50, false), (gwei * 30, false), (gwei * 10, true)]
+        );
+        // 降到门控以下就不再查询
+        assert_eq!(prices.borrow().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn gas_gate_returns_rpc_errors() {
+        let result = wait_for_gas_gate(
+            U256::from(1),
+            Duration::from_millis(1),
+            None,
+            || async { Err(InkError::Rpc("connection refused".to_string())) },
+            |_, _| {},
+        )
+        .await;
+        assert!(matches!(result, Err(InkError::Rpc(_))));
+    }
+
+    #[tokio::test]
+    async fn watch_loop_times_out() {
+        let calls = std::cell::Cell::new(0);
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let current = wait_for_gas_gate(
+            U256::from(10),
+            Duration::from_millis(20),
+            Some(deadline),
+            || {
+                calls.set(calls.get() + 1);
+                async { Ok(U256::from(100)) }
+            },
+            |_, passed| assert!(!passed),
+        )
+        .await
+        .unwrap();
+        assert!(current.is_none());
+        assert!(Instant::now() <= deadline + Duration::from_millis(20));
+        assert!(calls.get() >= 2);
+    }
+
+    #[tokio::test]
+    async fn wait_next_batch_on_new_block() {
+        let (tx, rx) = watch::channel(0u64);
+        let mut new_blocks = Some(rx);
+        tx.send(1).unwrap();
+        // 有新区块时立即返回 不会等 interval
+        tokio::time::timeout(Duration::from_secs(1), wait_next_batch(&mut new_blocks, 60.0))
+            .await
+            .unwrap();
+        assert!(new_blocks.is_some());
+
+        // 订阅放弃后回退到 interval 模式
+        drop(tx);
+        wait_next_batch(&mut new_blocks, 0.0).await;
+        assert!(new_blocks.is_none());
+    }
+
+    #[test]
+    fn send_urls_prefer_private_rpc() {
+        let mut config = test_config("0x00");
+        config.rpc_url = "http://a:8545, http://b:8545".to_string();
+        assert_eq!(config.send_urls(), vec!["http://a:8545", "http://b:8545"]);
+        config.private_rpc_url = Some("https://rpc.flashbots.net".to_string());
+        assert_eq!(config.send_urls(), vec!["https://rpc.flashbots.net"]);
+        assert_eq!(config.rpc_urls(), vec!["http://a:8545", "http://b:8545"]);
+        config.private_rpc_url = Some(String::new());
+        assert_eq!(config.send_urls().len(), 2);
+    }
+
+    #[test]
+    fn multichain_configs() {
+        let mut config = valid_config();
+        config.rpc_url = "http://a:8545, http://b:8545".to_string();
+        assert_eq!(config.chain_configs().len(), 1);
+        assert_eq!(config.chain_configs()[0].rpc_url, config.rpc_url);
+
+        config.multichain = true;
+        let chains = config.chain_configs();
+        let urls: Vec<&str> = chains.iter().map(|chain| chain.rpc_url.as_str()).collect();
+        assert_eq!(urls, ["http://a:8545", "http://b:8545"]);
+        // 每条链的配置只剩一个 RPC, 其余配置相同
+        assert_eq!(chains[1].rpc_urls(), vec!["http://b:8545"]);
+        assert_eq!(chains[1].data, config.data);
+        assert_eq!(chains[1].count, config.count);
+        assert!(config.validate().is_ok());
+
+        config.private_rpc_url = Some("https://rpc.flashbots.net".to_string());
+        assert_invalid(config, "private_rpc_url");
+    }
+
+    #[tokio::test]
+    async fn check_in_mempool_falls_back() {
+        let a = H256::repeat_byte(1);
+        let b = H256::repeat_byte(2);
+        let c = H256::repeat_byte(3);
+        let content = serde_json::json!({
+            "pending": { "0x0000000000000000000000000000000000000001": { "7": { "hash": a } } },
+            "queued": { "0x0000000000000000000000000000000000000001": { "9": { "hash": b } } },
+        });
+        assert_eq!(txpool_hashes(&content), HashSet::from([a, b]));
+
+        // 在 txpool 里的不再逐笔查询
+        let queried = std::sync::Mutex::new(Vec::new());
+        let get_transaction = |tx_hash: H256| {
+            queried.lock().unwrap().push(tx_hash);
+            async move { Ok(tx_hash == b) }
+        };
+        let accepted = check_in_mempool(
+            &[a, b, c],
+            || async { Ok(content.clone()) },
+            get_transaction,
+        )
+        .await
+        .unwrap();
+        assert_eq!(accepted, vec![true, true, false]);
+        assert_eq!(*queried.lock().unwrap(), vec![c]);
+
+        // 节点不支持 txpool 时全部用 get_transaction 确认
+        queried.lock().unwrap().clear();
+        let accepted = check_in_mempool(
+            &[a, b, c],
+            || async {
+                Err(InkError::Rpc(
+                    "txpool_content: (code: -32601, message: the method txpool_content does not exist)"
+                        .to_string(),
+                ))
+            },
+            get_transaction,
+        )
+        .await
+        .unwrap();
+        assert_eq!(accepted, vec![false, true, false]);
+        assert_eq!(*queried.lock().unwrap(), vec![a, b, c]);
+    }
+
+    #[tokio::test]
+    async fn verify_in_mempool_marks_missing_txs() {
+        let a = H256::repeat_byte(1);
+        let b = H256::repeat_byte(2);
+        let content = |hash: H256| {
+            serde_json::json!({
+                "pending": { "0x0000000000000000000000000000000000000001": { "7": { "hash": hash } } },
+                "queued": {},
+            })
+        };
+        let (provider, mock) = Provider::mocked();
+        let timeout = Duration::from_secs(1);
+
+        // txpool 里有这笔交易 结果不变
+        mock.push(content(a)).unwrap();
+        let mut responses = vec![Ok(a), Err("nonce too low".to_string())];
+        verify_in_mempool(&provider, &mut responses, timeout, 0)
+            .await
+            .unwrap();
+        assert_eq!(responses[0], Ok(a));
+        assert_eq!(responses[1], Err("nonce too low".to_string()));
+
+        // txpool 里没有 get_transaction 也查不到时改为 NOT_IN_MEMPOOL
+        mock.push(serde_json::Value::Null).unwrap();
+        mock.push(content(a)).unwrap();
+        let mut responses = vec![Ok(b)];
+        verify_in_mempool(&provider, &mut responses, timeout, 0)
+            .await
+            .unwrap();
+        assert_eq!(responses[0], Err(NOT_IN_MEMPOOL.to_string()));
+    }
+
+    #[test]
+    fn raw_tx_hash_matches_signed_tx() {
+        let wallet: LocalWallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse::<LocalWallet>()
+            .unwrap()
+            .with_chain_id(1u64);
+        let gas_price = GasPrice {
+            eip1559: true,
+            max_fee_per_gas: U256::from(10),
+            max_priority_fee_per_gas: U256::from(1),
+            value: U256::zero(),
+        };
+        let mut tx = build_tx(
+            wallet.address(),
+            1,
+            wallet.address(),
+            &gas_price,
+            U256::zero(),
+            Bytes::from_static(b"data:,"),
+        );
+        tx.set_gas(50000u64);
+        let raw_tx = sign_tx(&wallet, &tx).unwrap();
+        let signature = wallet.sign_transaction_sync(&tx).unwrap();
+        assert_eq!(raw_tx_hash(&raw_tx).unwrap(), tx.hash(&signature));
+        assert!(raw_tx_hash("0xzz").is_err());
+    }
+
+    #[test]
+    fn nonce_sequence_check() {
+        let nonces = |values: &[u64]| values.iter().map(|n| U256::from(*n)).collect::<Vec<_>>();
+        assert!(assert_nonce_sequence(&[]).is_ok());
+        assert!(assert_nonce_sequence(&nonces(&[7])).is_ok());
+        assert!(assert_nonce_sequence(&nonces(&[7, 8, 9, 10])).is_ok());
+
+        match assert_nonce_sequence(&nonces(&[7, 8, 8, 9])) {
+            Err(InkError::Send(e)) => assert!(e.contains("nonce 8 重复"), "{}", e),
+            other => panic!("应当检测到重复 nonce: {:?}", other),
+        }
+        match assert_nonce_sequence(&nonces(&[7, 8, 10])) {
+            Err(InkError::Send(e)) => assert!(e.contains("8 之后是 10"), "{}", e),
+            other => panic!("应当检测到跳号: {:?}", other),
+        }
+        assert!(assert_nonce_sequence(&nonces(&[9, 8])).is_err());
+    }
+
+    #[test]
+    fn nonce_sequence_with_step() {
+        let nonces = |values: &[u64]| values.iter().map(|n| U256::from(*n)).collect::<Vec<_>>();
+        // 上一批最后一笔 4, 本批 6 8 10
+        assert!(assert_nonce_sequence_step(&nonces(&[4, 6, 8, 10]), 2).is_ok());
+        match assert_nonce_sequence_step(&nonces(&[4, 5, 6]), 2) {
+            Err(InkError::Send(e)) => assert!(e.contains("4 之后是 5, 应为 6"), "{}", e),
+            other => panic!("应当检测到步长不符: {:?}", other),
+        }
+        assert!(assert_nonce_sequence_step(&nonces(&[4, 4]), 2).is_err());
+
+        let mut config = valid_config();
+        config.nonce_step = 0;
+        assert_invalid(config, "nonce_step");
+    }
+
+    #[test]
+    fn tx_type_unsupported_errors() {
+        assert!(is_tx_type_unsupported(
+            "(code: -32000, message: transaction type not supported)"
+        ));
+        assert!(is_tx_type_unsupported("Invalid transaction type"));
+        assert!(!is_tx_type_unsupported("nonce too low"));
+        assert!(!is_tx_type_unsupported("insufficient funds for gas * price + value"));
+    }
+
+    #[test]
+    fn humanize_common_errors() {
+        let hint = |error| humanize_error(error).unwrap();
+        // 替换交易的提示优先于一般的 underpriced
+        assert!(
+            hint("(code: -32000, message: replacement transaction underpriced)").contains("替换")
+        );
+        assert!(hint("transaction underpriced").contains("提高 max_fee_per_gas"));
+        assert!(hint(
+            "max fee per gas less than block base fee: address 0x1, maxFeePerGas: 1 baseFee: 7"
+        )
+        .contains("base fee"));
+        assert!(hint("(code: -32000, message: nonce too low)").contains("nonce 已经被用过"));
+        assert!(hint("Nonce too high").contains("跳号"));
+        assert!(hint("insufficient funds for gas * price + value").contains("余额不够"));
+        assert!(hint("intrinsic gas too low").contains("gas_limit 太低"));
+        assert!(hint("already known").contains("交易池"));
+        assert!(hint("HTTP 429 Too Many Requests").contains("限速"));
+        assert!(hint("execution reverted: mint not started").contains("回滚"));
+        assert_eq!(humanize_error("节点没有返回响应"), None);
+        assert_eq!(humanize_error(""), None);
+    }
+    #[test]
+    fn process_id_empty_range() {
+        assert!(process_id(r#"{"id":"[-]"}"#).is_err());
+    }
+
+    #[test]
+    fn process_id_without_range() {
+        let (id, current_id, count) = process_id(r#"{"id":"6227"}"#).unwrap();
+        assert!(id.is_none());
+        assert!(current_id.is_none());
+        assert_eq!(count, u64::MAX);
+    }
+
+    const MOCK_KEY: &str = "0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    type BatchResponse = Result<Vec<Result<H256, String>>, InkError>;
+
+    /// 按顺序返回预设响应的 TxSender, 记录每次收到的交易, 预设响应用完后每笔都返回成功
+    #[derive(Default)]
+    struct MockSender {
+        responses: std::sync::Mutex<std::collections::VecDeque<BatchResponse>>,
+        batches: std::sync::Mutex<Vec<Vec<String>>>,
+    }
+
+    impl MockSender {
+        fn new(responses: Vec<BatchResponse>) -> Arc<Self> {
+            Arc::new(MockSender {
+                responses: std::sync::Mutex::new(responses.into()),
+                ..Default::default()
+            })
+        }
+
+        fn batches(&self) -> Vec<Vec<String>> {
+            self.batches.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl TxSender for MockSender {
+        async fn send_batch(&self, raw_txs: &[String]) -> BatchResponse {
+            self.batches.lock().unwrap().push(raw_txs.to_vec());
+            match self.responses.lock().unwrap().pop_front() {
+                Some(response) => response,
+                None => Ok(raw_txs
+                    .iter()
+                    .map(|raw_tx| Ok(raw_tx_hash(raw_tx).unwrap()))
+                    .collect()),
+            }
+        }
+    }
+
+    fn mock_config(count: u64, batch_size: u64) -> Config {
+        let mut config = test_config(r#"{"p":"erc-20","op":"mint","id":"[1-:asc]"}"#);
+        config.count = count;
+        config.batch_size = batch_size;
+        config.network_backoff_secs = 0.0;
+        config
+    }
+
+    fn mock_ctx(sender: Arc<MockSender>, config: &Config) -> MintContext {
+        MintContext {
+            rpc_url: config.rpc_url.clone(),
+            // 不开启 gas 门控、估算和等待回执时 mint 不会用到 provider
+            provider: Provider::<Http>::try_from("http://127.0.0.1:1").unwrap(),
+            pool: Arc::new(RpcPool::new(Vec::new())),
+            sender,
+            new_blocks: None,
+            chain_id: U256::one(),
+            chain_ids: Arc::new(ChainIdCache::new()),
+            gas_price: config.init_gas_price().unwrap(),
+            inflight: None,
+            rate_limiter: None,
+        }
+    }
+
+    /// 不连网, 用 mock 发送从 nonce 7 开始的 mint
+    async fn mock_mint(
+        sender: Arc<MockSender>,
+        mut config: Config,
+    ) -> (Result<RunSummary, InkError>, Vec<MintEvent>) {
+        let ctx = mock_ctx(sender, &config);
+        let wallet = MOCK_KEY.parse::<LocalWallet>().unwrap().with_chain_id(1u64);
+        let gas_price = config.init_gas_price().unwrap();
+        let (recipients, _) = prepare_config(&mut config, wallet.address()).unwrap();
+        let mut events = Vec::new();
+        let nonce = U256::from(7);
+        let result = mint(
+            &ctx,
+            &wallet,
+            config,
+            &gas_price,
+            nonce,
+            &recipients,
+            |event| events.push(event),
+        )
+        .await;
+        (result, events)
+    }
+
+    fn decode_raw_tx(raw_tx: &str) -> TypedTransaction {
+        let bytes = hex::decode(raw_tx.trim_start_matches("0x")).unwrap();
+        TypedTransaction::decode_signed(&ethers::utils::rlp::Rlp::new(&bytes))
+            .unwrap()
+            .0
+    }
+
+    fn raw_nonces(batch: &[String]) -> Vec<u64> {
+        batch
+            .iter()
+            .map(|raw_tx| decode_raw_tx(raw_tx).nonce().unwrap().as_u64())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn mock_mint_aligns_nonces_across_batches() {
+        let sender = MockSender::new(vec![]);
+        let (result, events) = mock_mint(sender.clone(), mock_config(5, 2)).await;
+        let summary = result.unwrap();
+        assert_eq!((summary.sent, summary.success, summary.failed), (5, 5, 0));
+
+        let batches = sender.batches();
+        assert_eq!(
+            batches.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        let nonces: Vec<u64> = batches.iter().flat_map(|batch| raw_nonces(batch)).collect();
+        assert_eq!(nonces, vec![7, 8, 9, 10, 11]);
+        let tx_hashes: Vec<H256> = batches
+            .concat()
+            .iter()
+            .map(|raw_tx| raw_tx_hash(raw_tx).unwrap())
+            .collect();
+        assert_eq!(summary.tx_hashes, tx_hashes);
+        let batch_done = events
+            .iter()
+            .filter(|event| matches!(event, MintEvent::BatchDone { .. }))
+            .count();
+        assert_eq!(batch_done, 3);
+    }
+
+    #[tokio::test]
+    async fn mock_mint_watch_mode_stops_at_count() {
+        // 不设 gas 门控时每批直接发送, 只看 count 打完后是否停止
+        let mut config = mock_config(25, 100);
+        config.watch_mode = true;
+        config.watch_batch_size = 10;
+        let sender = MockSender::new(vec![]);
+        let (result, events) = mock_mint(sender.clone(), config).await;
+        assert_eq!(result.unwrap().success, 25);
+        assert_eq!(
+            sender.batches().iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![10, 10, 5]
+        );
+        let finished: Vec<(u64, bool)> = events
+            .iter()
+            .filter_map(|event| match event {
+                MintEvent::WatchFinished {
+                    remaining,
+                    timed_out,
+                } => Some((*remaining, *timed_out)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(finished, vec![(0, false)]);
+
+        // count 小于 watch_batch_size 时一批打完
+        let mut config = mock_config(3, 100);
+        config.watch_mode = true;
+        config.watch_batch_size = 10;
+        let sender = MockSender::new(vec![]);
+        let (result, _) = mock_mint(sender.clone(), config).await;
+        assert_eq!(result.unwrap().success, 3);
+        assert_eq!(sender.batches().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn mock_mint_closes_batch_on_error() {
+        let mut config = mock_config(4, 2);
+        config.network_retries = 0;
+        let sender = MockSender::new(vec![Err(InkError::Rpc(
+            "发送 batch: connection refused".to_string(),
+        ))]);
+        let (result, events) = mock_mint(sender, config).await;
+        assert!(result.is_err());
+        let batches: Vec<(bool, u64)> = events
+            .iter()
+            .filter_map(|event| match event {
+                MintEvent::BatchStart { index, .. } => Some((true, *index)),
+                MintEvent::BatchDone { index } => Some((false, *index)),
+                _ => None,
+            })
+            .collect();
+        // 第 1 批发送失败, 仍然补发它的 BatchDone, 之后不再发送
+        assert_eq!(batches, vec![(true, 1), (false, 1)]);
+    }
+
+    #[tokio::test]
+    async fn mock_mint_resend_accepts_known_txs() {
+        let reset = || Err(InkError::Rpc("发送 batch: connection reset".to_string()));
+        let mut config = mock_config(2, 2);
+        config.network_retries = 3;
+        // 第一次没拿到响应, 重发时节点提示两笔都已经收到过
+        let sender = MockSender::new(vec![
+            reset(),
+            Ok(vec![
+                Err("already known".to_string()),
+                Err("(code: -32000, message: nonce too low)".to_string()),
+            ]),
+        ]);
+        let (result, _) = mock_mint(sender.clone(), config.clone()).await;
+        let summary = result.unwrap();
+        assert_eq!((summary.success, summary.failed), (2, 0));
+        let batches = sender.batches();
+        assert_eq!(batches.len(), 2);
+        let tx_hashes: Vec<H256> = batches[0]
+            .iter()
+            .map(|raw_tx| raw_tx_hash(raw_tx).unwrap())
+            .collect();
+        assert_eq!(summary.tx_hashes, tx_hashes);
+
+        // 一直连不上时只重试 network_retries 次, 不再和 max_retries 相乘
+        config.network_retries = 2;
+        let sender = MockSender::new(vec![reset(), reset(), reset(), reset()]);
+        let (result, _) = mock_mint(sender.clone(), config).await;
+        assert!(result.is_err());
+        assert_eq!(sender.batches().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn mock_mint_records_failed_txs() {
+        let sender = MockSender::new(vec![Ok(vec![
+            Ok(H256::repeat_byte(1)),
+            Err("(code: -32000, message: nonce too low)".to_string()),
+        ])]);
+        let (result, events) = mock_mint(sender, mock_config(3, 2)).await;
+        let summary = result.unwrap();
+        assert_eq!((summary.sent, summary.success, summary.failed), (3, 2, 1));
+        assert_eq!(
+            summary.errors,
+            vec!["第 2 笔(nonce 8): (code: -32000, message: nonce too low)".to_string()]
+        );
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, MintEvent::TxFailed { index: 2, .. })));
+    }
+
+    #[test]
+    fn error_policy_lookup() {
+        let mut config = valid_config();
+        assert_eq!(config.error_action("nonce too low"), None);
+        config.error_policy = serde_json::from_str(
+            r#"{"underpriced":"retry","nonce too low":"abort","Already Known":"skip","known":"retry"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.error_action("(code: -32000, message: transaction underpriced)"),
+            Some(ErrorAction::Retry)
+        );
+        assert_eq!(config.error_action("NONCE TOO LOW"), Some(ErrorAction::Abort));
+        // 同时命中时取最长的关键字
+        assert_eq!(config.error_action("already known"), Some(ErrorAction::Skip));
+        assert_eq!(config.error_action("known transaction"), Some(ErrorAction::Retry));
+        assert_eq!(config.error_action("insufficient funds"), None);
+        let unknown = serde_json::from_str::<BTreeMap<String, ErrorAction>>(r#"{"a":"ignore"}"#);
+        assert!(unknown.is_err());
+
+        config.error_policy.insert(" ".to_string(), ErrorAction::Skip);
+        assert_invalid(config, "error_policy");
+    }
+
+    #[tokio::test]
+    async fn mock_mint_applies_error_policy() {
+        let underpriced = || Err("(code: -32000, message: underpriced)".to_string());
+        let mut config = mock_config(3, 3);
+        config.max_retries = 2;
+        config.error_policy = serde_json::from_str(
+            r#"{"underpriced":"retry","already known":"skip","nonce too low":"abort"}"#,
+        )
+        .unwrap();
+
+        // retry: 只重发失败的那笔, 重发成功后计为成功
+        let sender = MockSender::new(vec![
+            Ok(vec![Ok(H256::repeat_byte(1)), underpriced(), Ok(H256::repeat_byte(3))]),
+            Ok(vec![underpriced()]),
+        ]);
+        let (result, events) = mock_mint(sender.clone(), config.clone()).await;
+        let summary = result.unwrap();
+        assert_eq!((summary.sent, summary.success, summary.failed), (3, 3, 0));
+        let batches = sender.batches();
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[1], vec![batches[0][1].clone()]);
+        assert_eq!(batches[2], batches[1]);
+        let attempts: Vec<u32> = events
+            .iter()
+            .filter_map(|event| match event {
+                MintEvent::TxRetry { index: 2, attempt, .. } => Some(*attempt),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(attempts, vec![1, 2]);
+
+        // 重试次数用完后仍记为失败
+        let sender = MockSender::new(vec![
+            Ok(vec![Ok(H256::repeat_byte(1)), underpriced(), Ok(H256::repeat_byte(3))]),
+            Ok(vec![underpriced()]),
+            Ok(vec![underpriced()]),
+        ]);
+        let (result, _) = mock_mint(sender, config.clone()).await;
+        assert_eq!(result.unwrap().failed, 1);
+
+        // skip: 不计入失败也不导出
+        let sender = MockSender::new(vec![Ok(vec![
+            Ok(H256::repeat_byte(1)),
+            Err("already known".to_string()),
+            Ok(H256::repeat_byte(3)),
+        ])]);
+        let (result, events) = mock_mint(sender, config.clone()).await;
+        let summary = result.unwrap();
+        assert_eq!((summary.sent, summary.success, summary.failed), (3, 2, 0));
+        assert!(summary.failures.is_empty());
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, MintEvent::TxSkipped { index: 2, .. })));
+
+        // abort: 停止后面的批次
+        config.batch_size = 1;
+        let sender = MockSender::new(vec![
+            Ok(vec![Ok(H256::repeat_byte(1))]),
+            Ok(vec![Err("nonce too low".to_string())]),
+        ]);
+        let (result, events) = mock_mint(sender.clone(), config).await;
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("abort") && error.contains("第 2 笔"), "{}", error);
+        assert_eq!(sender.batches().len(), 2);
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, MintEvent::TxFailed { index: 2, .. })));
+    }
+
+    #[tokio::test]
+    async fn mock_mint_item_list() {
+        let items = parse_mint_items(
+            r#"[
+                {"data":"a","to":"0x0000000000000000000000000000000000000001","value":0.001},
+                {"data":"0x1249c58b"},
+                {"data":"[nonce]","to":"0x0000000000000000000000000000000000000002"}
+            ]"#,
+        )
+        .unwrap();
+        assert!(parse_mint_items("[]").is_err());
+        assert!(parse_mint_items(r#"[{"to":"0x01"}]"#).is_err());
+
+        let mut config = mock_config(100, 2);
+        config.value = TxValue::Fixed(0.0);
+        config.set_items(items);
+        assert_eq!(config.count, 3);
+
+        let sender = MockSender::new(vec![]);
+        let (result, _) = mock_mint(sender.clone(), config.clone()).await;
+        assert_eq!(result.unwrap().success, 3);
+        let txs: Vec<TypedTransaction> = sender
+            .batches()
+            .concat()
+            .iter()
+            .map(|raw_tx| decode_raw_tx(raw_tx))
+            .collect();
+        let nonces: Vec<u64> = txs.iter().map(|tx| tx.nonce().unwrap().as_u64()).collect();
+        assert_eq!(nonces, vec![7, 8, 9]);
+        let wallet = MOCK_KEY.parse::<LocalWallet>().unwrap();
+        let to: Vec<Address> = txs.iter().map(|tx| *tx.to_addr().unwrap()).collect();
+        assert_eq!(
+            to,
+            vec![Address::from_low_u64_be(1), wallet.address(), Address::from_low_u64_be(2)]
+        );
+        assert_eq!(txs[0].value(), Some(&U256::exp10(15)));
+        assert_eq!(txs[1].value(), Some(&U256::zero()));
+        let data: Vec<Bytes> = txs.iter().map(|tx| tx.data().cloned().unwrap()).collect();
+        assert_eq!(data[0], Bytes::from(b"data:,a".to_vec()));
+        assert_eq!(data[1], Bytes::from_str("0x1249c58b").unwrap());
+        assert_eq!(data[2], Bytes::from(b"data:,9".to_vec()));
+
+        let mut config = valid_config();
+        config.set_items(vec![MintItem::new("a"), MintItem::new("")]);
+        assert_eq!(config.count, 2);
+        assert_invalid(config.clone(), "第 2 条任务的 data");
+        config.items[1] = MintItem {
+            value: Some(-1.0),
+            ..MintItem::new("b")
+        };
+        assert_invalid(config.clone(), "第 2 条任务的 value");
+        config.items[1].value = None;
+        assert!(config.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn mock_mint_precomputes_tx_hashes() {
+        let path =
+            std::env::temp_dir().join(format!("evm_ink_rs_tx_hashes_{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut config = mock_config(3, 2);
+        config.tx_hashes_file = Some(path.to_string_lossy().to_string());
+        let sender = MockSender::new(vec![]);
+        let (result, events) = mock_mint(sender.clone(), config).await;
+        assert_eq!(result.unwrap().success, 3);
+
+        let signed: Vec<(u64, H256)> = events
+            .iter()
+            .filter_map(|event| match event {
+                MintEvent::TxSigned { index, tx_hash, .. } => Some((*index, *tx_hash)),
+                _ => None,
+            })
+            .collect();
+        let sent: Vec<(u64, H256)> = events
+            .iter()
+            .filter_map(|event| match event {
+                MintEvent::TxSent { index, tx_hash, .. } => Some((*index, *tx_hash)),
+                _ => None,
+            })
+            .collect();
+        // 和节点返回的 hash 一致, 也和 tx.hash(&signature) 算出的一致
+        assert_eq!(signed, sent);
+        let hashes: Vec<H256> = sender
+            .batches()
+            .concat()
+            .iter()
+            .map(|raw_tx| {
+                let bytes = hex::decode(raw_tx.trim_start_matches("0x")).unwrap();
+                let (tx, signature) =
+                    TypedTransaction::decode_signed(&ethers::utils::rlp::Rlp::new(&bytes)).unwrap();
+                tx.hash(&signature)
+            })
+            .collect();
+        assert_eq!(
+            signed.iter().map(|(_, hash)| *hash).collect::<Vec<_>>(),
+            hashes
+        );
+
+        let wallet = MOCK_KEY.parse::<LocalWallet>().unwrap().address();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "wallet,index,nonce,id,tx_hash");
+        assert_eq!(lines[1], format!("{:?},1,7,1,{:?}", wallet, hashes[0]));
+        assert_eq!(lines[3], format!("{:?},3,9,3,{:?}", wallet, hashes[2]));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_txs_export_and_replay() {
+        let underpriced = || Err("(code: -32000, message: underpriced)".to_string());
+        let sender = MockSender::new(vec![Ok(vec![
+            Ok(H256::repeat_byte(1)),
+            underpriced(),
+            underpriced(),
+        ])]);
+        let config = mock_config(3, 3);
+        let (result, _) = mock_mint(sender.clone(), config.clone()).await;
+        let failures = result.unwrap().failures;
+        let wallet = MOCK_KEY.parse::<LocalWallet>().unwrap().with_chain_id(1u64);
+        let nonces: Vec<u64> = failures
+            .iter()
+            .map(|failed| failed.nonce.as_u64())
+            .collect();
+        assert_eq!(nonces, [8, 9]);
+        assert!(failures
+            .iter()
+            .all(|failed| failed.wallet == wallet.address() && failed.chain_id == 1));
+        assert_eq!(failures[0].index, 2);
+        let original = &sender.batches()[0];
+        assert_eq!(Some(&failures[1].data), decode_raw_tx(&original[2]).data());
+
+        let path = env::temp_dir().join(format!("evm_ink_rs_failed_{}.json", std::process::id()));
+        write_failed_txs(&path, &failures).unwrap();
+        let loaded = read_failed_txs(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, failures);
+
+        // 链上 nonce 已经走到 20, 回放从 20 重新分配, to/value/data 不变
+        let replay_sender =
+            MockSender::new(vec![Ok(vec![Ok(H256::repeat_byte(2)), underpriced()])]);
+        let ctx = mock_ctx(replay_sender.clone(), &config);
+        let gas_price = config.init_gas_price().unwrap();
+        let mut events = Vec::new();
+        let summary = replay(
+            &ctx,
+            &wallet,
+            &config,
+            &gas_price,
+            U256::from(20),
+            &loaded,
+            |event| events.push(event),
+        )
+        .await
+        .unwrap();
+        assert_eq!((summary.success, summary.failed), (1, 1));
+        let batch = &replay_sender.batches()[0];
+        assert_eq!(raw_nonces(batch), vec![20, 21]);
+        for (raw_tx, failed) in batch.iter().zip(&loaded) {
+            let tx = decode_raw_tx(raw_tx);
+            assert_eq!(tx.to_addr(), Some(&failed.to));
+            assert_eq!(tx.value(), Some(&failed.value));
+            assert_eq!(tx.data(), Some(&failed.data));
+        }
+        // 再次失败的记下新的 nonce, 可以继续回放
+        assert_eq!(summary.failures[0].nonce, U256::from(21));
+        assert_eq!(summary.failures[0].index, 3);
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, MintEvent::TxSent { index: 2, .. })));
+    }
+    #[tokio::test]
+    async fn mock_mint_binds_responses_to_nonces() {
+        let sender = MockSender::new(vec![Ok(vec![
+            Err("(code: -32000, message: replacement transaction underpriced)".to_string()),
+            Ok(H256::repeat_byte(2)),
+            Err("(code: -32000, message: already known)".to_string()),
+        ])]);
+        let (result, events) = mock_mint(sender.clone(), mock_config(3, 3)).await;
+        assert_eq!(result.unwrap().failed, 2);
+        let batch = &sender.batches()[0];
+        for event in events {
+            match event {
+                MintEvent::TxSent {
+                    index,
+                    nonce,
+                    tx_hash,
+                } => {
+                    assert_eq!(index, 2);
+                    assert_eq!(nonce, U256::from(8));
+                    assert_eq!(tx_hash, H256::repeat_byte(2));
+                }
+                MintEvent::TxFailed {
+                    index,
+                    nonce,
+                    id,
+                    data,
+                    raw_tx,
+                    ..
+                } => {
+                    assert_eq!(raw_tx, batch[index as usize - 1]);
+                    assert_eq!(id, Some(index));
+                    let tx = decode_raw_tx(&raw_tx);
+                    assert_eq!(Some(&nonce), tx.nonce());
+                    assert_eq!(Some(&data), tx.data());
+                    let expected =
+                        format!(r#"data:,{{"p":"erc-20","op":"mint","id":"{}"}}"#, index);
+                    assert_eq!(data.to_vec(), expected.into_bytes());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn mock_mint_retries_transport_errors() {
+        let sender = MockSender::new(vec![
+            Err(InkError::Rpc(
+                "batch 请求失败: error trying to connect: tcp connect error: Connection refused"
+                    .to_string(),
+            )),
+            Err(InkError::Rpc(
+                "batch 请求失败: error trying to connect: dns error".to_string(),
+            )),
+        ]);
+        let (result, _) = mock_mint(sender.clone(), mock_config(2, 2)).await;
+        assert_eq!(result.unwrap().success, 2);
+        // 整批重发, 内容不变
+        let batches = sender.batches();
+        assert_eq!(batches.len(), 3);
+        assert!(batches.iter().all(|batch| *batch == batches[0]));
+    }
+
+    #[tokio::test]
+    async fn mock_mint_does_not_retry_node_errors() {
+        let sender = MockSender::new(vec![Err(InkError::Rpc(
+            r#"batch 请求被拒绝: {"jsonrpc":"2.0","error":{"code":-32600,"message":"invalid request"}}"#.to_string(),
+        ))]);
+        let (result, _) = mock_mint(sender.clone(), mock_config(2, 2)).await;
+        assert!(matches!(result, Err(InkError::Rpc(_))));
+        assert_eq!(sender.batches().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn mock_mint_falls_back_to_legacy() {
+        let unsupported = "(code: -32000, message: transaction type not supported)".to_string();
+        let sender = MockSender::new(vec![Ok(vec![Err(unsupported.clone()), Err(unsupported)])]);
+        let mut config = mock_config(2, 2);
+        config.max_priority_fee_per_gas = Some(0.0);
+        let (result, events) = mock_mint(sender.clone(), config).await;
+        assert_eq!(result.unwrap().success, 2);
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, MintEvent::LegacyFallback { .. })));
+
+        let batches = sender.batches();
+        assert_eq!(batches.len(), 2);
+        assert!(batches[0]
+            .iter()
+            .all(|raw_tx| matches!(decode_raw_tx(raw_tx), TypedTransaction::Eip1559(_))));
+        assert!(batches[1]
+            .iter()
+            .all(|raw_tx| matches!(decode_raw_tx(raw_tx), TypedTransaction::Legacy(_))));
+        // 改成 legacy 后沿用原来的 nonce
+        assert_eq!(raw_nonces(&batches[1]), vec![7, 8]);
+    }
+
+    #[tokio::test]
+    async fn mock_mint_exports_legacy_hashes_once() {
+        let path = std::env::temp_dir().join(format!(
+            "evm_ink_rs_legacy_hashes_{}.csv",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let unsupported = "(code: -32000, message: transaction type not supported)".to_string();
+        let sender = MockSender::new(vec![Ok(vec![Err(unsupported.clone()), Err(unsupported)])]);
+        let mut config = mock_config(3, 2);
+        config.max_priority_fee_per_gas = Some(0.0);
+        config.tx_hashes_file = Some(path.to_string_lossy().to_string());
+        let (result, _) = mock_mint(sender.clone(), config).await;
+        assert_eq!(result.unwrap().success, 3);
+
+        // 被拒的 type-2 交易不导出, 每笔只有最终 legacy 交易的一行
+        let batches = sender.batches();
+        let legacy: Vec<String> = batches[1..]
+            .concat()
+            .iter()
+            .map(|raw_tx| format!("{:?}", raw_tx_hash(raw_tx).unwrap()))
+            .collect();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let exported: Vec<&str> = text
+            .lines()
+            .skip(1)
+            .map(|line| line.rsplit(',').next().unwrap())
+            .collect();
+        assert_eq!(exported, legacy);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    const FEE_CONTRACT: &str = "0x00000000000000000000000000000000000000fe";
+
+    #[test]
+    fn paid_inscription_config() {
+        let mut config = valid_config();
+        assert_eq!(config.paid_inscription().unwrap(), None);
+
+        config.fee_contract = Some(FEE_CONTRACT.to_string());
+        config.fee_value = 0.001;
+        let paid = config.paid_inscription().unwrap().unwrap();
+        assert_eq!(paid.contract, Address::from_low_u64_be(0xfe));
+        assert_eq!(paid.value, U256::exp10(15));
+        assert_eq!(paid.calldata, None);
+        // fee_value 代替 value
+        assert_eq!(config.init_gas_price().unwrap().value, U256::exp10(15));
+
+        config.data = String::new();
+        assert_invalid(config.clone(), "data 不能为空");
+        config.fee_calldata = Some("0x1249c58b".to_string());
+        assert!(config.validate().is_ok());
+
+        config.fee_calldata = Some("0x12zz".to_string());
+        assert_invalid(config.clone(), "fee_calldata 不合法");
+        config.fee_contract = Some("0x1234".to_string());
+        assert_invalid(config, "fee_contract 不是合法地址");
+    }
+
+    #[tokio::test]
+    async fn mock_mint_value_expr() {
+        let mut config = mock_config(3, 2);
+        config.value = "0.001+id*0.0001".parse().unwrap();
+        assert!(config.value.is_expr());
+        // 预估余额时按第一笔的 id 计算
+        assert_eq!(config.init_gas_price().unwrap().value, U256::exp10(14) * 11);
+        let sender = MockSender::new(vec![]);
+        let (result, _) = mock_mint(sender.clone(), config).await;
+        assert_eq!(result.unwrap().success, 3);
+
+        let values: Vec<U256> = sender
+            .batches()
+            .concat()
+            .iter()
+            .map(|raw_tx| *decode_raw_tx(raw_tx).value().unwrap())
+            .collect();
+        let expected: Vec<U256> = [11, 12, 13].map(|n| U256::exp10(14) * n).to_vec();
+        assert_eq!(values, expected);
+
+        let mut config = mock_config(3, 2);
+        config.value = "0.001+amt".parse().unwrap();
+        assert_invalid(config.clone(), "value 表达式不合法");
+        config.value = "0.001-id".parse().unwrap();
+        assert_invalid(config, "结果小于 0");
+        assert_eq!("1gwei".parse::<TxValue>().unwrap(), TxValue::Fixed(1e-9));
+    }
+
+    #[tokio::test]
+    async fn mock_mint_paid_inscription() {
+        let mut config = mock_config(3, 2);
+        config.value = TxValue::Fixed(1.0);
+        config.to_address = Some("0x0000000000000000000000000000000000000001".to_string());
+        config.fee_contract = Some(FEE_CONTRACT.to_string());
+        config.fee_value = 0.001;
+        config.fee_calldata = Some("0x1249c58b".to_string());
+        let sender = MockSender::new(vec![]);
+        let (result, _) = mock_mint(sender.clone(), config).await;
+        assert_eq!(result.unwrap().success, 3);
+
+        let contract = Address::from_low_u64_be(0xfe);
+        for raw_tx in sender.batches().concat() {
+            let tx = decode_raw_tx(&raw_tx);
+            assert_eq!(tx.to_addr(), Some(&contract));
+            assert_eq!(tx.value(), Some(&U256::exp10(15)));
+            assert_eq!(tx.data().unwrap().to_vec(), vec![0x12, 0x49, 0xc5, 0x8b]);
+        }
+    }
+
+    #[tokio::test]
+    async fn mock_mint_paid_inscription_with_data() {
+        // 没有 fee_calldata 时仍按 data 生成铭文, 只是发到合约并附带服务费
+        let mut config = mock_config(2, 2);
+        config.fee_contract = Some(FEE_CONTRACT.to_string());
+        let sender = MockSender::new(vec![]);
+        let (result, _) = mock_mint(sender.clone(), config).await;
+        assert_eq!(result.unwrap().success, 2);
+
+        let batch = &sender.batches()[0];
+        let tx = decode_raw_tx(&batch[1]);
+        assert_eq!(tx.to_addr(), Some(&Address::from_low_u64_be(0xfe)));
+        assert_eq!(tx.value(), Some(&U256::zero()));
+        assert_eq!(
+            String::from_utf8(tx.data().unwrap().to_vec()).unwrap(),
+            r#"data:,{"p":"erc-20","op":"mint","id":"2"}"#
+        );
+    }
+
+    fn sign_test_txs(count: u64) -> (LocalWallet, Vec<TypedTransaction>) {
+        let wallet = MOCK_KEY.parse::<LocalWallet>().unwrap().with_chain_id(1u64);
+        let gas_price = GasPrice {
+            eip1559: true,
+            max_fee_per_gas: U256::exp10(10),
+            max_priority_fee_per_gas: U256::exp10(9),
+            value: U256::zero(),
+        };
+        let txs = (0..count)
+            .map(|nonce| {
+                let data = Bytes::from(format!("data:,{{\"id\":\"{}\"}}", nonce).into_bytes());
+                build_tx(wallet.address(), 1, wallet.address(), &gas_price, nonce.into(), data)
+            })
+            .collect();
+        (wallet, txs)
+    }
+
+    #[test]
+    fn sign_txs_matches_serial() {
+        let (wallet, txs) = sign_test_txs(64);
+        let serial: Vec<String> = txs.iter().map(|tx| sign_tx(&wallet, tx).unwrap()).collect();
+        let parallel = sign_txs(&wallet, &txs).unwrap();
+        assert_eq!(parallel, serial);
+        assert_eq!(raw_nonces(&parallel), (0..64).collect::<Vec<u64>>());
+        assert!(sign_txs(&wallet, &[]).unwrap().is_empty());
+    }
+
+    /// 测量并行签名的加速效果: cargo test --release sign_txs_speedup -- --ignored --nocapture
+    #[test]
+    #[ignore = "只用于测量耗时"]
+    fn sign_txs_speedup() {
+        let (wallet, txs) = sign_test_txs(1000);
+        let started = Instant::now();
+        for tx in &txs {
+            sign_tx(&wallet, tx).unwrap();
+        }
+        let serial = started.elapsed();
+        let started = Instant::now();
+        sign_txs(&wallet, &txs).unwrap();
+        let parallel = started.elapsed();
+        println!(
+            "签名 {} 笔: 串行 {:?} 并行 {:?} ({} 线程) 加速 {:.1} 倍",
+            txs.len(),
+            serial,
+            parallel,
+            rayon::current_num_threads(),
+            serial.as_secs_f64() / parallel.as_secs_f64()
+        );
     }
 }
@@ -1,187 +1,833 @@
-use std::cmp::min;
+use std::path::PathBuf;
 use std::process;
-use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
+use clap::Parser;
 use dotenv::dotenv;
-use ethers::core::k256::ecdsa::SigningKey;
 use ethers::prelude::*;
-use ethers::utils::hex;
-use ethers_batch_request::batch::{BatchRequest, BatchResponse};
-use ethers_batch_request::middleware::BatchRequestMiddleware;
-use log::{error, info, warn};
-use tokio;
-use url::Url;
+use ethers::utils::{format_ether, format_units};
+use log::{debug, error, info, warn};
 
-use lib::{Config, GasPrice, Id};
+use evm_ink_rs::{
+    build_http_client, cancel_nonces, connect_chains, decode_hex, decode_hex_gzip, decode_hex_lossy,
+    distribute, example_env, example_toml, execution_addresses, humanize_error, load_config,
+    mask_header_value, parse_number, parse_rpc_url, read_data_source, read_failed_txs,
+    read_mint_items, retry_on_timeout, run_all, serve_metrics, source_wallet, sweep_wallets, to_wei,
+    top_up_wallets, wallet_addresses, write_failed_txs, BalancePreview, BatchSender, Config,
+    FailedTx, InkError, Metrics, MintContext, MintEvent, RunReport, RunSummary, Transfer,
+    WalletFilter, WalletReport, FAILED_FILE,
+};
 
-use crate::initialization::{log_banner, print_banner, setup_logger};
-use crate::lib::{decode_hex, execution_addresses, process_id};
+use crate::cli::{Cli, Command, ExampleFormat, Output};
+use crate::decode::run_decode;
+use crate::initialization::{format_duration, log_banner, print_banner, setup_logger};
 
+mod cli;
+mod decode;
 mod initialization;
-mod lib;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    // 打印示例配置不需要读取 .env
+    if let Some(format) = cli.print_example_config {
+        match format {
+            ExampleFormat::Toml => print!("{}", example_toml()),
+            ExampleFormat::Env => print!("{}", example_env()),
+        }
+        return Ok(());
+    }
     dotenv().ok();
-    setup_logger()?;
-    print_banner();
+    // decode 不需要完整的配置
+    if let Some(Command::Decode { hex, tx, rpc_url }) = &cli.command {
+        run_decode(hex.as_deref(), tx.as_deref(), rpc_url.as_deref(), cli.no_color).await?;
+        return Ok(());
+    }
+    let (mut config, fallback_error) = match &cli.config {
+        Some(source) => {
+            load_config(source, cli.config_fallback.as_deref(), std::env::vars()).await?
+        }
+        None => (envy::from_env::<Config>()?, None),
+    };
+    setup_logger(&config, &cli)?;
+    if !config.quiet_banner {
+        print_banner();
+    }
+    if let Some(e) = fallback_error {
+        if let Some(fallback) = &cli.config_fallback {
+            warn!("{}, 已改用本地配置 {}", e, fallback.display());
+        }
+    }
     info!("开始执行任务");
-    warn!("Author:[𝕏] @0xNaiXi");
-    warn!("Author:[𝕏] @0xNaiXi");
-    warn!("Author:[𝕏] @0xNaiXi");
-    let config = envy::from_env::<Config>()?;
-
-    let provider = Provider::<Http>::try_from(&config.rpc_url)?;
-    let chain_id = provider.get_chainid().await?;
-    let client = BatchRequestMiddleware::new(provider.clone(), Url::parse(&config.rpc_url)?);
-    let gas_price = config.init_gas_price();
-
-    let wallets = execution_addresses(config);
-    info!("钱包数量: {}", wallets.len());
-    for mut config in wallets {
-        let wallet = config
-            .private_key
-            .parse::<LocalWallet>()?
-            .with_chain_id(chain_id.as_u64());
-        let address = wallet.address();
-        let nonce = provider
-            .get_transaction_count(wallet.address(), None)
-            .await?;
-        // 配置文件处理
-        let (id, current_id, id_count) = process_id(&config.data);
-        config.id = id;
-        config.count = min(config.count, id_count);
-        config.address = format!("{:?}", address);
-        // 检查配置文件
-        let to_address: Address = if let Some(str) = config.to_address.as_ref() {
-            if str.is_empty() {
-                address
-            } else {
-                str.parse()?
-            }
+    if !config.quiet_banner {
+        warn!("Author:[𝕏] @0xNaiXi");
+        warn!("Author:[𝕏] @0xNaiXi");
+        warn!("Author:[𝕏] @0xNaiXi");
+    }
+    // stdin 只能读一次 在拆分钱包之前读取 data 来源
+    config.data_list = read_data_source(&config.data)?;
+    if let Some(data) = config.data_list.first() {
+        config.data = data.clone();
+    }
+    if config.data_list.len() > 1 {
+        info!("data 数量: {} 将按顺序轮流使用", config.data_list.len());
+    }
+    if let Some(items_file) = config.items_file.clone().filter(|s| !s.trim().is_empty()) {
+        config.set_items(read_mint_items(&items_file)?);
+        info!("任务条目数量: {} 每条一笔, count 按条目数量计算", config.items.len());
+    }
+    if let Err(errors) = config.validate() {
+        error!("配置检查未通过:");
+        for e in errors {
+            error!("  - {}", e);
+        }
+        process::exit(1);
+    }
+    if let Some(private_rpc_url) = config.private_rpc_url.as_deref().filter(|s| !s.is_empty()) {
+        info!("交易将通过私有 RPC 发送: {}", private_rpc_url);
+    }
+    for (name, value) in &config.rpc_headers {
+        info!("RPC header: {}: {}", name, mask_header_value(name, value));
+    }
+
+    let ctx = Arc::new(MintContext::connect(&config.chain_configs()[0]).await?);
+    match &cli.command {
+        Some(Command::Distribute { amount }) => {
+            run_distribute(&ctx, config, amount).await?;
+            return Ok(());
+        }
+        Some(Command::Sweep) => {
+            run_sweep(&ctx, config).await?;
+            return Ok(());
+        }
+        Some(Command::Cancel {
+            from_nonce,
+            to_nonce,
+        }) => {
+            run_cancel(&ctx, config, *from_nonce, *to_nonce).await?;
+            return Ok(());
+        }
+        Some(Command::NonceStatus) => {
+            run_nonce_status(config).await?;
+            return Ok(());
+        }
+        Some(Command::Decode { .. }) | None => {}
+    }
+    let metrics = Arc::new(Metrics::new());
+    metrics.set_gas_price(ctx.gas_price.max_fee_per_gas);
+    if let Some(metrics_port) = config.metrics_port {
+        let addr = serve_metrics(metrics_port, metrics.clone())?;
+        info!("指标服务已启动: http://{}/metrics", addr);
+    }
+    if let Some(status_port) = config.status_port.filter(|port| config.metrics_port != Some(*port)) {
+        let addr = serve_metrics(status_port, metrics.clone())?;
+        info!("状态服务已启动: http://{}/status", addr);
+    }
+    let replay_txs = match &cli.replay {
+        Some(path) => {
+            let failed = read_failed_txs(path)?;
+            info!("回放模式: 只重发 {} 里的 {} 笔失败交易", path.display(), failed.len());
+            Some(failed)
+        }
+        None => None,
+    };
+    let chains = config.chain_configs();
+    let multichain = chains.len() > 1;
+    if multichain {
+        info!("多链模式: 同一份 data 打到 {} 条链", chains.len());
+    }
+    let chain_ctxs = connect_chains(ctx.clone(), &chains).await?;
+    // 每条链是一个独立的 future, 各自用自己的 chain_id 和 nonce
+    let wallet_filter = cli.wallet_filter();
+    let chain_runs = chains.into_iter().zip(chain_ctxs).map(|(chain, ctx)| {
+        let metrics = metrics.clone();
+        let wallet_filter = &wallet_filter;
+        let replay_txs = replay_txs.as_deref();
+        let chain_tag = if multichain {
+            format!("[链 {}]", ctx.chain_id)
         } else {
-            address
+            String::new()
         };
-        config.to_address = Some(format!("{:?}", to_address));
-        if config.data.is_empty() {
-            error!("data 不能为空");
-            process::exit(1);
-        }
-        let data = config.get_hex_text();
-        let text = decode_hex(&data)?;
-        info!("当前链ID: {}", chain_id);
-        info!("钱包地址: {:?}", address);
-        info!("铭文接收地址: {:?}", to_address);
-        info!("钱包nonce: {:?}", nonce);
-        info!("mint 数据: {}", text);
-        info!("十六进制数据: {}", data);
-        info!("mint总数量: {}", config.count);
-        if let Some(id) = current_id {
-            config.set_id(id);
-        }
-
-        mint(
-            &client,
-            &wallet,
-            config.clone(),
-            &gas_price,
-            nonce,
-            to_address,
-        )
-            .await?;
-        for _ in 0..3 {
-            println!();
+        async move {
+            let result =
+                run_chain(ctx, chain, metrics, &chain_tag, replay_txs, wallet_filter).await;
+            (chain_tag, result)
+        }
+    });
+    let mut addresses: Vec<Option<Address>> = Vec::new();
+    let mut results: Vec<Result<RunSummary, InkError>> = Vec::new();
+    for (chain_tag, result) in futures::future::join_all(chain_runs).await {
+        match result {
+            Ok(chain_results) => {
+                for (address, result) in chain_results {
+                    addresses.push(address);
+                    results.push(result);
+                }
+            }
+            Err(e) if multichain => error!("{} {}", chain_tag, e),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    let failed = results.iter().filter(|result| result.is_err()).count();
+    if failed > 0 {
+        error!("{} 个钱包执行失败", failed);
+    }
+    let failures: Vec<FailedTx> = results
+        .iter()
+        .flatten()
+        .flat_map(|summary| summary.failures.iter().cloned())
+        .collect();
+    // 回放时把还没成功的写回原文件, 全部成功后文件清空 避免下次重复发送
+    let failed_file = cli.replay.clone().unwrap_or_else(|| PathBuf::from(FAILED_FILE));
+    if !failures.is_empty() || cli.replay.is_some() {
+        write_failed_txs(&failed_file, &failures)?;
+        if !failures.is_empty() {
+            warn!(
+                "{} 笔失败交易已写入 {} 可以用 --replay {} 只重发这些",
+                failures.len(),
+                failed_file.display(),
+                failed_file.display()
+            );
         }
     }
+    let total_summary = results
+        .iter()
+        .flatten()
+        .fold(RunSummary::default(), |mut total, summary| {
+            total.confirmed += summary.confirmed;
+            total.gas_used += summary.gas_used;
+            total.gas_cost += summary.gas_cost;
+            total.pending += summary.pending;
+            total.dropped += summary.dropped;
+            total
+        });
+    if total_summary.pending > 0 || total_summary.dropped > 0 {
+        warn!("待确认 {} 笔, 已丢弃 {} 笔", total_summary.pending, total_summary.dropped);
+    }
+    if total_summary.confirmed > 0 {
+        info!(
+            "已上链 {} 笔 总 gas 消耗 {} ether, 平均每笔 {} ether",
+            total_summary.confirmed,
+            format_ether(total_summary.gas_cost),
+            format_ether(total_summary.average_gas_cost())
+        );
+    }
+    if cli.output == Output::Json {
+        let report = RunReport::new(
+            addresses
+                .into_iter()
+                .zip(&results)
+                .map(|(address, result)| WalletReport::new(address, result))
+                .collect(),
+        );
+        println!("{}", serde_json::to_string(&report)?);
+    }
     info!("任务执行完毕 程序将在 1000 秒后关闭");
     //编译成exe 取消下面的屏蔽 不让程序关闭窗口 不然的话 会执行完任务 直接关闭窗口 无法看输出的日志了
     //tokio::time::sleep(Duration::new(1000, 0)).await;
     Ok(())
 }
 
-async fn mint(
-    provider: &BatchRequestMiddleware<Provider<Http>>,
-    wallet: &Wallet<SigningKey>,
-    mut config: Config,
-    gas_price: &GasPrice,
-    mut nonce: U256,
-    to_address: Address,
-) -> Result<bool, Box<dyn std::error::Error>> {
-    let chain_id = wallet.chain_id();
-    //每 100 为 一组 生成 100 个 tx
-    let batch_size = config.batch_size;
-    let batch_count = (config.count + batch_size - 1) / batch_size;
-    for i in 0..batch_count {
-        let start = i * batch_size;
-        let end = min((i + 1) * batch_size, config.count);
-        let current_batch_size = end - start; // 计算当前批次的实际大小
-        log_banner(format!(
-            "第 {} 轮,共 {} 轮 当前批次大小 {}",
-            i + 1,
-            batch_count,
-            current_batch_size
-        ));
-        let mut batch = BatchRequest::with_capacity(current_batch_size as usize);
-        for _ in start..end {
-            let data = config.get_hex_text();
-            //println!("data: {}", data);
-            let data = Bytes::from_str(&data)?;
-            //println!("data: {}", hex::encode(&data));
-            let tx = if gas_price.eip1559 {
-                Eip1559TransactionRequest::new()
-                    .chain_id(chain_id)
-                    .from(wallet.address())
-                    .to(to_address)
-                    .value(gas_price.value)
-                    .max_fee_per_gas(gas_price.max_fee_per_gas)
-                    .max_priority_fee_per_gas(gas_price.max_priority_fee_per_gas)
-                    .gas(config.gas_limit)
-                    .nonce(nonce)
-                    .data(data)
-                    .access_list(vec![])
-                    .into()
-            } else {
-                TransactionRequest::new()
-                    .chain_id(chain_id)
-                    .from(wallet.address())
-                    .to(to_address)
-                    .value(gas_price.value)
-                    .nonce(nonce)
-                    .data(data)
-                    .gas(config.gas_limit)
-                    .gas_price(gas_price.max_fee_per_gas)
-                    .into()
-            };
+/// 在一条链上执行全部钱包, 结果按钱包顺序返回; chain_tag 为多链模式下日志的链标识
+async fn run_chain(
+    ctx: Arc<MintContext>,
+    config: Config,
+    metrics: Arc<Metrics>,
+    chain_tag: &str,
+    replay: Option<&[FailedTx]>,
+    wallet_filter: &WalletFilter,
+) -> Result<Vec<(Option<Address>, Result<RunSummary, InkError>)>, InkError> {
+    if let Some(start_block) = config.start_block {
+        wait_for_start_block(&ctx, &config, start_block).await?;
+    }
+    let concurrency = config.concurrency;
 
-            let signature = wallet.sign_transaction_sync(&tx)?;
-            let signed_tx = tx.rlp_signed(&signature);
+    let funding = config.clone();
+    let wallets = execution_addresses(config)?;
+    let all_wallets = wallets.len();
+    let wallets = wallet_filter.apply(wallets)?;
+    if !wallet_filter.is_empty() {
+        info!("{}按命令行过滤后执行 {}/{} 个钱包", chain_tag, wallets.len(), all_wallets);
+    }
+    if funding.funding_key().is_some() {
+        info!(
+            "{}开始检查钱包余额 低于 {} ether 的由资助钱包补足",
+            chain_tag, funding.min_gas_ether
+        );
+        let top_ups = top_up_wallets(&ctx, &funding, &wallets).await?;
+        for top_up in &top_ups {
+            if let Ok(tx_hash) = &top_up.result {
+                info!(
+                    "{}[{:?}] 已补 gas {} ether 交易哈希: {:?}",
+                    chain_tag,
+                    top_up.to,
+                    format_ether(top_up.amount),
+                    tx_hash
+                );
+            }
+        }
+        info!("{}补 gas 完成 共 {} 个钱包", chain_tag, top_ups.len());
+    }
+    if replay.is_none() {
+        // 余额预览只用于展示, 查询失败不影响后面的执行
+        if let Err(e) = preview_balances(&funding, &wallets, chain_tag).await {
+            warn!("{}查询钱包余额失败: {}", chain_tag, e);
+        }
+    }
+    let total = wallets.len();
+    info!("{}钱包数量: {} 并发数: {}", chain_tag, total, concurrency);
+    let addresses: Vec<Option<Address>> = wallets
+        .iter()
+        .map(|wallet| wallet.private_key.parse::<LocalWallet>().ok().map(|w| w.address()))
+        .collect();
+    // 日志里的钱包标识和解码 data 用的配置, 按钱包在 wallets 里的序号取
+    let wallet_logs: Vec<(String, Config)> = wallets
+        .iter()
+        .enumerate()
+        .map(|(index, wallet)| {
+            let tag = format!("{}{}", chain_tag, wallet_tag(index, total, wallet));
+            (tag, wallet.clone())
+        })
+        .collect();
+    let on_event = |index: usize, event: MintEvent| {
+        let (tag, wallet) = &wallet_logs[index];
+        match addresses[index] {
+            Some(address) => metrics.record_wallet(address, &event),
+            None => metrics.record(&event),
+        }
+        if let MintEvent::WalletFinished { .. } = event {
+            if let Some(address) = addresses[index] {
+                metrics.finish_wallet(address);
+            }
+        }
+        log_mint_event(tag, wallet, event)
+    };
+    let results = run_all(ctx, wallets, concurrency, replay, on_event).await;
+    Ok(addresses.into_iter().zip(results).collect())
+}
 
-            let sign_tx = format!("0x{}", hex::encode(signed_tx));
+/// distribute 子命令: 从源钱包给 wallets_file 里的每个地址转 amount
+async fn run_distribute(ctx: &MintContext, config: Config, amount: &str) -> Result<(), InkError> {
+    let amount = parse_number(amount, "ether").map_err(InkError::Config)?;
+    let amount = to_wei("amount", amount, "ether")?;
+    let source = source_wallet(ctx, &config)?;
+    let wallets = execution_addresses(config.clone())?;
+    let targets = wallet_addresses(source.address(), &wallets)?;
+    info!(
+        "开始分发: 从 {:?} 给 {} 个地址各转 {} ether",
+        source.address(),
+        targets.len(),
+        format_ether(amount)
+    );
+    let transfers = distribute(ctx, &config, &source, &targets, amount).await?;
+    let mut failed = 0;
+    for transfer in &transfers {
+        match &transfer.result {
+            Ok(tx_hash) => info!("[{:?}] 分发成功 交易哈希: {:?}", transfer.to, tx_hash),
+            Err(e) => {
+                failed += 1;
+                error!("[{:?}] 分发失败: {}", transfer.to, e);
+            }
+        }
+    }
+    info!("分发完成 成功 {} 笔 失败 {} 笔", transfers.len() - failed, failed);
+    Ok(())
+}
 
-            batch.add_request("eth_sendRawTransaction", vec![sign_tx])?;
-            nonce = nonce + 1;
+/// sweep 子命令: 把每个钱包扣除手续费后的余额转到 to_address
+async fn run_sweep(ctx: &MintContext, config: Config) -> Result<(), InkError> {
+    let wallets = execution_addresses(config.clone())?;
+    info!(
+        "开始归集 {} 个钱包的余额到 {}",
+        wallets.len(),
+        config.to_address.as_deref().unwrap_or("")
+    );
+    let sweeps = sweep_wallets(ctx, &config, &wallets).await?;
+    let mut total = U256::zero();
+    for sweep in &sweeps {
+        match &sweep.transfer {
+            Some(Transfer {
+                amount,
+                result: Ok(tx_hash),
+                ..
+            }) => {
+                total += *amount;
+                info!(
+                    "[{:?}] 已归集 {} ether 交易哈希: {:?}",
+                    sweep.from,
+                    format_ether(*amount),
+                    tx_hash
+                );
+            }
+            Some(Transfer { result: Err(e), .. }) => {
+                error!("[{:?}] 归集失败: {}", sweep.from, e);
+            }
+            None => warn!(
+                "[{:?}] 余额 {} ether 不足以支付手续费 跳过",
+                sweep.from,
+                format_ether(sweep.balance)
+            ),
         }
-        let mut http_responses: BatchResponse = provider.execute_batch(&mut batch).await?;
-        let mut count = 0;
+    }
+    info!("归集完成 共转出 {} ether", format_ether(total));
+    Ok(())
+}
 
-        while let Some(tx_response) = http_responses.next_response::<H256>() {
-            match tx_response {
-                Ok(tx_hash) => {
-                    info!(
-                        "第 {} 次 交易发送成功: {:?}",
-                        i * batch_size + count + 1,
-                        tx_hash
-                    );
-                }
-                Err(e) => {
-                    error!("第 {} 次 交易发送失败: {:?}", i * batch_size + count + 1, e);
+async fn run_cancel(
+    ctx: &MintContext,
+    config: Config,
+    from_nonce: u64,
+    to_nonce: u64,
+) -> Result<(), InkError> {
+    info!(
+        "开始替换 nonce {} - {} 的交易 max_fee_per_gas: {} gwei",
+        from_nonce, to_nonce, config.max_fee_per_gas
+    );
+    let cancels = cancel_nonces(ctx, &config, from_nonce, to_nonce).await?;
+    let mut success = 0;
+    for cancel in &cancels {
+        match &cancel.result {
+            Ok(tx_hash) => {
+                success += 1;
+                info!("[nonce {}] 已替换 交易哈希: {:?}", cancel.nonce, tx_hash);
+            }
+            Err(e) => error!("[nonce {}] 替换失败: {}", cancel.nonce, e),
+        }
+    }
+    info!("替换完成 成功: {} 失败: {}", success, cancels.len() - success);
+    Ok(())
+}
+
+/// 查询用的 batch 客户端, 使用第一个 rpc_url
+fn query_sender(config: &Config) -> Result<BatchSender, InkError> {
+    Ok(BatchSender::new(
+        build_http_client(config)?,
+        parse_rpc_url(config.rpc_urls()[0])?,
+    ))
+}
+
+/// 开打前用一个 batch 请求查询所有钱包的余额, 打印每个钱包预估可打的笔数和汇总
+async fn preview_balances(
+    config: &Config,
+    wallets: &[Config],
+    chain_tag: &str,
+) -> Result<(), InkError> {
+    let addresses = wallets
+        .iter()
+        .map(|wallet| {
+            wallet
+                .private_key
+                .parse::<LocalWallet>()
+                .map(|wallet| wallet.address())
+                .map_err(|e| InkError::Wallet(e.to_string()))
+        })
+        .collect::<Result<Vec<Address>, InkError>>()?;
+    let balances = query_sender(config)?.balances(&addresses).await?;
+    let mut total_balance = U256::zero();
+    let mut total_count: u64 = 0;
+    let mut short = 0;
+    for ((wallet, address), balance) in wallets.iter().zip(addresses).zip(balances) {
+        let preview = BalancePreview::new(wallet, address, balance)?;
+        let affordable = if preview.affordable == u64::MAX {
+            "不限".to_string()
+        } else {
+            preview.affordable.to_string()
+        };
+        let line = format!(
+            "{}[{:?}] 余额 {} ether 预估可打 {} 笔",
+            chain_tag,
+            address,
+            format_ether(balance),
+            affordable
+        );
+        if preview.is_enough() {
+            info!("{}", line);
+        } else {
+            short += 1;
+            warn!("{} 不够计划的 {} 笔", line, preview.planned);
+        }
+        total_balance += balance;
+        total_count = total_count.saturating_add(preview.affordable);
+    }
+    info!(
+        "{}共 {} 个钱包 总余额 {} ether 预估共可打 {} 笔 其中 {} 个钱包余额不够计划的笔数",
+        chain_tag,
+        wallets.len(),
+        format_ether(total_balance),
+        total_count,
+        short
+    );
+    Ok(())
+}
+
+async fn run_nonce_status(config: Config) -> Result<(), InkError> {
+    let sender = query_sender(&config)?;
+    let mut addresses: Vec<Address> = Vec::new();
+    for wallet in execution_addresses(config)? {
+        let address = wallet
+            .private_key
+            .parse::<LocalWallet>()
+            .map_err(|e| InkError::Wallet(e.to_string()))?
+            .address();
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+    let statuses = sender.nonce_statuses(&addresses).await?;
+    let mut stuck = 0;
+    for status in &statuses {
+        let queued = status.queued();
+        let line = format!(
+            "[{:?}] latest: {} pending: {} 积压: {}",
+            status.address, status.latest, status.pending, queued
+        );
+        if queued.is_zero() {
+            info!("{}", line);
+        } else {
+            stuck += 1;
+            warn!("{}", line);
+        }
+    }
+    info!("共 {} 个钱包 其中 {} 个有积压的交易", statuses.len(), stuck);
+    Ok(())
+}
+
+/// 轮询区块高度的间隔
+const START_BLOCK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 轮询 get_block_number 直到到达 start_block
+async fn wait_for_start_block(
+    ctx: &MintContext,
+    config: &Config,
+    start_block: u64,
+) -> Result<(), InkError> {
+    let mut last_block = None;
+    loop {
+        let current = retry_on_timeout(
+            config.rpc_timeout(),
+            config.max_retries,
+            "get_block_number",
+            || ctx.provider.get_block_number(),
+        )
+        .await?
+        .as_u64();
+        if current >= start_block {
+            info!("当前区块 {} 已到达开始区块 {}, 开始执行", current, start_block);
+            return Ok(());
+        }
+        // 区块高度变化时才打印 避免刷屏
+        if last_block != Some(current) {
+            info!(
+                "当前区块 {} 目标区块 {} 还差 {} 个区块",
+                current,
+                start_block,
+                start_block - current
+            );
+            last_block = Some(current);
+        }
+        tokio::time::sleep(START_BLOCK_POLL_INTERVAL).await;
+    }
+}
+
+fn wallet_tag(index: usize, total: usize, config: &Config) -> String {
+    match config.private_key.parse::<LocalWallet>() {
+        Ok(wallet) => {
+            let address = format!("{:?}", wallet.address());
+            format!(
+                "[钱包 {}/{} {}..{}]",
+                index + 1,
+                total,
+                &address[..6],
+                &address[address.len() - 4..]
+            )
+        }
+        Err(_) => format!("[钱包 {}/{}]", index + 1, total),
+    }
+}
+
+/// 错误后面附上 humanize_error 的中文提示
+fn with_hint(error: &str) -> String {
+    match humanize_error(error) {
+        Some(hint) => format!("{} (提示: {})", error, hint),
+        None => error.to_string(),
+    }
+}
+
+fn log_mint_event(tag: &str, config: &Config, event: MintEvent) {
+    match event {
+        MintEvent::AutoCount { balance, count } => {
+            info!(
+                "{} 钱包余额: {} ether 自动计算可 mint 数量: {}",
+                tag,
+                format_ether(balance),
+                count
+            );
+            if count == 0 {
+                warn!("{} 余额不足 跳过", tag);
+            }
+        }
+        MintEvent::StartJitter { delay } => {
+            info!("{} 随机等待 {} 后开始", tag, format_duration(delay));
+        }
+        MintEvent::SendWindowWait { window, delay } => {
+            info!(
+                "{} 当前不在发送时间窗口 {} 内, 等待 {} 后继续",
+                tag,
+                window,
+                format_duration(delay)
+            );
+        }
+        MintEvent::WalletRpc { rpc_url } => {
+            info!("{} 使用单独的 RPC: {}", tag, rpc_url);
+        }
+        MintEvent::WalletReady {
+            chain_id,
+            address,
+            recipients,
+            nonce,
+            count,
+            data,
+        } => {
+            let paid = config.paid_inscription().ok().flatten();
+            // 付费铭文的 calldata 是合约方法调用 不是文本
+            let is_calldata = paid.as_ref().is_some_and(|paid| paid.calldata.is_some());
+            let text = if is_calldata {
+                Ok(String::new())
+            } else if config.is_gzip_text() {
+                decode_hex_gzip(&data, &config.prefix)
+            } else if config.encoding.is_some() {
+                // 自定义编码管线的结果不一定是文本
+                decode_hex_lossy(&data).map(|(text, _)| text)
+            } else if config.strict_utf8 {
+                decode_hex(&data)
+            } else {
+                decode_hex_lossy(&data).map(|(text, valid)| {
+                    if !valid {
+                        warn!("{} mint 数据不是合法的 UTF-8 文本, 以下为 lossy 显示", tag);
+                    }
+                    text
+                })
+            };
+            info!("{} 当前链ID: {}", tag, chain_id);
+            info!("{} 钱包地址: {:?}", tag, address);
+            match recipients.as_slice() {
+                [(to_address, _)] => info!("{} 铭文接收地址: {:?}", tag, to_address),
+                _ => {
+                    let recipients: Vec<String> = recipients
+                        .iter()
+                        .map(|(to_address, weight)| format!("{:?}({})", to_address, weight))
+                        .collect();
+                    info!("{} 铭文接收地址(权重): {}", tag, recipients.join(", "));
                 }
             }
-            count += 1;
+            info!("{} 钱包nonce: {:?}", tag, nonce);
+            if let Some(paid) = &paid {
+                info!(
+                    "{} 付费铭文 合约: {:?} 每笔服务费: {} ether",
+                    tag,
+                    paid.contract,
+                    format_ether(paid.value)
+                );
+            }
+            match text {
+                _ if is_calldata => {}
+                Ok(text) => info!("{} mint 数据: {}", tag, text),
+                Err(e) => error!("{} mint 数据解码失败: {}", tag, e),
+            }
+            info!("{} 十六进制数据: {}", tag, data);
+            info!("{} mint总数量: {}", tag, count);
+        }
+        MintEvent::BatchStart {
+            index,
+            total,
+            size,
+            elapsed,
+            eta,
+        } => {
+            log_banner(format!(
+                "{} 第 {} 轮,共 {} 轮 当前批次大小 {} 已用时 {} 预计剩余 {}",
+                tag,
+                index,
+                total,
+                size,
+                format_duration(elapsed),
+                format_duration(eta)
+            ));
+        }
+        MintEvent::TxSigned {
+            index,
+            nonce,
+            id,
+            tx_hash,
+        } => {
+            let id = id.map(|id| format!(" id {}", id)).unwrap_or_default();
+            debug!(
+                "{} 第 {} 次 nonce {}{} 预计算交易哈希: {:?}",
+                tag, index, nonce, id, tx_hash
+            );
+        }
+        MintEvent::TxSent {
+            index,
+            nonce,
+            tx_hash,
+        } => {
+            info!("{} 第 {} 次 nonce {} 交易发送成功: {:?}", tag, index, nonce, tx_hash);
+        }
+        MintEvent::TxFailed {
+            index,
+            nonce,
+            id,
+            raw_tx,
+            error,
+            ..
+        } => {
+            let id = id.map(|id| format!(" id {}", id)).unwrap_or_default();
+            error!(
+                "{} 第 {} 次 nonce {}{} 交易发送失败: {}",
+                tag,
+                index,
+                nonce,
+                id,
+                with_hint(&error)
+            );
+            error!("{} 原始交易(可用 eth_sendRawTransaction 复现): {}", tag, raw_tx);
+        }
+        MintEvent::TxRetry {
+            index,
+            attempt,
+            error,
+        } => {
+            warn!(
+                "{} 第 {} 次交易发送失败({}), 按 error_policy 第 {} 次重发",
+                tag, index, error, attempt
+            );
+        }
+        MintEvent::TxSkipped {
+            index,
+            nonce,
+            error,
+        } => {
+            warn!("{} 第 {} 次 nonce {} 交易按 error_policy 跳过: {}", tag, index, nonce, error);
+        }
+        MintEvent::FeeClamp { base_fee, max_fee } => {
+            info!(
+                "{} 当前 base fee {} gwei 本批 max_fee 上限 {} gwei",
+                tag,
+                format_units(base_fee, "gwei").unwrap_or_default(),
+                format_units(max_fee, "gwei").unwrap_or_default()
+            );
+        }
+        MintEvent::GasGate {
+            current,
+            gate,
+            passed,
+        } => {
+            let current = format_units(current, "gwei").unwrap_or_default();
+            let gate = format_units(gate, "gwei").unwrap_or_default();
+            if passed {
+                info!("{} 当前 gas {} gwei <= 门控 {} gwei, 开始发送", tag, current, gate);
+            } else {
+                info!("{} 当前 gas {} gwei > 门控 {} gwei, 等待 gas 降低", tag, current, gate);
+            }
+        }
+        MintEvent::BundleSent {
+            bundle_hash,
+            target_block,
+            attempt,
+        } => {
+            info!(
+                "{} bundle 已提交(第 {} 次) 目标区块 {}: {}",
+                tag,
+                attempt + 1,
+                target_block,
+                bundle_hash
+            );
+        }
+        MintEvent::BundleMissed {
+            target_block,
+            attempt,
+            error,
+        } => {
+            warn!(
+                "{} bundle 第 {} 次未上链 目标区块 {}: {}",
+                tag,
+                attempt + 1,
+                target_block,
+                error
+            );
+        }
+        MintEvent::WatchFinished {
+            remaining,
+            timed_out,
+        } => {
+            if timed_out {
+                warn!("{} 监控模式已到 watch_timeout_secs, 还有 {} 笔未打 退出", tag, remaining);
+            } else {
+                info!("{} 监控模式 count 已全部打完 退出", tag);
+            }
+        }
+        MintEvent::BatchConfirmed {
+            index,
+            confirmed,
+            pending,
+            dropped,
+            resent,
+        } => {
+            if pending == 0 && dropped == 0 {
+                info!("{} 第 {} 轮 {} 笔交易已全部上链", tag, index, confirmed);
+            } else {
+                warn!(
+                    "{} 第 {} 轮 已确认 {} 笔, 待确认 {} 笔, 已丢弃 {} 笔(重发成功 {} 笔)",
+                    tag, index, confirmed, pending, dropped, resent
+                );
+            }
+        }
+        MintEvent::NonceShortfall {
+            before,
+            after,
+            success,
+            missing,
+        } => {
+            warn!(
+                "{} 链上 nonce 从 {} 增长到 {}, 比成功发送的 {} 笔少 {} 笔, 部分交易可能未上链",
+                tag, before, after, success, missing
+            );
+        }
+        MintEvent::BatchDone { .. } => {}
+        MintEvent::WalletFinished { error } => {
+            if let Some(error) = error {
+                error!("{} {}", tag, with_hint(&error));
+            }
+        }
+        MintEvent::WalletInterval { delay } => {
+            info!("{} 执行结束 {} 秒后进入下一个钱包", tag, delay.as_secs_f64());
+        }
+        MintEvent::BatchLimitReduced { limit } => {
+            warn!("{} 节点提示 batch 过大, 之后每次最多发送 {} 笔", tag, limit);
+        }
+        MintEvent::Approved {
+            token,
+            spender,
+            amount,
+            tx_hash,
+        } => match tx_hash {
+            Some(tx_hash) => info!(
+                "{} 已授权 {:?} 使用代币 {:?} 数量 {} 交易哈希: {:?}",
+                tag, spender, token, amount, tx_hash
+            ),
+            None => info!(
+                "{} 代币 {:?} 对 {:?} 已有足额授权(不少于 {}) 跳过 approve",
+                tag, token, spender, amount
+            ),
+        },
+        MintEvent::LegacyFallback { error } => {
+            warn!(
+                "{} 节点不支持 EIP-1559 交易({}), 剩余交易已回退为 legacy 使用 max_fee_per_gas 作为 gas_price",
+                tag, error
+            );
         }
-        tokio::time::sleep(tokio::time::Duration::from_secs_f64(config.interval)).await;
     }
-    Ok(true)
 }
 
 #[cfg(test)]
@@ -203,16 +849,16 @@ mod tests {
         let re = regex::Regex::new(r"\[(\d+)?-(\d+)?]").unwrap();
         let text = r#"{"p":"erc-20","op":"mint","tick":"pi","id":"6227","amt":"1000"}"#;
         println!("text: {}", text);
-        if let Some(caps) = re.captures(&text) {
-            let start_id: Option<u64> = caps.get(1).and_then(|m| m.as_str().parse().ok());
-            let end_id: Option<u64> = caps.get(2).and_then(|m| m.as_str().parse().ok());
+        if let Some(caps) = re.captures(text) {
+            let _start_id: Option<u64> = caps.get(1).and_then(|m| m.as_str().parse().ok());
+            let _end_id: Option<u64> = caps.get(2).and_then(|m| m.as_str().parse().ok());
         } else {
             println!("未匹配到任何内容");
         }
 
         let text = r#"{"p":"erc-20","op":"mint","tick":"pi","id":"[1200-]","to":"[address]","amt":"1000"}"#;
         // start_id 可能为 u64 也可能为 None
-        if let Some(caps) = re.captures(&text) {
+        if let Some(caps) = re.captures(text) {
             let start_id: Option<u64> = caps.get(1).and_then(|m| m.as_str().parse().ok());
             let end_id: Option<u64> = caps.get(2).and_then(|m| m.as_str().parse().ok());
             assert_eq!(start_id, Some(1200));
@@ -224,7 +870,7 @@ mod tests {
 
         let text = r#"{"p":"erc-20","op":"mint","tick":"pi","id":"[-2000]","to":"[address]","amt":"1000"}"#;
 
-        if let Some(caps) = re.captures(&text) {
+        if let Some(caps) = re.captures(text) {
             let start_id: Option<u64> = caps.get(1).and_then(|m| m.as_str().parse().ok());
             let end_id: Option<u64> = caps.get(2).and_then(|m| m.as_str().parse().ok());
             assert_eq!(start_id, None);
@@ -236,7 +882,7 @@ mod tests {
 
         let text = r#"{"p":"erc-20","op":"mint","tick":"pi","id":"[1200-2000]","to":"[address]","amt":"1000"}"#;
 
-        if let Some(caps) = re.captures(&text) {
+        if let Some(caps) = re.captures(text) {
             let start_id: Option<u64> = caps.get(1).and_then(|m| m.as_str().parse().ok());
             let end_id: Option<u64> = caps.get(2).and_then(|m| m.as_str().parse().ok());
             assert_eq!(start_id, Some(1200));
@@ -0,0 +1,317 @@
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use ethers::prelude::{Address, U256};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::Serialize;
+
+use crate::{InkError, MintEvent};
+
+/// /status 返回的运行状态
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct RunStatus {
+    /// 正在执行的钱包
+    pub current_wallets: Vec<Address>,
+    pub wallets_done: u64,
+    pub sent: u64,
+    /// 已开始的钱包计划发送的总笔数
+    pub total: u64,
+    pub success: u64,
+    /// 成功笔数 / 已发送笔数, 还没有发送时为 0
+    pub success_rate: f64,
+    /// 正在执行的钱包里最长的预计剩余时间(秒)
+    pub eta_secs: u64,
+}
+
+/// 运行指标, 多个钱包并发时共用一份, 计数使用原子变量
+///
+/// /status 的状态放在 Mutex 里, 每次只在更新或复制快照时短暂持有
+#[derive(Debug, Default)]
+pub struct Metrics {
+    sent: AtomicU64,
+    success: AtomicU64,
+    failed: AtomicU64,
+    /// 当前 gas 价格(wei)
+    gas_price: AtomicU64,
+    /// 已开始但还没有结束的批次数
+    inflight_batches: AtomicI64,
+    status: Mutex<RunStatus>,
+    /// 每个正在执行的钱包最近一批报告的预计剩余时间(秒)
+    wallet_etas: Mutex<HashMap<Address, u64>>,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_gas_price(&self, gas_price: U256) {
+        let gas_price = if gas_price > U256::from(u64::MAX) {
+            u64::MAX
+        } else {
+            gas_price.as_u64()
+        };
+        self.gas_price.store(gas_price, Ordering::Relaxed);
+    }
+
+    /// 当前运行状态的快照
+    pub fn status(&self) -> RunStatus {
+        let mut status = self.status.lock().unwrap().clone();
+        if status.sent > 0 {
+            status.success_rate = status.success as f64 / status.sent as f64;
+        }
+        status.eta_secs = self.wallet_etas.lock().unwrap().values().copied().max().unwrap_or(0);
+        status
+    }
+
+    /// 钱包执行结束(不论成功失败)
+    pub fn finish_wallet(&self, address: Address) {
+        let mut status = self.status.lock().unwrap();
+        status.current_wallets.retain(|current| *current != address);
+        status.wallets_done += 1;
+        self.wallet_etas.lock().unwrap().remove(&address);
+    }
+
+    fn update_status(&self, event: &MintEvent) {
+        let mut status = self.status.lock().unwrap();
+        match event {
+            MintEvent::WalletReady { address, count, .. } => {
+                status.current_wallets.push(*address);
+                status.total += count;
+            }
+            MintEvent::TxSent { .. } => {
+                status.sent += 1;
+                status.success += 1;
+            }
+            MintEvent::TxFailed { .. } | MintEvent::TxSkipped { .. } => status.sent += 1,
+            _ => {}
+        }
+    }
+
+    /// 按某个钱包的 mint 事件更新指标, 同时记录该钱包的预计剩余时间
+    pub fn record_wallet(&self, address: Address, event: &MintEvent) {
+        if let MintEvent::BatchStart { eta, .. } = event {
+            self.wallet_etas.lock().unwrap().insert(address, eta.as_secs());
+        }
+        self.record(event);
+    }
+
+    /// 按 mint 事件更新指标
+    pub fn record(&self, event: &MintEvent) {
+        self.update_status(event);
+        match event {
+            MintEvent::BatchStart { .. } => {
+                self.inflight_batches.fetch_add(1, Ordering::Relaxed);
+            }
+            MintEvent::BatchDone { .. } => {
+                self.inflight_batches.fetch_sub(1, Ordering::Relaxed);
+            }
+            MintEvent::TxSent { .. } => {
+                self.sent.fetch_add(1, Ordering::Relaxed);
+                self.success.fetch_add(1, Ordering::Relaxed);
+            }
+            MintEvent::TxFailed { .. } => {
+                self.sent.fetch_add(1, Ordering::Relaxed);
+                self.failed.fetch_add(1, Ordering::Relaxed);
+            }
+            MintEvent::GasGate { current, .. } => self.set_gas_price(*current),
+            MintEvent::FeeClamp { max_fee, .. } => self.set_gas_price(*max_fee),
+            _ => {}
+        }
+    }
+
+    /// Prometheus 文本格式
+    pub fn render(&self) -> String {
+        let metrics = [
+            (
+                "evm_ink_sent_total",
+                "counter",
+                "已发送的交易数",
+                self.sent.load(Ordering::Relaxed).to_string(),
+            ),
+            (
+                "evm_ink_success_total",
+                "counter",
+                "发送成功的交易数",
+                self.success.load(Ordering::Relaxed).to_string(),
+            ),
+            (
+                "evm_ink_failed_total",
+                "counter",
+                "发送失败的交易数",
+                self.failed.load(Ordering::Relaxed).to_string(),
+            ),
+            (
+                "evm_ink_gas_price_wei",
+                "gauge",
+                "当前 gas 价格(wei)",
+                self.gas_price.load(Ordering::Relaxed).to_string(),
+            ),
+            (
+                "evm_ink_inflight_batches",
+                "gauge",
+                "正在发送的批次数",
+                self.inflight_batches.load(Ordering::Relaxed).to_string(),
+            ),
+        ];
+        let mut text = String::new();
+        for (name, kind, help, value) in metrics {
+            let _ = writeln!(text, "# HELP {} {}", name, help);
+            let _ = writeln!(text, "# TYPE {} {}", name, kind);
+            let _ = writeln!(text, "{} {}", name, value);
+        }
+        text
+    }
+}
+
+async fn handle(
+    metrics: Arc<Metrics>,
+    request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/metrics") => Response::builder()
+            .header("Content-Type", "text/plain; version=0.0.4; charset=utf-8")
+            .body(Body::from(metrics.render())),
+        (&Method::GET, "/status") => {
+            let status = metrics.status();
+            Response::builder()
+                .header("Content-Type", "application/json")
+                .body(Body::from(serde_json::to_string(&status).unwrap()))
+        }
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty()),
+    };
+    Ok(response.unwrap())
+}
+
+/// 在后台启动 /metrics 和 /status 服务, 返回实际监听的地址(port 为 0 时随机分配端口)
+pub fn serve_metrics(port: u16, metrics: Arc<Metrics>) -> Result<SocketAddr, InkError> {
+    let addr = SocketAddr::from(([0, 0, 0, 0], port));
+    let make_service = make_service_fn(move |_| {
+        let metrics = metrics.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |request| handle(metrics.clone(), request))) }
+    });
+    let server = Server::try_bind(&addr)
+        .map_err(|e| InkError::Config(format!("metrics 端口 {} 监听失败: {}", port, e)))?
+        .serve(make_service);
+    let local_addr = server.local_addr();
+    tokio::spawn(async move {
+        if let Err(e) = server.await {
+            log::error!("metrics 服务异常退出: {}", e);
+        }
+    });
+    Ok(local_addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_events() {
+        let metrics = Metrics::new();
+        metrics.record(&MintEvent::BatchStart {
+            index: 1,
+            total: 1,
+            size: 2,
+            elapsed: Default::default(),
+            eta: Default::default(),
+        });
+        metrics.record(&MintEvent::TxSent {
+            index: 1,
+            nonce: U256::zero(),
+            tx_hash: Default::default(),
+        });
+        metrics.record(&MintEvent::TxFailed {
+            index: 2,
+            nonce: U256::one(),
+            id: None,
+            data: Default::default(),
+            raw_tx: String::new(),
+            error: "nonce too low".to_string(),
+        });
+        metrics.record(&MintEvent::GasGate {
+            current: U256::exp10(9),
+            gate: U256::exp10(10),
+            passed: true,
+        });
+        let text = metrics.render();
+        assert!(text.contains("evm_ink_sent_total 2\n"));
+        assert!(text.contains("evm_ink_success_total 1\n"));
+        assert!(text.contains("evm_ink_failed_total 1\n"));
+        assert!(text.contains("evm_ink_gas_price_wei 1000000000\n"));
+        assert!(text.contains("evm_ink_inflight_batches 1\n"));
+        assert!(text.contains("# TYPE evm_ink_sent_total counter\n"));
+
+        metrics.record(&MintEvent::BatchDone { index: 1 });
+        assert!(metrics.render().contains("evm_ink_inflight_batches 0\n"));
+    }
+
+    #[test]
+    fn status_tracks_wallets() {
+        let metrics = Metrics::new();
+        assert_eq!(metrics.status(), RunStatus::default());
+        let a = Address::from_low_u64_be(1);
+        let b = Address::from_low_u64_be(2);
+        for address in [a, b] {
+            metrics.record(&MintEvent::WalletReady {
+                chain_id: U256::one(),
+                address,
+                recipients: vec![],
+                nonce: U256::zero(),
+                count: 10,
+                data: String::new(),
+            });
+        }
+        // 每个钱包的预计剩余时间分开记录, 报告最长的那个
+        for (address, eta) in [(a, 30), (b, 8)] {
+            metrics.record_wallet(
+                address,
+                &MintEvent::BatchStart {
+                    index: 1,
+                    total: 5,
+                    size: 2,
+                    elapsed: Default::default(),
+                    eta: std::time::Duration::from_secs(eta),
+                },
+            );
+        }
+        assert_eq!(metrics.status().eta_secs, 30);
+        for index in 1..=3 {
+            metrics.record(&MintEvent::TxSent {
+                index,
+                nonce: U256::from(index),
+                tx_hash: Default::default(),
+            });
+        }
+        metrics.record(&MintEvent::TxFailed {
+            index: 4,
+            nonce: U256::from(4),
+            id: None,
+            data: Default::default(),
+            raw_tx: String::new(),
+            error: "nonce too low".to_string(),
+        });
+        metrics.record(&MintEvent::TxSkipped {
+            index: 5,
+            nonce: U256::from(5),
+            error: "nonce too low".to_string(),
+        });
+        metrics.finish_wallet(a);
+
+        let status = metrics.status();
+        assert_eq!(status.current_wallets, vec![b]);
+        assert_eq!(status.wallets_done, 1);
+        assert_eq!(status.total, 20);
+        assert_eq!(status.sent, 5);
+        assert_eq!(status.success, 3);
+        assert_eq!(status.success_rate, 0.6);
+        assert_eq!(status.eta_secs, 8);
+    }
+}
@@ -0,0 +1,94 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 令牌桶限速, 每秒补充 rate 个令牌, 桶里最多攒 rate 个(至少 1 个)
+///
+/// 一次取的令牌超过桶里的数量时先记欠账, 调用方按欠账等待, 长期看发送速率不超过 rate
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    capacity: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// rate 为每秒的令牌数, 必须大于 0
+    pub fn new(rate: f64) -> Self {
+        let capacity = rate.max(1.0);
+        RateLimiter {
+            rate,
+            capacity,
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// 预占 n 个令牌, 返回需要等待多久才能发送
+    pub fn reserve(&self, n: usize) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.capacity);
+        bucket.updated = now;
+        bucket.tokens -= n as f64;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.rate)
+        }
+    }
+
+    /// 取 n 个令牌, 不够时等待
+    pub async fn acquire(&self, n: usize) {
+        let wait = self.reserve(n);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserve_tokens() {
+        let limiter = RateLimiter::new(10.0);
+        // 一开始桶是满的
+        assert_eq!(limiter.reserve(10), Duration::ZERO);
+        let wait = limiter.reserve(5);
+        assert!(wait > Duration::from_millis(450) && wait <= Duration::from_millis(500));
+        // 欠账累加
+        let wait = limiter.reserve(5);
+        assert!(wait > Duration::from_millis(950) && wait <= Duration::from_secs(1));
+
+        // rate 小于 1 时桶里至少能放 1 个
+        let limiter = RateLimiter::new(0.5);
+        assert_eq!(limiter.reserve(1), Duration::ZERO);
+        assert!(limiter.reserve(1) > Duration::from_millis(1900));
+    }
+
+    #[tokio::test]
+    async fn acquire_caps_rate() {
+        let rate = 100.0;
+        let limiter = RateLimiter::new(rate);
+        let started = Instant::now();
+        let mut sent = 0;
+        while started.elapsed() < Duration::from_millis(300) {
+            limiter.acquire(2).await;
+            sent += 2;
+        }
+        // 发送数不超过桶容量加上这段时间补充的令牌
+        let limit = rate + rate * started.elapsed().as_secs_f64();
+        assert!(sent as f64 <= limit + 2.0, "{} > {}", sent, limit);
+        assert!(sent as f64 >= rate, "{}", sent);
+    }
+}
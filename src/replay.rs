@@ -0,0 +1,164 @@
+use std::path::Path;
+
+use ethers::prelude::{Address, Bytes, LocalWallet, Middleware, Signer, U256};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    build_tx, retry_on_timeout, send_batch, sign_txs, Config, GasPrice, InkError, MintContext,
+    MintEvent, RunSummary,
+};
+
+/// 默认导出失败交易的文件
+pub const FAILED_FILE: &str = "failed.json";
+
+/// 一笔发送失败的交易, 导出到 failed.json 后可以用 --replay 只重发这些
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FailedTx {
+    pub chain_id: u64,
+    pub wallet: Address,
+    /// 原来是第几笔(从 1 开始)
+    pub index: u64,
+    /// 原来使用的 nonce, 回放时按链上当前 nonce 重新分配
+    pub nonce: U256,
+    pub to: Address,
+    pub value: U256,
+    pub data: Bytes,
+    pub error: String,
+}
+
+/// 把失败交易写成 JSON 数组
+pub fn write_failed_txs(path: &Path, failed: &[FailedTx]) -> Result<(), InkError> {
+    let json = serde_json::to_string_pretty(failed)
+        .map_err(|e| InkError::Data(format!("序列化失败交易失败: {}", e)))?;
+    std::fs::write(path, json)?;
+    Ok(())
+}
+
+pub fn read_failed_txs(path: &Path) -> Result<Vec<FailedTx>, InkError> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| InkError::Config(format!("读取文件失败: {}: {}", path.display(), e)))?;
+    serde_json::from_str(&json)
+        .map_err(|e| InkError::Config(format!("{} 格式不正确: {}", path.display(), e)))
+}
+
+/// 从 nonce 开始按顺序重发 failed 里的交易, to/value/data 不变, gas 使用当前配置(含 gas_limit_buffer_percent)
+///
+/// 按 batch_size 分批发送, 再次失败的交易仍记到 summary.failures
+pub async fn replay<F>(
+    ctx: &MintContext,
+    wallet: &LocalWallet,
+    config: &Config,
+    gas_price: &GasPrice,
+    mut nonce: U256,
+    failed: &[FailedTx],
+    mut on_event: F,
+) -> Result<RunSummary, InkError>
+where
+    F: FnMut(MintEvent),
+{
+    let mut summary = RunSummary {
+        address: wallet.address(),
+        ..Default::default()
+    };
+    let mut batch_limit = config.batch_size.max(1) as usize;
+    for chunk in failed.chunks(config.batch_size.max(1) as usize) {
+        let txs: Vec<_> = chunk
+            .iter()
+            .map(|failed| {
+                let gas_price = GasPrice {
+                    value: failed.value,
+                    ..gas_price.clone()
+                };
+                let mut tx = build_tx(
+                    wallet.address(),
+                    wallet.chain_id(),
+                    failed.to,
+                    &gas_price,
+                    nonce,
+                    failed.data.clone(),
+                );
+                tx.set_gas(config.buffered_gas_limit());
+                nonce += U256::one();
+                tx
+            })
+            .collect();
+        let batch = sign_txs(wallet, &txs)?;
+        let responses = send_batch(ctx, config, &batch, &mut batch_limit, &mut on_event).await?;
+        for (((response, tx), raw_tx), failed) in
+            responses.into_iter().zip(&txs).zip(&batch).zip(chunk)
+        {
+            let nonce = tx.nonce().copied().unwrap_or_default();
+            summary.sent += 1;
+            match response {
+                Ok(tx_hash) => {
+                    summary.success += 1;
+                    summary.tx_hashes.push(tx_hash);
+                    on_event(MintEvent::TxSent {
+                        index: failed.index,
+                        nonce,
+                        tx_hash,
+                    });
+                }
+                Err(error) => {
+                    summary.failed += 1;
+                    summary.errors.push(format!(
+                        "第 {} 笔(nonce {}): {}",
+                        failed.index, nonce, error
+                    ));
+                    summary.failures.push(FailedTx {
+                        nonce,
+                        error: error.clone(),
+                        ..failed.clone()
+                    });
+                    on_event(MintEvent::TxFailed {
+                        index: failed.index,
+                        nonce,
+                        id: None,
+                        data: failed.data.clone(),
+                        raw_tx: raw_tx.clone(),
+                        error,
+                    });
+                }
+            }
+        }
+    }
+    Ok(summary)
+}
+
+/// 取 config 钱包当前的 nonce 后重发 failed 里属于这个钱包和这条链的交易
+pub async fn replay_wallet<F>(
+    ctx: &MintContext,
+    config: &Config,
+    failed: &[FailedTx],
+    on_event: F,
+) -> Result<RunSummary, InkError>
+where
+    F: FnMut(MintEvent),
+{
+    let wallet = config
+        .private_key
+        .parse::<LocalWallet>()
+        .map_err(|e| InkError::Wallet(e.to_string()))?
+        .with_chain_id(ctx.chain_id.as_u64());
+    let address = wallet.address();
+    let failed: Vec<FailedTx> = failed
+        .iter()
+        .filter(|failed| failed.wallet == address && failed.chain_id == ctx.chain_id.as_u64())
+        .cloned()
+        .collect();
+    if failed.is_empty() {
+        return Ok(RunSummary {
+            address,
+            ..Default::default()
+        });
+    }
+    let nonce = retry_on_timeout(
+        config.rpc_timeout(),
+        config.max_retries,
+        "get_transaction_count",
+        || ctx.provider.get_transaction_count(address, None),
+    )
+    .await?;
+    let gas_price = config.init_gas_price()?;
+    replay(ctx, &wallet, config, &gas_price, nonce, &failed, on_event).await
+}
@@ -0,0 +1,205 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// 按大小或日期滚动的日志文件
+///
+/// 滚动时 path 改名为 path.1, 原来的 path.1 改名为 path.2 ..., 只保留最近 keep 个旧文件
+pub struct RollingFile {
+    path: PathBuf,
+    /// 超过该字节数时切分新文件, 0 为不按大小切分
+    max_bytes: u64,
+    /// 日期变化时切分新文件
+    daily: bool,
+    keep: usize,
+    /// 切分时先关闭再改名, 否则 Windows 上无法重命名正在打开的文件
+    file: Option<File>,
+    written: u64,
+    /// 当前文件对应的日期
+    date: String,
+    /// 只在一行的开头切分 避免一行日志被拆到两个文件
+    at_line_start: bool,
+}
+
+fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+impl RollingFile {
+    pub fn new(
+        path: impl AsRef<Path>,
+        max_bytes: u64,
+        daily: bool,
+        keep: usize,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = open_append(&path)?;
+        let metadata = file.metadata()?;
+        // 已有的日志文件按最后修改时间算日期
+        let date = match metadata.modified() {
+            Ok(modified) if metadata.len() > 0 => {
+                let modified: chrono::DateTime<chrono::Local> = modified.into();
+                modified.format("%Y-%m-%d").to_string()
+            }
+            _ => today(),
+        };
+        Ok(RollingFile {
+            path,
+            max_bytes,
+            daily,
+            keep,
+            file: Some(file),
+            written: metadata.len(),
+            date,
+            at_line_start: true,
+        })
+    }
+
+    fn rolled_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
+    }
+
+    /// 写入 len 字节前检查是否需要切分, today 为当前日期
+    fn roll_if_needed(&mut self, len: usize, today: &str) -> io::Result<()> {
+        if !self.at_line_start || self.written == 0 {
+            self.date = today.to_string();
+            return Ok(());
+        }
+        let too_large = self.max_bytes > 0 && self.written + len as u64 > self.max_bytes;
+        let new_day = self.daily && self.date != today;
+        if too_large || new_day {
+            self.roll()?;
+        }
+        self.date = today.to_string();
+        Ok(())
+    }
+
+    fn file(&mut self) -> io::Result<&mut File> {
+        if self.file.is_none() {
+            self.file = Some(open_append(&self.path)?);
+        }
+        Ok(self.file.as_mut().unwrap())
+    }
+
+    fn roll(&mut self) -> io::Result<()> {
+        if let Some(mut file) = self.file.take() {
+            file.flush()?;
+        }
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let oldest = self.rolled_path(self.keep);
+            if oldest.exists() {
+                fs::remove_file(&oldest)?;
+            }
+            for index in (1..self.keep).rev() {
+                let from = self.rolled_path(index);
+                if from.exists() {
+                    fs::rename(&from, self.rolled_path(index + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rolled_path(1))?;
+        }
+        self.written = 0;
+        Ok(())
+    }
+
+    fn write_at(&mut self, buf: &[u8], today: &str) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.roll_if_needed(buf.len(), today)?;
+        let written = self.file()?.write(buf)?;
+        self.written += written as u64;
+        self.at_line_start = buf[written - 1] == b'\n';
+        Ok(written)
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_at(buf, &today())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "evm_ink_rs_rolling_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn rolls_when_exceeding_max_bytes() {
+        let dir = temp_dir("size");
+        let path = dir.join("evm_ink.log");
+        let mut file = RollingFile::new(&path, 10, false, 2).unwrap();
+        file.write_at(b"12345678\n", "2024-01-01").unwrap();
+        // 还没超过阈值 不切分
+        assert!(!dir.join("evm_ink.log.1").exists());
+        file.write_at(b"abc\n", "2024-01-01").unwrap();
+        assert_eq!(fs::read_to_string(dir.join("evm_ink.log.1")).unwrap(), "12345678\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "abc\n");
+
+        file.write_at(b"defghijk\n", "2024-01-01").unwrap();
+        file.write_at(b"lmn\n", "2024-01-01").unwrap();
+        // 只保留最近 2 个旧文件
+        assert_eq!(fs::read_to_string(dir.join("evm_ink.log.2")).unwrap(), "abc\n");
+        assert_eq!(fs::read_to_string(dir.join("evm_ink.log.1")).unwrap(), "defghijk\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "lmn\n");
+        file.write_at(b"opqrstuvw\n", "2024-01-01").unwrap();
+        assert!(!dir.join("evm_ink.log.3").exists());
+        assert_eq!(fs::read_to_string(dir.join("evm_ink.log.2")).unwrap(), "defghijk\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn never_splits_a_line() {
+        let dir = temp_dir("line");
+        let path = dir.join("evm_ink.log");
+        let mut file = RollingFile::new(&path, 4, false, 1).unwrap();
+        file.write_at(b"[info] ", "2024-01-01").unwrap();
+        file.write_at(b"hello\n", "2024-01-01").unwrap();
+        assert!(!dir.join("evm_ink.log.1").exists());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[info] hello\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rolls_on_new_day() {
+        let dir = temp_dir("daily");
+        let path = dir.join("evm_ink.log");
+        let mut file = RollingFile::new(&path, 0, true, 3).unwrap();
+        file.write_at(b"day1\n", "2024-01-01").unwrap();
+        file.write_at(b"day1 again\n", "2024-01-01").unwrap();
+        assert!(!dir.join("evm_ink.log.1").exists());
+        file.write_at(b"day2\n", "2024-01-02").unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join("evm_ink.log.1")).unwrap(),
+            "day1\nday1 again\n"
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "day2\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1510 @@
+use std::cmp::min;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use ethers::prelude::{Address, Http, LocalWallet, Middleware, Provider, Signer, Ws, H256, U256};
+use ethers::utils::{hash_message, hex, keccak256};
+use futures::StreamExt;
+use log::warn;
+use rand::Rng;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::{watch, Mutex as AsyncMutex, Semaphore};
+use url::Url;
+
+use crate::{Config, InkError};
+
+/// 日志里需要脱敏的 header 关键字
+const SENSITIVE_HEADER_KEYWORDS: [&str; 6] =
+    ["authorization", "cookie", "key", "secret", "token", "password"];
+
+pub fn is_sensitive_header(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SENSITIVE_HEADER_KEYWORDS
+        .iter()
+        .any(|keyword| name.contains(keyword))
+}
+
+/// 敏感 header 只显示前 4 个字符
+pub fn mask_header_value(name: &str, value: &str) -> String {
+    if !is_sensitive_header(name) {
+        return value.to_string();
+    }
+    let visible: String = value.chars().take(4).collect();
+    format!("{}****", visible)
+}
+
+pub fn build_headers(headers: &BTreeMap<String, String>) -> Result<HeaderMap, InkError> {
+    let mut header_map = HeaderMap::new();
+    for (name, value) in headers {
+        let header_name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+            InkError::Config(format!("rpc_headers 中的 header 名不合法: {}: {}", name, e))
+        })?;
+        let mut header_value = HeaderValue::from_str(value).map_err(|e| {
+            InkError::Config(format!("rpc_headers 中 {} 的值不合法: {}", name, e))
+        })?;
+        header_value.set_sensitive(is_sensitive_header(name));
+        header_map.insert(header_name, header_value);
+    }
+    Ok(header_map)
+}
+
+/// 所有 RPC 请求共用的 reqwest client, 带上自定义 header 和底层超时
+pub fn build_http_client(config: &Config) -> Result<reqwest::Client, InkError> {
+    reqwest::Client::builder()
+        .timeout(config.rpc_timeout())
+        .default_headers(build_headers(&config.rpc_headers)?)
+        .build()
+        .map_err(|e| InkError::Config(format!("创建 http client 失败: {}", e)))
+}
+
+pub fn parse_rpc_url(rpc_url: &str) -> Result<Url, InkError> {
+    Url::parse(rpc_url).map_err(|e| InkError::Config(format!("rpc_url 不合法: {}", e)))
+}
+
+/// 构造 Http provider, reqwest 底层也设置超时作为双保险
+///
+/// 配置了多个 rpc_url 时 provider 使用第一个
+pub fn build_provider(config: &Config) -> Result<Provider<Http>, InkError> {
+    let url = parse_rpc_url(config.rpc_urls()[0])?;
+    Ok(Provider::new(Http::new_with_client(
+        url,
+        build_http_client(config)?,
+    )))
+}
+
+/// 给 RPC 请求加上超时, 超时视为可重试错误 最多重试 retries 次, 其它错误直接返回
+pub async fn retry_on_timeout<T, E, F, Fut>(
+    timeout: Duration,
+    retries: u32,
+    what: &str,
+    mut f: F,
+) -> Result<T, InkError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: std::fmt::Display,
+{
+    let mut attempt = 0;
+    loop {
+        match tokio::time::timeout(timeout, f()).await {
+            Ok(result) => return result.map_err(|e| InkError::Rpc(format!("{}: {}", what, e))),
+            Err(_) if attempt < retries => {
+                attempt += 1;
+                warn!("{} 超时 {:?}, 第 {} 次重试", what, timeout, attempt);
+            }
+            Err(_) => {
+                return Err(InkError::Timeout(format!(
+                    "{} 超时 {:?}, 已重试 {} 次",
+                    what, timeout, retries
+                )));
+            }
+        }
+    }
+}
+
+/// 发送失败的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendErrorKind {
+    /// 请求没有到达节点或没有拿到响应, 如连接被拒绝、DNS 解析失败、超时、网关 5xx
+    Transport,
+    /// 节点处理了请求并返回了错误, 重发同样的交易结果不会变
+    Node,
+}
+
+/// 区分传输层错误和节点返回的错误, 只有传输层错误值得整批重试
+pub fn classify_send_error(error: &InkError) -> SendErrorKind {
+    const PATTERNS: [&str; 12] = [
+        "请求失败",
+        "读取 batch 响应失败",
+        "connection refused",
+        "connection reset",
+        "connection closed",
+        "broken pipe",
+        "dns error",
+        "error trying to connect",
+        "timed out",
+        "http 502",
+        "http 503",
+        "http 504",
+    ];
+    match error {
+        InkError::Timeout(_) => SendErrorKind::Transport,
+        InkError::Rpc(message) => {
+            let message = message.to_lowercase();
+            if PATTERNS.iter().any(|pattern| message.contains(pattern)) {
+                SendErrorKind::Transport
+            } else {
+                SendErrorKind::Node
+            }
+        }
+        _ => SendErrorKind::Node,
+    }
+}
+
+/// 遇到传输层错误时等待 backoff 后重试, 每次等待时间翻倍, 最多重试 retries 次
+///
+/// 节点返回的错误直接返回, 不做无谓的重试
+pub async fn retry_on_transport<T, F, Fut>(
+    retries: u32,
+    backoff: Duration,
+    what: &str,
+    mut f: F,
+) -> Result<T, InkError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, InkError>>,
+{
+    let mut attempt = 0;
+    let mut delay = backoff;
+    loop {
+        match f().await {
+            Err(e) if attempt < retries && classify_send_error(&e) == SendErrorKind::Transport => {
+                attempt += 1;
+                warn!("{} 连接失败: {}, {:?} 后第 {} 次重试", what, e, delay, attempt);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// 节点返回的错误是否是 batch 条数或请求体积超出限制
+pub fn is_batch_too_large(error: &str) -> bool {
+    const PATTERNS: [&str; 6] = [
+        "batch too large",
+        "batch size too large",
+        "request entity too large",
+        "payload too large",
+        "http 413",
+        "exceeds the batch limit",
+    ];
+    let error = error.to_lowercase();
+    PATTERNS.iter().any(|pattern| error.contains(pattern))
+}
+
+/// 每次最多发送 limit 笔, 节点提示 batch 过大时把 limit 减半重试
+///
+/// 减半后的 limit 会写回, 后续批次直接沿用
+pub async fn send_adaptive<F, Fut>(
+    raw_txs: &[String],
+    limit: &mut usize,
+    mut send: F,
+) -> Result<Vec<Result<H256, String>>, InkError>
+where
+    F: FnMut(Vec<String>) -> Fut,
+    Fut: Future<Output = Result<Vec<Result<H256, String>>, InkError>>,
+{
+    let mut results = Vec::with_capacity(raw_txs.len());
+    let mut start = 0;
+    while start < raw_txs.len() {
+        let end = min(start + (*limit).max(1), raw_txs.len());
+        match send(raw_txs[start..end].to_vec()).await {
+            Ok(responses) => {
+                results.extend(responses);
+                start = end;
+            }
+            Err(e) if end - start > 1 && is_batch_too_large(&e.to_string()) => {
+                *limit = (end - start) / 2;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(results)
+}
+
+/// 发送一批交易, 节点提示请求过大时把当前批次一分为二分别发送, 直到成功或只剩单笔
+///
+/// 单笔仍然过大时只把这一笔记为失败, 不影响其它交易
+pub async fn send_with_split<F, Fut>(
+    raw_txs: &[String],
+    mut send: F,
+) -> Result<Vec<Result<H256, String>>, InkError>
+where
+    F: FnMut(Vec<String>) -> Fut,
+    Fut: Future<Output = Result<Vec<Result<H256, String>>, InkError>>,
+{
+    let mut results: Vec<Result<H256, String>> =
+        vec![Err("节点没有返回响应".to_string()); raw_txs.len()];
+    let mut pending = VecDeque::new();
+    if !raw_txs.is_empty() {
+        pending.push_back((0, raw_txs.len()));
+    }
+    while let Some((start, end)) = pending.pop_front() {
+        match send(raw_txs[start..end].to_vec()).await {
+            Ok(responses) => {
+                for (result, response) in results[start..end].iter_mut().zip(responses) {
+                    *result = response;
+                }
+            }
+            Err(e) if is_batch_too_large(&e.to_string()) => {
+                if end - start == 1 {
+                    results[start] = Err(e.to_string());
+                } else {
+                    // 先发前一半 保持 nonce 顺序
+                    let middle = start + (end - start) / 2;
+                    pending.push_front((middle, end));
+                    pending.push_front((start, middle));
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(results)
+}
+
+#[derive(Deserialize, Debug)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct JsonRpcResponse<T> {
+    id: Option<u64>,
+    result: Option<T>,
+    error: Option<JsonRpcError>,
+}
+
+impl<T> JsonRpcResponse<T> {
+    /// 节点返回的错误转成 (code, message) 文本
+    fn into_result(self) -> Result<T, String> {
+        match (self.result, self.error) {
+            (_, Some(error)) => Err(format!("(code: {}, message: {})", error.code, error.message)),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err("节点返回了空结果".to_string()),
+        }
+    }
+}
+
+/// 一个地址 latest 和 pending 的 nonce
+#[derive(Debug, Clone, PartialEq)]
+pub struct NonceStatus {
+    pub address: Address,
+    pub latest: U256,
+    pub pending: U256,
+}
+
+impl NonceStatus {
+    /// pending 比 latest 多出的部分, 即还在 mempool 里积压的交易数
+    pub fn queued(&self) -> U256 {
+        self.pending.saturating_sub(self.latest)
+    }
+}
+
+/// 每个地址两个 eth_getTransactionCount 请求, id 为 2i 查 latest、2i+1 查 pending
+pub fn nonce_status_requests(addresses: &[Address]) -> Vec<serde_json::Value> {
+    addresses
+        .iter()
+        .flat_map(|address| ["latest", "pending"].map(|block| (address, block)))
+        .enumerate()
+        .map(|(id, (address, block))| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "eth_getTransactionCount",
+                "params": [address, block],
+            })
+        })
+        .collect()
+}
+
+/// 解析 nonce_status_requests 的 batch 响应, 任何一个地址查询失败都返回 Err
+pub fn parse_nonce_status(body: &str, addresses: &[Address]) -> Result<Vec<NonceStatus>, InkError> {
+    let mut results = parse_batch_results::<U256>(body, addresses.len() * 2)?.into_iter();
+    let mut next = |address: &Address, block: &str| {
+        results.next().unwrap().map_err(|e| {
+            InkError::Rpc(format!("查询 {:?} 的 {} nonce 失败: {}", address, block, e))
+        })
+    };
+    addresses
+        .iter()
+        .map(|address| {
+            Ok(NonceStatus {
+                address: *address,
+                latest: next(address, "latest")?,
+                pending: next(address, "pending")?,
+            })
+        })
+        .collect()
+}
+
+/// 每个地址一个 eth_getBalance 请求, id 为地址的下标
+pub fn balance_requests(addresses: &[Address]) -> Vec<serde_json::Value> {
+    addresses
+        .iter()
+        .enumerate()
+        .map(|(id, address)| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "eth_getBalance",
+                "params": [address, "latest"],
+            })
+        })
+        .collect()
+}
+
+/// 解析 balance_requests 的 batch 响应, 任何一个地址查询失败都返回 Err
+pub fn parse_balances(body: &str, addresses: &[Address]) -> Result<Vec<U256>, InkError> {
+    parse_batch_results::<U256>(body, addresses.len())?
+        .into_iter()
+        .zip(addresses)
+        .map(|(result, address)| {
+            result.map_err(|e| InkError::Rpc(format!("查询 {:?} 的余额失败: {}", address, e)))
+        })
+        .collect()
+}
+
+/// 直接用 reqwest 发送 JSON-RPC batch, 和 provider 共用同一个带 header 的 client
+#[derive(Debug, Clone)]
+pub struct BatchSender {
+    client: reqwest::Client,
+    url: Url,
+    /// 节点不支持 JSON-RPC batch 时为 false, 交易改为逐笔发送
+    batch: bool,
+}
+
+impl BatchSender {
+    pub fn new(client: reqwest::Client, url: Url) -> Self {
+        BatchSender {
+            client,
+            url,
+            batch: true,
+        }
+    }
+
+    /// 不再使用 batch, 每笔交易单独发一个 eth_sendRawTransaction 请求
+    pub fn without_batch(mut self) -> Self {
+        self.batch = false;
+        self
+    }
+
+    pub fn supports_batch(&self) -> bool {
+        self.batch
+    }
+
+    /// 发一个两个元素的小 batch 探测节点是否支持 JSON-RPC batch
+    ///
+    /// 节点返回 HTTP 错误或响应不是完整的数组时为 false, 连接失败等传输层错误返回 Err
+    pub async fn health_check(&self) -> Result<bool, InkError> {
+        let body = serde_json::to_string(&batch_probe_requests())
+            .map_err(|e| InkError::Rpc(format!("序列化 batch 探测请求失败: {}", e)))?;
+        match self.post("batch 探测", body, HeaderMap::new()).await {
+            Ok(text) => Ok(is_batch_probe_ok(&text)),
+            Err(e) if classify_send_error(&e) == SendErrorKind::Transport => Err(e),
+            Err(_) => Ok(false),
+        }
+    }
+
+    /// 一次 HTTP 请求发送一批 eth_sendRawTransaction, 每笔的结果按 raw_txs 的顺序返回
+    ///
+    /// 不支持 batch 时按顺序逐笔发送, 结果的顺序相同, 某一笔请求失败只记在这一笔上
+    pub async fn send_raw_transactions(
+        &self,
+        raw_txs: &[String],
+    ) -> Result<Vec<Result<H256, String>>, InkError> {
+        if !self.batch {
+            return self.send_one_by_one(raw_txs).await;
+        }
+        let requests: Vec<serde_json::Value> = raw_txs
+            .iter()
+            .enumerate()
+            .map(|(id, raw_tx)| {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": "eth_sendRawTransaction",
+                    "params": [raw_tx],
+                })
+            })
+            .collect();
+        let body = serde_json::to_string(&requests)
+            .map_err(|e| InkError::Send(format!("序列化 batch 请求失败: {}", e)))?;
+        let text = self.post("batch", body, HeaderMap::new()).await?;
+        parse_batch_response(&text, raw_txs.len())
+    }
+
+    async fn send_one_by_one(
+        &self,
+        raw_txs: &[String],
+    ) -> Result<Vec<Result<H256, String>>, InkError> {
+        let mut results = Vec::with_capacity(raw_txs.len());
+        let mut failed = Vec::new();
+        for raw_tx in raw_txs {
+            let body = json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "method": "eth_sendRawTransaction",
+                "params": [raw_tx],
+            })
+            .to_string();
+            let response = self
+                .post("eth_sendRawTransaction", body, HeaderMap::new())
+                .await
+                .and_then(|text| parse_single_response(&text));
+            match response {
+                Ok(result) => results.push(result),
+                Err(e) => {
+                    results.push(Err(e.to_string()));
+                    failed.push(e);
+                }
+            }
+        }
+        // 每一笔都没有拿到响应时整体返回错误, 交给外层按传输层错误重试
+        if !raw_txs.is_empty() && failed.len() == raw_txs.len() {
+            return Err(failed.swap_remove(0));
+        }
+        Ok(results)
+    }
+
+    /// 一次 batch 请求查完所有地址 latest 和 pending 的 nonce
+    pub async fn nonce_statuses(
+        &self,
+        addresses: &[Address],
+    ) -> Result<Vec<NonceStatus>, InkError> {
+        if addresses.is_empty() {
+            return Ok(Vec::new());
+        }
+        let body = serde_json::to_string(&nonce_status_requests(addresses))
+            .map_err(|e| InkError::Rpc(format!("序列化 nonce 请求失败: {}", e)))?;
+        let text = self.post("nonce batch", body, HeaderMap::new()).await?;
+        parse_nonce_status(&text, addresses)
+    }
+
+    /// 用一个 batch 请求查询所有地址的余额, 结果按 addresses 的顺序返回
+    pub async fn balances(&self, addresses: &[Address]) -> Result<Vec<U256>, InkError> {
+        if addresses.is_empty() {
+            return Ok(Vec::new());
+        }
+        let body = serde_json::to_string(&balance_requests(addresses))
+            .map_err(|e| InkError::Rpc(format!("序列化余额请求失败: {}", e)))?;
+        let text = self.post("balance batch", body, HeaderMap::new()).await?;
+        parse_balances(&text, addresses)
+    }
+
+    /// 发送 eth_sendBundle / mev_sendBundle, 返回 bundleHash
+    ///
+    /// 请求体用 signer 签名放进 X-Flashbots-Signature header
+    pub async fn send_bundle(
+        &self,
+        method: &str,
+        raw_txs: &[String],
+        block_number: u64,
+        signer: &LocalWallet,
+    ) -> Result<String, InkError> {
+        let body = bundle_request_body(method, raw_txs, block_number).to_string();
+        let mut headers = HeaderMap::new();
+        let signature = HeaderValue::from_str(&flashbots_signature(signer, &body)?)
+            .map_err(|e| InkError::Send(format!("bundle 签名 header 不合法: {}", e)))?;
+        headers.insert("X-Flashbots-Signature", signature);
+        let text = self.post("bundle", body, headers).await?;
+        parse_bundle_response(&text)
+    }
+
+    async fn post(&self, what: &str, body: String, headers: HeaderMap) -> Result<String, InkError> {
+        let response = self
+            .client
+            .post(self.url.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .headers(headers)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| InkError::Rpc(format!("{} 请求失败: {}", what, e)))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| InkError::Rpc(format!("读取 {} 响应失败: {}", what, e)))?;
+        if !status.is_success() {
+            return Err(InkError::Rpc(format!(
+                "{} 请求返回 HTTP {}: {}",
+                what, status, text
+            )));
+        }
+        Ok(text)
+    }
+}
+
+/// 构造 bundle 请求, eth_sendBundle 使用 flashbots 格式, mev_sendBundle 使用 MEV-Share v0.1 格式
+pub fn bundle_request_body(method: &str, raw_txs: &[String], block_number: u64) -> serde_json::Value {
+    let block = format!("0x{:x}", block_number);
+    let params = if method == "mev_sendBundle" {
+        let body: Vec<serde_json::Value> = raw_txs
+            .iter()
+            .map(|raw_tx| json!({ "tx": raw_tx, "canRevert": false }))
+            .collect();
+        json!({
+            "version": "v0.1",
+            "inclusion": { "block": block, "maxBlock": block },
+            "body": body,
+        })
+    } else {
+        json!({ "txs": raw_txs, "blockNumber": block })
+    };
+    json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": method,
+        "params": [params],
+    })
+}
+
+/// X-Flashbots-Signature: `地址:签名`, 签名内容为请求体 keccak256 的十六进制字符串(EIP-191)
+pub fn flashbots_signature(signer: &LocalWallet, body: &str) -> Result<String, InkError> {
+    let message = format!("0x{}", hex::encode(keccak256(body.as_bytes())));
+    let signature = signer
+        .sign_hash(hash_message(message))
+        .map_err(|e| InkError::Wallet(e.to_string()))?;
+    Ok(format!("{:?}:0x{}", signer.address(), signature))
+}
+
+fn parse_bundle_response(body: &str) -> Result<String, InkError> {
+    let value: serde_json::Value = serde_json::from_str(body)
+        .map_err(|e| InkError::Rpc(format!("bundle 响应不是合法的 JSON: {}: {}", e, body)))?;
+    if let Some(error) = value.get("error").filter(|error| !error.is_null()) {
+        return Err(InkError::Send(format!("bundle 被拒绝: {}", error)));
+    }
+    match value.get("result").and_then(|result| result.get("bundleHash")) {
+        Some(serde_json::Value::String(bundle_hash)) => Ok(bundle_hash.clone()),
+        _ => Err(InkError::Rpc(format!("bundle 响应格式不正确: {}", body))),
+    }
+}
+
+/// 每个 RPC 保留最近多少次请求的延迟
+const LATENCY_WINDOW: usize = 10;
+/// 请求超时的 RPC 暂时不再使用的时长
+const EVICT_DURATION: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default, Clone)]
+struct RpcStats {
+    latencies: VecDeque<Duration>,
+    evicted_until: Option<Instant>,
+}
+
+impl RpcStats {
+    fn average_latency(&self) -> Option<Duration> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        Some(self.latencies.iter().sum::<Duration>() / self.latencies.len() as u32)
+    }
+
+    fn is_evicted(&self, now: Instant) -> bool {
+        self.evicted_until.is_some_and(|until| until > now)
+    }
+}
+
+/// 多个 RPC 发送 batch, 按最近请求延迟的倒数加权随机选择, 超时的 RPC 暂时剔除
+#[derive(Debug)]
+pub struct RpcPool {
+    senders: Vec<BatchSender>,
+    stats: Mutex<Vec<RpcStats>>,
+}
+
+impl RpcPool {
+    pub fn new(senders: Vec<BatchSender>) -> Self {
+        let stats = Mutex::new(vec![RpcStats::default(); senders.len()]);
+        RpcPool { senders, stats }
+    }
+
+    pub fn len(&self) -> usize {
+        self.senders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.senders.is_empty()
+    }
+
+    /// 第一个 RPC, 不需要负载均衡的请求(如 bundle)使用
+    pub fn primary(&self) -> &BatchSender {
+        &self.senders[0]
+    }
+
+    /// 选出下一个请求使用的 RPC 下标
+    ///
+    /// 还没有延迟数据的 RPC 按已知最快的权重参与选择, 全部被剔除时选最早恢复的那个
+    pub fn pick<R: Rng>(&self, rng: &mut R, now: Instant) -> usize {
+        let stats = self.stats.lock().unwrap();
+        let weight = |stat: &RpcStats| {
+            stat.average_latency()
+                .map(|latency| 1.0 / latency.as_secs_f64().max(1e-6))
+        };
+        let fastest = stats
+            .iter()
+            .filter_map(weight)
+            .max_by(f64::total_cmp)
+            .unwrap_or(1.0);
+        let weights: Vec<f64> = stats
+            .iter()
+            .map(|stat| {
+                if stat.is_evicted(now) {
+                    0.0
+                } else {
+                    weight(stat).unwrap_or(fastest)
+                }
+            })
+            .collect();
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return stats
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, stat)| stat.evicted_until)
+                .map_or(0, |(index, _)| index);
+        }
+        let mut target = rng.gen_range(0.0..total);
+        for (index, weight) in weights.iter().enumerate() {
+            if target < *weight {
+                return index;
+            }
+            target -= weight;
+        }
+        weights.iter().rposition(|weight| *weight > 0.0).unwrap_or(0)
+    }
+
+    /// 记录一次成功请求的延迟, 只保留最近 LATENCY_WINDOW 次
+    pub fn record_latency(&self, index: usize, latency: Duration) {
+        let mut stats = self.stats.lock().unwrap();
+        let stat = &mut stats[index];
+        if stat.latencies.len() == LATENCY_WINDOW {
+            stat.latencies.pop_front();
+        }
+        stat.latencies.push_back(latency);
+        stat.evicted_until = None;
+    }
+
+    /// 请求超时 暂时剔除这个 RPC
+    pub fn record_timeout(&self, index: usize, now: Instant) {
+        let mut stats = self.stats.lock().unwrap();
+        stats[index].evicted_until = Some(now + EVICT_DURATION);
+    }
+
+    pub async fn send_raw_transactions(
+        &self,
+        raw_txs: &[String],
+    ) -> Result<Vec<Result<H256, String>>, InkError> {
+        let index = self.pick(&mut rand::thread_rng(), Instant::now());
+        // 外层超时会直接丢弃这个 future, 由 guard 在 drop 时记为超时
+        let mut guard = InflightGuard {
+            pool: self,
+            index,
+            started: Instant::now(),
+            finished: false,
+        };
+        let result = self.senders[index].send_raw_transactions(raw_txs).await;
+        guard.finished = true;
+        if result.is_ok() {
+            self.record_latency(index, guard.started.elapsed());
+        }
+        result
+    }
+}
+
+/// 发送一批已签名的交易, mint 只通过它发送 batch, 单测时可以换成返回预设结果的 mock
+#[async_trait]
+pub trait TxSender: Send + Sync {
+    /// 每笔的结果按 raw_txs 的顺序返回, 整批请求失败时返回 Err
+    async fn send_batch(&self, raw_txs: &[String]) -> Result<Vec<Result<H256, String>>, InkError>;
+}
+
+#[async_trait]
+impl TxSender for RpcPool {
+    async fn send_batch(&self, raw_txs: &[String]) -> Result<Vec<Result<H256, String>>, InkError> {
+        self.send_raw_transactions(raw_txs).await
+    }
+}
+
+/// 限制在途请求数的 TxSender, 多个任务共用同一个 Semaphore, 超过容量的请求排队等待
+pub struct LimitedSender {
+    inner: Arc<dyn TxSender>,
+    semaphore: Arc<Semaphore>,
+}
+
+impl LimitedSender {
+    pub fn new(inner: Arc<dyn TxSender>, semaphore: Arc<Semaphore>) -> Self {
+        LimitedSender { inner, semaphore }
+    }
+}
+
+#[async_trait]
+impl TxSender for LimitedSender {
+    async fn send_batch(&self, raw_txs: &[String]) -> Result<Vec<Result<H256, String>>, InkError> {
+        let _permit = self
+            .semaphore
+            .acquire()
+            .await
+            .map_err(|e| InkError::Send(format!("在途请求限制已关闭: {}", e)))?;
+        self.inner.send_batch(raw_txs).await
+    }
+}
+
+struct InflightGuard<'a> {
+    pool: &'a RpcPool,
+    index: usize,
+    started: Instant,
+    finished: bool,
+}
+
+impl Drop for InflightGuard<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            warn!("RPC {} 请求超时, 暂时不再使用", self.pool.senders[self.index].url);
+            self.pool.record_timeout(self.index, Instant::now());
+        }
+    }
+}
+
+/// 按 rpc_url 缓存 chain_id, 多个钱包/上下文共用时相同的 RPC 只查询一次
+///
+/// 查询期间持有锁, 并发请求同一个 URL 时后来的会等第一个查完直接用缓存
+#[derive(Debug, Default)]
+pub struct ChainIdCache {
+    ids: AsyncMutex<HashMap<String, U256>>,
+}
+
+impl ChainIdCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 返回 rpc_url 的 chain_id, 没有缓存时调用 fetch 查询, 查询失败不缓存
+    pub async fn get_or_fetch<F, Fut>(&self, rpc_url: &str, fetch: F) -> Result<U256, InkError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<U256, InkError>>,
+    {
+        let mut ids = self.ids.lock().await;
+        if let Some(chain_id) = ids.get(rpc_url) {
+            return Ok(*chain_id);
+        }
+        let chain_id = fetch().await?;
+        ids.insert(rpc_url.to_string(), chain_id);
+        Ok(chain_id)
+    }
+}
+
+/// newHeads 订阅断开后重连的间隔
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+/// 连续多少次连接失败后放弃订阅
+const MAX_RECONNECTS: u32 = 5;
+
+/// 后台订阅 ws 的 newHeads, 新区块号通过 watch channel 通知, 多个新块只保留最新一个
+///
+/// 断开后自动重连, 连续失败 MAX_RECONNECTS 次后放弃, 这时 channel 关闭 调用方应回退到 interval 模式
+pub fn spawn_new_block_watcher(ws_url: String) -> watch::Receiver<u64> {
+    let (tx, rx) = watch::channel(0);
+    tokio::spawn(async move {
+        let mut failures = 0;
+        while !tx.is_closed() {
+            match subscribe_new_blocks(&ws_url, &tx).await {
+                Ok(received) => {
+                    if received {
+                        failures = 0;
+                    }
+                    warn!("newHeads 订阅断开, {:?} 后重连", RECONNECT_DELAY);
+                }
+                Err(e) => {
+                    failures += 1;
+                    warn!("newHeads 订阅失败({}/{}): {}", failures, MAX_RECONNECTS, e);
+                    if failures >= MAX_RECONNECTS {
+                        warn!("newHeads 订阅多次失败 回退到 interval 模式");
+                        return;
+                    }
+                }
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+    rx
+}
+
+/// 订阅直到断开, 返回期间是否收到过新区块
+async fn subscribe_new_blocks(ws_url: &str, tx: &watch::Sender<u64>) -> Result<bool, InkError> {
+    let provider = Provider::<Ws>::connect(ws_url)
+        .await
+        .map_err(|e| InkError::Rpc(format!("连接 ws 失败: {}", e)))?;
+    let mut stream = provider
+        .subscribe_blocks()
+        .await
+        .map_err(|e| InkError::Rpc(format!("订阅 newHeads 失败: {}", e)))?;
+    let mut received = false;
+    while let Some(block) = stream.next().await {
+        received = true;
+        let number = block.number.map_or(0, |number| number.as_u64());
+        if tx.send(number).is_err() {
+            break;
+        }
+    }
+    Ok(received)
+}
+
+/// 按 id 把 batch 响应对应回请求的顺序, 没有响应的请求记为失败
+///
+/// 整个 batch 被拒绝时节点通常只返回一个错误对象, 这时返回 Err
+pub fn parse_batch_response(
+    body: &str,
+    len: usize,
+) -> Result<Vec<Result<H256, String>>, InkError> {
+    parse_batch_results(body, len)
+}
+
+/// batch 探测请求: 两个 eth_chainId, id 为 0 和 1
+pub fn batch_probe_requests() -> Vec<serde_json::Value> {
+    (0..2)
+        .map(|id| json!({ "jsonrpc": "2.0", "id": id, "method": "eth_chainId", "params": [] }))
+        .collect()
+}
+
+/// batch 探测的响应是否是数组且两个请求都有结果
+pub fn is_batch_probe_ok(body: &str) -> bool {
+    parse_batch_results::<serde_json::Value>(body, 2)
+        .map(|results| results.iter().all(Result::is_ok))
+        .unwrap_or(false)
+}
+
+/// 解析单个(非 batch)请求的响应, 节点返回的错误放在内层的 Err 里
+pub fn parse_single_response(body: &str) -> Result<Result<H256, String>, InkError> {
+    let response: JsonRpcResponse<H256> = serde_json::from_str(body)
+        .map_err(|e| InkError::Rpc(format!("响应格式不正确: {}: {}", e, body)))?;
+    Ok(response.into_result())
+}
+
+fn parse_batch_results<T: DeserializeOwned + Clone>(
+    body: &str,
+    len: usize,
+) -> Result<Vec<Result<T, String>>, InkError> {
+    let value: serde_json::Value = serde_json::from_str(body)
+        .map_err(|e| InkError::Rpc(format!("batch 响应不是合法的 JSON: {}: {}", e, body)))?;
+    if !value.is_array() {
+        return Err(InkError::Rpc(format!("batch 请求被拒绝: {}", body)));
+    }
+    let responses: Vec<JsonRpcResponse<T>> = serde_json::from_value(value)
+        .map_err(|e| InkError::Rpc(format!("batch 响应格式不正确: {}: {}", e, body)))?;
+    let mut results: Vec<Result<T, String>> = vec![Err("节点没有返回响应".to_string()); len];
+    for response in responses {
+        let index = match response.id {
+            Some(id) if (id as usize) < len => id as usize,
+            _ => continue,
+        };
+        results[index] = response.into_result();
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_sensitive_headers() {
+        assert_eq!(
+            mask_header_value("Authorization", "Bearer abcdef"),
+            "Bear****"
+        );
+        assert_eq!(mask_header_value("x-api-key", "12345678"), "1234****");
+        assert_eq!(mask_header_value("X-Client", "evm_ink"), "evm_ink");
+    }
+
+    #[test]
+    fn build_headers_from_map() {
+        let headers: BTreeMap<String, String> = serde_json::from_str(
+            r#"{"Authorization":"Bearer abc","X-Client":"evm_ink"}"#,
+        )
+        .unwrap();
+        let header_map = build_headers(&headers).unwrap();
+        assert_eq!(header_map["authorization"], "Bearer abc");
+        assert!(header_map["authorization"].is_sensitive());
+        assert!(!header_map["x-client"].is_sensitive());
+
+        let invalid: BTreeMap<String, String> =
+            serde_json::from_str(r#"{"bad header":"x"}"#).unwrap();
+        assert!(build_headers(&invalid).is_err());
+    }
+
+    #[test]
+    fn parse_batch_response_results() {
+        let hash = format!("0x{}", "11".repeat(32));
+        let body = format!(
+            r#"[{{"jsonrpc":"2.0","id":0,"result":"{}"}},{{"jsonrpc":"2.0","id":1,"error":{{"code":-32000,"message":"nonce too low"}}}}]"#,
+            hash
+        );
+        let results = parse_batch_response(&body, 3).unwrap();
+        assert_eq!(results[0], Ok(hash.parse().unwrap()));
+        assert_eq!(
+            results[1],
+            Err("(code: -32000, message: nonce too low)".to_string())
+        );
+        assert!(results[2].is_err());
+    }
+
+    #[test]
+    fn nonce_status_batch() {
+        let a = Address::from_low_u64_be(1);
+        let b = Address::from_low_u64_be(2);
+        let requests = nonce_status_requests(&[a, b]);
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[0]["method"], "eth_getTransactionCount");
+        assert_eq!(requests[0]["params"], json!([a, "latest"]));
+        assert_eq!(requests[3]["id"], 3);
+        assert_eq!(requests[3]["params"], json!([b, "pending"]));
+
+        // 响应顺序打乱也按 id 对应
+        let body = r#"[{"jsonrpc":"2.0","id":3,"result":"0xa"},{"jsonrpc":"2.0","id":0,"result":"0x5"},{"jsonrpc":"2.0","id":1,"result":"0x8"},{"jsonrpc":"2.0","id":2,"result":"0xa"}]"#;
+        let statuses = parse_nonce_status(body, &[a, b]).unwrap();
+        assert_eq!(
+            statuses[0],
+            NonceStatus {
+                address: a,
+                latest: U256::from(5),
+                pending: U256::from(8),
+            }
+        );
+        assert_eq!(statuses[0].queued(), U256::from(3));
+        assert_eq!(statuses[1].queued(), U256::zero());
+
+        let body = r#"[{"jsonrpc":"2.0","id":0,"result":"0x5"},{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"oops"}}]"#;
+        let error = parse_nonce_status(body, &[a]).unwrap_err().to_string();
+        assert!(error.contains("pending"), "{}", error);
+    }
+
+    #[test]
+    fn balance_batch() {
+        let a = Address::from_low_u64_be(1);
+        let b = Address::from_low_u64_be(2);
+        let requests = balance_requests(&[a, b]);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0]["method"], "eth_getBalance");
+        assert_eq!(requests[1]["id"], 1);
+        assert_eq!(requests[1]["params"], json!([b, "latest"]));
+
+        let body = r#"[{"jsonrpc":"2.0","id":1,"result":"0x0"},{"jsonrpc":"2.0","id":0,"result":"0xde0b6b3a7640000"}]"#;
+        assert_eq!(
+            parse_balances(body, &[a, b]).unwrap(),
+            vec![U256::exp10(18), U256::zero()]
+        );
+        let body = r#"[{"jsonrpc":"2.0","id":0,"result":"0x1"}]"#;
+        let error = parse_balances(body, &[a, b]).unwrap_err().to_string();
+        assert!(error.contains("余额失败"), "{}", error);
+    }
+
+    #[test]
+    fn parse_batch_response_out_of_order() {
+        let hash = |byte: u8| format!("0x{}", hex::encode([byte; 32]));
+        // 节点不保证 batch 响应的顺序, 按 id 对应回请求
+        let body = format!(
+            r#"[{{"jsonrpc":"2.0","id":2,"result":"{}"}},{{"jsonrpc":"2.0","id":0,"result":"{}"}},{{"jsonrpc":"2.0","id":1,"error":{{"code":-32000,"message":"already known"}}}}]"#,
+            hash(3),
+            hash(1)
+        );
+        let results = parse_batch_response(&body, 3).unwrap();
+        assert_eq!(results[0], Ok(H256::repeat_byte(1)));
+        assert_eq!(
+            results[1],
+            Err("(code: -32000, message: already known)".to_string())
+        );
+        assert_eq!(results[2], Ok(H256::repeat_byte(3)));
+    }
+
+    #[test]
+    fn parse_batch_response_rejected() {
+        let body = r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32600,"message":"batch too large"}}"#;
+        assert!(matches!(
+            parse_batch_response(body, 2),
+            Err(InkError::Rpc(_))
+        ));
+    }
+
+    #[test]
+    fn batch_too_large_errors() {
+        assert!(is_batch_too_large("batch 请求被拒绝: {\"message\":\"batch too large\"}"));
+        assert!(is_batch_too_large("batch 请求返回 HTTP 413 Payload Too Large: "));
+        assert!(is_batch_too_large("Request Entity Too Large"));
+        assert!(!is_batch_too_large("nonce too low"));
+    }
+
+    #[tokio::test]
+    async fn send_adaptive_halves_limit() {
+        let calls = std::cell::RefCell::new(Vec::new());
+        let raw_txs: Vec<String> = (0..5).map(|i| format!("0x{:02x}", i)).collect();
+        let mut limit = 4;
+        let results = send_adaptive(&raw_txs, &mut limit, |chunk| {
+            calls.borrow_mut().push(chunk.len());
+            async move {
+                // 节点每批最多接受 2 笔
+                if chunk.len() > 2 {
+                    return Err(InkError::Rpc("batch too large".to_string()));
+                }
+                Ok(chunk.iter().map(|_| Ok(H256::zero())).collect())
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(results.len(), 5);
+        assert_eq!(limit, 2);
+        // 首次 4 笔过大, 减半后按 2, 2, 1 发送
+        assert_eq!(*calls.borrow(), vec![4, 2, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn send_adaptive_returns_other_errors() {
+        let raw_txs = vec!["0x00".to_string(), "0x01".to_string()];
+        let mut limit = 2;
+        let result = send_adaptive(&raw_txs, &mut limit, |_| async {
+            Err(InkError::Rpc("connection refused".to_string()))
+        })
+        .await;
+        assert!(matches!(result, Err(InkError::Rpc(_))));
+        assert_eq!(limit, 2);
+    }
+
+    #[tokio::test]
+    async fn send_with_split_bisects() {
+        let calls = std::cell::RefCell::new(Vec::new());
+        let raw_txs: Vec<String> = (0..4).map(|i| format!("0x{:02x}", i)).collect();
+        let results = send_with_split(&raw_txs, |chunk| {
+            calls.borrow_mut().push(chunk.clone());
+            async move {
+                // 0x03 这一笔单独就超出限制, 包含它的请求都会被拒绝
+                if chunk.iter().any(|raw_tx| raw_tx == "0x03") {
+                    return Err(InkError::Rpc("request entity too large".to_string()));
+                }
+                Ok(chunk.iter().map(|_| Ok(H256::zero())).collect())
+            }
+        })
+        .await
+        .unwrap();
+        let sizes: Vec<usize> = calls.borrow().iter().map(|chunk| chunk.len()).collect();
+        // 4 -> 2 + 2, 后一半 2 -> 1 + 1
+        assert_eq!(sizes, vec![4, 2, 2, 1, 1]);
+        assert_eq!(calls.borrow()[1], vec!["0x00".to_string(), "0x01".to_string()]);
+        assert!(results[..3].iter().all(|result| result.is_ok()));
+        assert!(results[3].is_err());
+    }
+
+    #[tokio::test]
+    async fn send_with_split_no_split_on_success() {
+        let calls = std::cell::Cell::new(0);
+        let raw_txs = vec!["0x00".to_string(), "0x01".to_string()];
+        let results = send_with_split(&raw_txs, |chunk| {
+            calls.set(calls.get() + 1);
+            async move { Ok(chunk.iter().map(|_| Ok(H256::zero())).collect()) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(calls.get(), 1);
+        assert_eq!(results.len(), 2);
+    }
+
+    fn test_pool(len: usize) -> RpcPool {
+        let senders = (0..len)
+            .map(|i| {
+                let url = Url::parse(&format!("http://127.0.0.1:{}", 8545 + i)).unwrap();
+                BatchSender::new(reqwest::Client::new(), url)
+            })
+            .collect();
+        RpcPool::new(senders)
+    }
+
+    fn pick_counts(pool: &RpcPool, now: Instant) -> Vec<usize> {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let mut counts = vec![0; pool.len()];
+        for _ in 0..10000 {
+            counts[pool.pick(&mut rng, now)] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn rpc_pool_weights_by_latency() {
+        let pool = test_pool(2);
+        pool.record_latency(0, Duration::from_millis(10));
+        pool.record_latency(1, Duration::from_millis(30));
+        // 延迟倒数加权 快的应当占约 3/4
+        let counts = pick_counts(&pool, Instant::now());
+        assert!((7000..8000).contains(&counts[0]), "{:?}", counts);
+    }
+
+    #[test]
+    fn rpc_pool_latency_window() {
+        let pool = test_pool(2);
+        pool.record_latency(0, Duration::from_secs(10));
+        for _ in 0..LATENCY_WINDOW {
+            pool.record_latency(0, Duration::from_millis(10));
+        }
+        pool.record_latency(1, Duration::from_millis(10));
+        // 最早的慢请求已经滑出窗口 两个 RPC 权重相同
+        let average = pool.stats.lock().unwrap()[0].average_latency();
+        assert_eq!(average, Some(Duration::from_millis(10)));
+        let counts = pick_counts(&pool, Instant::now());
+        assert!((4500..5500).contains(&counts[0]), "{:?}", counts);
+    }
+
+    #[test]
+    fn rpc_pool_evicts_timeouts() {
+        let pool = test_pool(3);
+        let now = Instant::now();
+        pool.record_latency(0, Duration::from_millis(10));
+        pool.record_timeout(1, now);
+        let counts = pick_counts(&pool, now);
+        assert_eq!(counts[1], 0);
+        // 没有延迟数据的 RPC 按最快的权重参与选择
+        assert!(counts[2] > 4000, "{:?}", counts);
+        // 剔除到期后恢复使用
+        let counts = pick_counts(&pool, now + EVICT_DURATION + Duration::from_secs(1));
+        assert!(counts[1] > 0);
+
+        // 全部被剔除时选最早恢复的
+        pool.record_timeout(0, now + Duration::from_secs(2));
+        pool.record_timeout(2, now + Duration::from_secs(1));
+        assert_eq!(pick_counts(&pool, now + Duration::from_secs(3)), vec![0, 10000, 0]);
+    }
+
+    #[test]
+    fn bundle_request_formats() {
+        let raw_txs = vec!["0x01".to_string(), "0x02".to_string()];
+        assert_eq!(
+            bundle_request_body("eth_sendBundle", &raw_txs, 100),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "eth_sendBundle",
+                "params": [{ "txs": ["0x01", "0x02"], "blockNumber": "0x64" }],
+            })
+        );
+        assert_eq!(
+            bundle_request_body("mev_sendBundle", &raw_txs, 100),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "mev_sendBundle",
+                "params": [{
+                    "version": "v0.1",
+                    "inclusion": { "block": "0x64", "maxBlock": "0x64" },
+                    "body": [
+                        { "tx": "0x01", "canRevert": false },
+                        { "tx": "0x02", "canRevert": false },
+                    ],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn flashbots_signature_recovers_signer() {
+        let signer: LocalWallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap();
+        let body = r#"{"jsonrpc":"2.0","id":1}"#;
+        let header = flashbots_signature(&signer, body).unwrap();
+        let (address, signature) = header.split_once(':').unwrap();
+        assert_eq!(address, format!("{:?}", signer.address()));
+        let signature: ethers::types::Signature = signature.parse().unwrap();
+        let message = format!("0x{}", hex::encode(keccak256(body.as_bytes())));
+        assert_eq!(signature.recover(message).unwrap(), signer.address());
+    }
+
+    #[test]
+    fn bundle_response_results() {
+        let ok = r#"{"jsonrpc":"2.0","id":1,"result":{"bundleHash":"0xabc"}}"#;
+        assert_eq!(parse_bundle_response(ok).unwrap(), "0xabc");
+        let rejected = r#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000,"message":"bundle too old"}}"#;
+        assert!(matches!(parse_bundle_response(rejected), Err(InkError::Send(_))));
+    }
+
+    #[tokio::test]
+    async fn retry_on_timeout_retries() {
+        let calls = std::cell::Cell::new(0);
+        let result = retry_on_timeout(Duration::from_millis(20), 3, "test", || {
+            calls.set(calls.get() + 1);
+            let attempt = calls.get();
+            async move {
+                // 前两次超时 第三次成功
+                if attempt < 3 {
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                }
+                Ok::<_, String>(attempt)
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_on_timeout_gives_up() {
+        let calls = std::cell::Cell::new(0);
+        let result = retry_on_timeout(Duration::from_millis(10), 2, "test", || {
+            calls.set(calls.get() + 1);
+            async {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                Ok::<_, String>(())
+            }
+        })
+        .await;
+        assert!(matches!(result, Err(InkError::Timeout(_))));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn classify_transport_errors() {
+        let errors = [
+            InkError::Rpc("batch 请求失败: error sending request for url (http://127.0.0.1:8545/): error trying to connect: tcp connect error: Connection refused (os error 111)".to_string()),
+            InkError::Rpc("batch 请求失败: error trying to connect: dns error: failed to lookup address information".to_string()),
+            InkError::Rpc("batch 请求返回 HTTP 502 Bad Gateway: ".to_string()),
+            InkError::Timeout("发送 batch 超时 30s, 已重试 3 次".to_string()),
+        ];
+        for error in &errors {
+            assert_eq!(classify_send_error(error), SendErrorKind::Transport, "{}", error);
+        }
+    }
+
+    #[test]
+    fn classify_node_errors() {
+        let errors = [
+            InkError::Rpc("发送 batch: nonce too low".to_string()),
+            InkError::Rpc("batch 请求被拒绝: {\"error\":{\"code\":-32600,\"message\":\"invalid request\"}}".to_string()),
+            InkError::Rpc("batch 请求返回 HTTP 413 Payload Too Large: ".to_string()),
+            InkError::Rpc("batch 请求返回 HTTP 429 Too Many Requests: ".to_string()),
+            InkError::Send("insufficient funds for gas * price + value".to_string()),
+        ];
+        for error in &errors {
+            assert_eq!(classify_send_error(error), SendErrorKind::Node, "{}", error);
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_on_transport_retries_with_backoff() {
+        let calls = std::cell::Cell::new(0);
+        let started = Instant::now();
+        let result = retry_on_transport(3, Duration::from_millis(10), "test", || {
+            calls.set(calls.get() + 1);
+            let attempt = calls.get();
+            async move {
+                if attempt < 3 {
+                    Err(InkError::Rpc("batch 请求失败: Connection refused".to_string()))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 3);
+        // 10ms + 20ms
+        assert!(started.elapsed() >= Duration::from_millis(30));
+    }
+
+    /// 记录同时在途的最大请求数
+    #[derive(Default)]
+    struct SlowSender {
+        inflight: std::sync::atomic::AtomicUsize,
+        max_inflight: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl TxSender for SlowSender {
+        async fn send_batch(
+            &self,
+            raw_txs: &[String],
+        ) -> Result<Vec<Result<H256, String>>, InkError> {
+            use std::sync::atomic::Ordering;
+            let current = self.inflight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_inflight.fetch_max(current, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.inflight.fetch_sub(1, Ordering::SeqCst);
+            Ok(raw_txs.iter().map(|_| Ok(H256::zero())).collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn limited_sender_caps_inflight() {
+        let inner = Arc::new(SlowSender::default());
+        let semaphore = Arc::new(Semaphore::new(3));
+        // 两个任务各自包一层, 共用同一个信号量
+        let senders: Vec<Arc<LimitedSender>> = (0..2)
+            .map(|_| Arc::new(LimitedSender::new(inner.clone(), semaphore.clone())))
+            .collect();
+        let tasks: Vec<_> = (0..12)
+            .map(|i| {
+                let sender = senders[i % 2].clone();
+                tokio::spawn(async move { sender.send_batch(&["0x00".to_string()]).await })
+            })
+            .collect();
+        for task in tasks {
+            assert!(task.await.unwrap().is_ok());
+        }
+        let max_inflight = inner.max_inflight.load(std::sync::atomic::Ordering::SeqCst);
+        assert!(max_inflight <= 3, "{}", max_inflight);
+        assert!(max_inflight > 1);
+        assert_eq!(semaphore.available_permits(), 3);
+    }
+
+    #[tokio::test]
+    async fn chain_id_cached_per_url() {
+        let cache = ChainIdCache::new();
+        let calls = std::cell::Cell::new(0);
+        let fetch = |chain_id: u64| {
+            calls.set(calls.get() + 1);
+            async move { Ok(U256::from(chain_id)) }
+        };
+        let a = "http://a.example";
+        let b = "http://b.example";
+        assert_eq!(cache.get_or_fetch(a, || fetch(1)).await.unwrap(), U256::from(1));
+        assert_eq!(cache.get_or_fetch(a, || fetch(2)).await.unwrap(), U256::from(1));
+        assert_eq!(calls.get(), 1);
+        assert_eq!(cache.get_or_fetch(b, || fetch(5)).await.unwrap(), U256::from(5));
+        assert_eq!(calls.get(), 2);
+
+        // 查询失败不缓存, 下次重新查
+        let c = "http://c.example";
+        let failed = cache
+            .get_or_fetch(c, || async { Err(InkError::Timeout("get_chainid".to_string())) })
+            .await;
+        assert!(failed.is_err());
+        assert_eq!(cache.get_or_fetch(c, || fetch(10)).await.unwrap(), U256::from(10));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_on_transport_gives_up() {
+        let calls = std::cell::Cell::new(0);
+        let result = retry_on_transport(2, Duration::ZERO, "test", || {
+            calls.set(calls.get() + 1);
+            async { Err::<(), _>(InkError::Timeout("test".to_string())) }
+        })
+        .await;
+        assert!(matches!(result, Err(InkError::Timeout(_))));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_on_transport_skips_node_errors() {
+        let calls = std::cell::Cell::new(0);
+        let result = retry_on_transport(3, Duration::ZERO, "test", || {
+            calls.set(calls.get() + 1);
+            async { Err::<(), _>(InkError::Rpc("发送 batch: nonce too low".to_string())) }
+        })
+        .await;
+        assert!(matches!(result, Err(InkError::Rpc(_))));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn retry_on_timeout_does_not_retry_errors() {
+        let calls = std::cell::Cell::new(0);
+        let result = retry_on_timeout(Duration::from_millis(100), 3, "test", || {
+            calls.set(calls.get() + 1);
+            async { Err::<(), _>("nonce too low".to_string()) }
+        })
+        .await;
+        assert!(matches!(result, Err(InkError::Rpc(_))));
+        assert_eq!(calls.get(), 1);
+    }
+
+    /// 本地 JSON-RPC 节点, batch 为 false 时拒绝数组请求, 单个请求按 method 返回结果,
+    /// 发送 0xbad 这笔交易时返回 HTTP 502
+    fn serve_rpc(batch: bool) -> std::net::SocketAddr {
+        use hyper::service::{make_service_fn, service_fn};
+        use hyper::{Body, Request, Response, Server};
+
+        fn reply(request: &serde_json::Value) -> serde_json::Value {
+            let result = match request["method"].as_str() {
+                Some("eth_sendRawTransaction") => json!(H256::repeat_byte(0x11)),
+                _ => json!("0x1"),
+            };
+            json!({ "jsonrpc": "2.0", "id": request["id"], "result": result })
+        }
+
+        let make_service = make_service_fn(move |_| async move {
+            Ok::<_, std::convert::Infallible>(service_fn(
+                move |request: Request<Body>| async move {
+                    let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                    let request: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                    if request["params"][0] == "0xbad" {
+                        let mut response = Response::new(Body::from("bad gateway"));
+                        *response.status_mut() = hyper::StatusCode::BAD_GATEWAY;
+                        return Ok(response);
+                    }
+                    let response = match request.as_array() {
+                        Some(requests) if batch => {
+                            json!(requests.iter().map(reply).collect::<Vec<_>>())
+                        }
+                        Some(_) => json!({
+                            "jsonrpc": "2.0",
+                            "id": null,
+                            "error": { "code": -32600, "message": "batch requests are not supported" },
+                        }),
+                        None => reply(&request),
+                    };
+                    Ok::<_, std::convert::Infallible>(Response::new(Body::from(
+                        response.to_string(),
+                    )))
+                },
+            ))
+        });
+        let server =
+            Server::bind(&std::net::SocketAddr::from(([127, 0, 0, 1], 0))).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    #[tokio::test]
+    async fn health_check_falls_back_without_batch() {
+        assert!(is_batch_probe_ok(
+            r#"[{"jsonrpc":"2.0","id":1,"result":"0x1"},{"jsonrpc":"2.0","id":0,"result":"0x1"}]"#
+        ));
+        assert!(!is_batch_probe_ok(
+            r#"[{"jsonrpc":"2.0","id":0,"result":"0x1"}]"#
+        ));
+        assert!(!is_batch_probe_ok(
+            r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32600,"message":"batch not supported"}}"#
+        ));
+
+        let raw_txs = vec!["0x00".to_string(), "0x01".to_string()];
+        let sender = |addr| {
+            BatchSender::new(
+                reqwest::Client::new(),
+                Url::parse(&format!("http://{}", addr)).unwrap(),
+            )
+        };
+
+        let supported = sender(serve_rpc(true));
+        assert!(supported.health_check().await.unwrap());
+        let results = supported.send_raw_transactions(&raw_txs).await.unwrap();
+        assert_eq!(results, vec![Ok(H256::repeat_byte(0x11)); 2]);
+
+        let unsupported = sender(serve_rpc(false));
+        assert!(!unsupported.health_check().await.unwrap());
+        assert!(unsupported.send_raw_transactions(&raw_txs).await.is_err());
+        // 降级后逐笔发送, 结果和 batch 相同
+        let unsupported = unsupported.without_batch();
+        assert!(!unsupported.supports_batch());
+        let results = unsupported.send_raw_transactions(&raw_txs).await.unwrap();
+        assert_eq!(results, vec![Ok(H256::repeat_byte(0x11)); 2]);
+        // 逐笔发送时某一笔请求失败只记在这一笔上, 后面的照常发送
+        let mixed = ["0x00", "0xbad", "0x01"].map(String::from);
+        let results = unsupported.send_raw_transactions(&mixed).await.unwrap();
+        assert_eq!(results[0], Ok(H256::repeat_byte(0x11)));
+        assert!(results[1].as_ref().unwrap_err().contains("HTTP 502"));
+        assert_eq!(results[2], Ok(H256::repeat_byte(0x11)));
+        // 全部失败时整体返回错误
+        let result = unsupported.send_raw_transactions(&["0xbad".to_string()]).await;
+        assert!(matches!(result, Err(InkError::Rpc(e)) if e.contains("HTTP 502")));
+
+        // 连接失败不当作不支持 batch
+        let closed = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        assert!(sender(closed).health_check().await.is_err());
+    }
+}
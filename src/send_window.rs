@@ -0,0 +1,118 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{NaiveTime, Timelike};
+
+use crate::InkError;
+
+/// 每天允许发送的时间段(本地时间), 如 22:00-06:00, 结束时间早于开始时间时跨午夜
+///
+/// 包含开始时间不包含结束时间, 开始和结束相同时全天都可以发送
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendWindow {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl SendWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start == self.end || (self.start <= time && time < self.end)
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// 从 now 开始还要等多久窗口才开启, 已经在窗口内时为 0
+    pub fn wait_time(&self, now: NaiveTime) -> Duration {
+        if self.contains(now) {
+            return Duration::ZERO;
+        }
+        let seconds = |time: NaiveTime| {
+            time.num_seconds_from_midnight() as f64 + time.nanosecond() as f64 / 1e9
+        };
+        let wait = (seconds(self.start) - seconds(now)).rem_euclid(86400.0);
+        Duration::from_secs_f64(wait)
+    }
+}
+
+impl FromStr for SendWindow {
+    type Err = InkError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            InkError::Config(format!(
+                "send_window 不合法: {}(格式为 HH:MM-HH:MM, 如 22:00-06:00)",
+                text.trim()
+            ))
+        };
+        let (start, end) = text.split_once('-').ok_or_else(invalid)?;
+        let time =
+            |text: &str| NaiveTime::parse_from_str(text.trim(), "%H:%M").map_err(|_| invalid());
+        Ok(SendWindow {
+            start: time(start)?,
+            end: time(end)?,
+        })
+    }
+}
+
+impl fmt::Display for SendWindow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn parse_window() {
+        let window: SendWindow = " 09:30 - 18:00 ".parse().unwrap();
+        assert_eq!(window.start, at(9, 30));
+        assert_eq!(window.end, at(18, 0));
+        assert_eq!(window.to_string(), "09:30-18:00");
+        assert!("9:30".parse::<SendWindow>().is_err());
+        assert!("25:00-06:00".parse::<SendWindow>().is_err());
+        assert!("22:00-".parse::<SendWindow>().is_err());
+    }
+
+    #[test]
+    fn inside_and_outside_window() {
+        let window: SendWindow = "09:00-18:00".parse().unwrap();
+        assert!(window.contains(at(9, 0)));
+        assert!(window.contains(at(17, 59)));
+        assert!(!window.contains(at(18, 0)));
+        assert!(!window.contains(at(8, 59)));
+        assert_eq!(window.wait_time(at(12, 0)), Duration::ZERO);
+        assert_eq!(window.wait_time(at(8, 30)), Duration::from_secs(30 * 60));
+        // 当天已经过了窗口 等到第二天开启
+        assert_eq!(window.wait_time(at(20, 0)), Duration::from_secs(13 * 3600));
+
+        let always: SendWindow = "00:00-00:00".parse().unwrap();
+        assert!(always.contains(at(13, 0)));
+    }
+
+    #[test]
+    fn window_across_midnight() {
+        let window: SendWindow = "22:00-06:00".parse().unwrap();
+        assert!(window.contains(at(23, 0)));
+        assert!(window.contains(at(0, 0)));
+        assert!(window.contains(at(5, 59)));
+        assert!(!window.contains(at(6, 0)));
+        assert!(!window.contains(at(12, 0)));
+        assert_eq!(window.wait_time(at(3, 0)), Duration::ZERO);
+        assert_eq!(window.wait_time(at(21, 0)), Duration::from_secs(3600));
+        assert_eq!(window.wait_time(at(6, 0)), Duration::from_secs(16 * 3600));
+    }
+}
@@ -0,0 +1,281 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use ethers::prelude::{Address, BlockNumber, Bytes, LocalWallet, Middleware, Signer, H256, U256};
+use ethers::utils::format_ether;
+
+use crate::{
+    build_tx, confirm_batch, retry_on_timeout, sign_tx, to_wei, Config, GasPrice, InkError,
+    MintContext, TxStatus,
+};
+
+/// 普通转账的 gas_limit
+pub const TRANSFER_GAS_LIMIT: u64 = 21000;
+
+/// 一笔原生币转账的结果
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    pub to: Address,
+    pub amount: U256,
+    /// 上链成功时为交易哈希, 失败时为原因
+    pub result: Result<H256, String>,
+}
+
+/// 钱包配置对应的地址, 重复的地址和 source 自己会被跳过
+pub fn wallet_addresses(source: Address, wallets: &[Config]) -> Result<Vec<Address>, InkError> {
+    let mut addresses: Vec<Address> = Vec::with_capacity(wallets.len());
+    for wallet in wallets {
+        let address = wallet
+            .private_key
+            .parse::<LocalWallet>()
+            .map_err(|e| InkError::Wallet(e.to_string()))?
+            .address();
+        if address != source && !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+    Ok(addresses)
+}
+
+/// 资助/源钱包, 优先使用 funding_private_key 没有时使用 private_key
+pub fn source_wallet(ctx: &MintContext, config: &Config) -> Result<LocalWallet, InkError> {
+    let key = config.funding_key().unwrap_or(&config.private_key);
+    Ok(key
+        .parse::<LocalWallet>()
+        .map_err(|e| InkError::Wallet(format!("源钱包私钥不合法: {}", e)))?
+        .with_chain_id(ctx.chain_id.as_u64()))
+}
+
+/// 从 source 依次转账, 每笔等待确认后再发下一笔
+///
+/// nonce 从 pending 开始本地递增, 发送失败的那笔不占用 nonce;
+/// source 余额不够支付全部转账和 gas 时不发送任何交易
+async fn send_transfers(
+    ctx: &MintContext,
+    config: &Config,
+    source: &LocalWallet,
+    gas_price: &GasPrice,
+    transfers: &[(Address, U256)],
+) -> Result<Vec<Transfer>, InkError> {
+    let from = source.address();
+    let timeout = config.rpc_timeout();
+    let retries = config.max_retries;
+
+    let gas_cost = U256::from(TRANSFER_GAS_LIMIT) * gas_price.max_fee_per_gas;
+    let required = transfers
+        .iter()
+        .fold(U256::zero(), |total, (_, amount)| total + *amount + gas_cost);
+    let balance = retry_on_timeout(timeout, retries, "get_balance", || {
+        ctx.provider.get_balance(from, None)
+    })
+    .await?;
+    if balance < required {
+        return Err(InkError::Wallet(format!(
+            "源钱包 {:?} 余额不足: 需要 {} ether 当前 {} ether",
+            from,
+            format_ether(required),
+            format_ether(balance)
+        )));
+    }
+
+    let mut nonce = retry_on_timeout(timeout, retries, "get_transaction_count", || {
+        ctx.provider.get_transaction_count(from, Some(BlockNumber::Pending.into()))
+    })
+    .await?;
+    let mut results = Vec::with_capacity(transfers.len());
+    for (to, amount) in transfers {
+        let gas_price = GasPrice {
+            value: *amount,
+            ..gas_price.clone()
+        };
+        let mut tx = build_tx(from, ctx.chain_id.as_u64(), *to, &gas_price, nonce, Bytes::new());
+        tx.set_gas(TRANSFER_GAS_LIMIT);
+        let raw_tx = sign_tx(source, &tx)?;
+        let response = retry_on_timeout(timeout, retries, "发送转账", || {
+            ctx.sender.send_batch(std::slice::from_ref(&raw_tx))
+        })
+        .await?
+        .pop()
+        .unwrap_or_else(|| Err("节点没有返回响应".to_string()));
+        let result = match response {
+            Ok(tx_hash) => {
+                nonce += U256::one();
+                let statuses = confirm_batch(
+                    &ctx.provider,
+                    &[tx_hash],
+                    Duration::from_secs(config.receipt_timeout_secs),
+                    timeout,
+                    retries,
+                )
+                .await?;
+                match &statuses[0] {
+                    TxStatus::Confirmed(receipt)
+                        if receipt.status.is_some_and(|s| s.as_u64() == 1) =>
+                    {
+                        Ok(tx_hash)
+                    }
+                    TxStatus::Confirmed(_) => Err("交易执行失败".to_string()),
+                    _ => Err(format!(
+                        "{} 秒内没有上链: {:?}",
+                        config.receipt_timeout_secs, tx_hash
+                    )),
+                }
+            }
+            Err(e) => Err(e),
+        };
+        results.push(Transfer {
+            to: *to,
+            amount: *amount,
+            result,
+        });
+    }
+    Ok(results)
+}
+
+/// 用资助钱包给余额低于 min_gas_ether 的钱包补足到 min_gas_ether, 并等待全部转账上链
+///
+/// 没有设置 funding_private_key 时什么也不做, 任何一笔补 gas 失败都返回错误
+pub async fn top_up_wallets(
+    ctx: &MintContext,
+    config: &Config,
+    wallets: &[Config],
+) -> Result<Vec<Transfer>, InkError> {
+    if config.funding_key().is_none() {
+        return Ok(Vec::new());
+    }
+    let source = source_wallet(ctx, config)?;
+    let timeout = config.rpc_timeout();
+    let min_balance = to_wei("min_gas_ether", config.min_gas_ether, "ether")?;
+
+    let mut top_ups = Vec::new();
+    for address in wallet_addresses(source.address(), wallets)? {
+        let balance = retry_on_timeout(timeout, config.max_retries, "get_balance", || {
+            ctx.provider.get_balance(address, None)
+        })
+        .await?;
+        if balance < min_balance {
+            top_ups.push((address, min_balance - balance));
+        }
+    }
+    if top_ups.is_empty() {
+        return Ok(Vec::new());
+    }
+    let transfers = send_transfers(ctx, config, &source, &ctx.gas_price, &top_ups).await?;
+    for transfer in &transfers {
+        if let Err(e) = &transfer.result {
+            return Err(InkError::Send(format!("给 {:?} 补 gas 失败: {}", transfer.to, e)));
+        }
+    }
+    Ok(transfers)
+}
+
+/// 从 source 给每个 target 转 amount, 每笔等待确认, 返回每笔的结果
+pub async fn distribute(
+    ctx: &MintContext,
+    config: &Config,
+    source: &LocalWallet,
+    targets: &[Address],
+    amount: U256,
+) -> Result<Vec<Transfer>, InkError> {
+    let transfers: Vec<(Address, U256)> =
+        targets.iter().map(|address| (*address, amount)).collect();
+    send_transfers(ctx, config, source, &ctx.gas_price, &transfers).await
+}
+
+/// 归集一个钱包的结果
+#[derive(Debug, Clone)]
+pub struct Sweep {
+    pub from: Address,
+    pub balance: U256,
+    /// 余额不足以支付一笔转账的手续费时为 None
+    pub transfer: Option<Transfer>,
+}
+
+/// 扣除手续费后可以转出的金额, 余额不超过手续费时返回 None
+pub fn sweep_amount(balance: U256, gas_cost: U256) -> Option<U256> {
+    if balance > gas_cost {
+        Some(balance - gas_cost)
+    } else {
+        None
+    }
+}
+
+/// 把每个钱包扣除手续费后的全部余额转到 to_address
+///
+/// 手续费按当前 gas 价格和普通转账的 gas_limit 预留
+pub async fn sweep_wallets(
+    ctx: &MintContext,
+    config: &Config,
+    wallets: &[Config],
+) -> Result<Vec<Sweep>, InkError> {
+    let to = config
+        .to_address
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| InkError::Config("sweep 需要设置 to_address".to_string()))?;
+    let to = Address::from_str(to)
+        .map_err(|e| InkError::Config(format!("to_address 不合法: {}: {}", to, e)))?;
+    let timeout = config.rpc_timeout();
+    let retries = config.max_retries;
+
+    let current = retry_on_timeout(timeout, retries, "get_gas_price", || {
+        ctx.provider.get_gas_price()
+    })
+    .await?;
+    let gas_price = GasPrice {
+        eip1559: ctx.gas_price.eip1559,
+        max_fee_per_gas: current,
+        max_priority_fee_per_gas: ctx.gas_price.max_priority_fee_per_gas.min(current),
+        value: U256::zero(),
+    };
+    let gas_cost = U256::from(TRANSFER_GAS_LIMIT) * current;
+
+    let mut sweeps = Vec::new();
+    for wallet in wallets {
+        let wallet = wallet
+            .private_key
+            .parse::<LocalWallet>()
+            .map_err(|e| InkError::Wallet(e.to_string()))?
+            .with_chain_id(ctx.chain_id.as_u64());
+        let from = wallet.address();
+        if from == to || sweeps.iter().any(|sweep: &Sweep| sweep.from == from) {
+            continue;
+        }
+        let balance = retry_on_timeout(timeout, retries, "get_balance", || {
+            ctx.provider.get_balance(from, None)
+        })
+        .await?;
+        let transfer = match sweep_amount(balance, gas_cost) {
+            Some(amount) => send_transfers(ctx, config, &wallet, &gas_price, &[(to, amount)])
+                .await?
+                .pop(),
+            None => None,
+        };
+        sweeps.push(Sweep {
+            from,
+            balance,
+            transfer,
+        });
+    }
+    Ok(sweeps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sweep_amount_reserves_gas() {
+        let gas_cost = U256::from(TRANSFER_GAS_LIMIT) * U256::exp10(9);
+        // 刚好够手续费 没有可以转出的余额
+        assert_eq!(sweep_amount(gas_cost, gas_cost), None);
+        assert_eq!(sweep_amount(gas_cost - 1, gas_cost), None);
+        assert_eq!(sweep_amount(U256::zero(), gas_cost), None);
+        assert_eq!(sweep_amount(gas_cost + 1, gas_cost), Some(U256::one()));
+        assert_eq!(
+            sweep_amount(U256::exp10(18), gas_cost),
+            Some(U256::exp10(18) - gas_cost)
+        );
+    }
+}
@@ -0,0 +1,925 @@
+use std::collections::{BTreeMap, HashSet};
+use std::env;
+use std::str::FromStr;
+
+use ethers::prelude::{Address, LocalWallet, Signer};
+use ethers::utils::hex;
+use log::warn;
+use serde::Deserialize;
+
+use crate::{
+    parse_number, AddressCase, Compress, Config, ErrorAction, InkError, IntervalStrategy, TxValue,
+};
+
+/// 读取要执行的钱包, 并按钱包序号设置 id_stride 的 id 偏移
+pub fn execution_addresses(config: Config) -> Result<Vec<Config>, InkError> {
+    let mut wallets = load_wallets(config)?;
+    assign_id_offsets(&mut wallets);
+    Ok(wallets)
+}
+
+/// 第 k 个钱包的 id 偏移为 k * id_stride
+pub fn assign_id_offsets(wallets: &mut [Config]) {
+    for (index, wallet) in wallets.iter_mut().enumerate() {
+        wallet.id_offset = (index as u64).saturating_mul(wallet.id_stride);
+    }
+}
+
+fn load_wallets(config: Config) -> Result<Vec<Config>, InkError> {
+    if let Some(keystore_dir) = env::var("keystore_dir").ok().filter(|s| !s.is_empty()) {
+        let password = env::var("keystore_password").unwrap_or_default();
+        let wallets = load_keystore_dir(&keystore_dir, &password)?;
+        if wallets.is_empty() {
+            return Err(InkError::Wallet(format!(
+                "keystore 中没有成功解密的钱包: {}",
+                keystore_dir
+            )));
+        }
+        Ok(wallets
+            .iter()
+            .map(|wallet| {
+                let mut config = config.clone();
+                config.private_key = format!("0x{}", hex::encode(wallet.signer().to_bytes()));
+                config
+            })
+            .collect())
+    } else if let Some(wallets_file) = env::var("wallets_file").ok().filter(|s| !s.is_empty()) {
+        load_wallets_files(&wallets_file, &config)
+    } else {
+        let private_keys = env_private_keys(env::vars());
+        if !private_keys.is_empty() {
+            return Ok(private_keys
+                .into_iter()
+                .map(|private_key| Config {
+                    private_key,
+                    ..config.clone()
+                })
+                .collect());
+        }
+        if config.private_key.is_empty() {
+            return Err(InkError::Config(
+                "没有设置 private_key(也没有 wallets_file、keystore_dir 或 PRIVATE_KEY_N)".to_string(),
+            ));
+        }
+        Ok(vec![config])
+    }
+}
+
+/// 按顺序读取逗号分隔的多个 wallets_file 并拼接, 重复的私钥只保留第一次出现的并警告
+pub fn load_wallets_files(paths: &str, config: &Config) -> Result<Vec<Config>, InkError> {
+    let mut seen = HashSet::new();
+    let mut wallets = Vec::new();
+    for path in paths.split(',').map(str::trim).filter(|path| !path.is_empty()) {
+        for wallet in load_wallets_file(path, config)? {
+            let key = wallet.private_key.to_lowercase();
+            if seen.insert(key.trim_start_matches("0x").to_string()) {
+                wallets.push(wallet);
+                continue;
+            }
+            let address = match wallet.private_key.parse::<LocalWallet>() {
+                Ok(signer) => format!("{:?}", signer.address()),
+                Err(_) => String::new(),
+            };
+            warn!("{} 中的钱包 {} 和前面的私钥重复, 已跳过", path, address);
+        }
+    }
+    Ok(wallets)
+}
+
+/// 按扩展名读取单个 wallets_file: .csv、.json 或每行一个钱包的文本
+fn load_wallets_file(path: &str, config: &Config) -> Result<Vec<Config>, InkError> {
+    let wallets = std::fs::read_to_string(path)
+        .map_err(|e| InkError::Config(format!("读取文件失败: {}: {}", path, e)))?;
+    if path.to_lowercase().ends_with(".csv") {
+        return parse_wallets_csv(&wallets, config);
+    }
+    if path.to_lowercase().ends_with(".json") {
+        return parse_wallets_json(&wallets, config);
+    }
+
+    wallets
+        .lines()
+        .enumerate()
+        .map(|(index, line)| parse_wallet_line(line, index + 1, config))
+        .collect()
+}
+
+/// 从环境变量里收集 PRIVATE_KEY_1、PRIVATE_KEY_2... 按编号排序, 忽略空值, 重复的私钥只保留编号最小的
+pub fn env_private_keys<I>(vars: I) -> Vec<String>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let re = regex::Regex::new(r"(?i)^private_key_(\d+)$").unwrap();
+    let mut numbered: Vec<(u64, String)> = vars
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let number = re.captures(&key)?[1].parse().ok()?;
+            let value = value.trim().to_string();
+            (!value.is_empty()).then_some((number, value))
+        })
+        .collect();
+    numbered.sort_by_key(|(number, _)| *number);
+    let mut seen = HashSet::new();
+    numbered
+        .into_iter()
+        .filter(|(_, key)| seen.insert(key.trim_start_matches("0x").to_lowercase()))
+        .map(|(_, key)| key)
+        .collect()
+}
+
+/// 只执行部分钱包: 索引范围和地址白名单同时设置时两者都要满足
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WalletFilter {
+    /// execution_addresses 结果里的索引范围, 从 0 开始, 包含两端
+    pub range: Option<(usize, usize)>,
+    /// 地址白名单, 为空时不按地址过滤
+    pub addresses: Vec<Address>,
+}
+
+impl WalletFilter {
+    pub fn is_empty(&self) -> bool {
+        self.range.is_none() && self.addresses.is_empty()
+    }
+
+    /// 按顺序保留符合条件的钱包, 一个都不剩时返回错误
+    pub fn apply(&self, wallets: Vec<Config>) -> Result<Vec<Config>, InkError> {
+        if self.is_empty() {
+            return Ok(wallets);
+        }
+        let mut kept = Vec::new();
+        for (index, wallet) in wallets.into_iter().enumerate() {
+            if let Some((start, end)) = self.range {
+                if index < start || index > end {
+                    continue;
+                }
+            }
+            if !self.addresses.is_empty() {
+                let address = wallet
+                    .private_key
+                    .parse::<LocalWallet>()
+                    .map_err(|e| InkError::Wallet(format!("第 {} 个钱包私钥不合法: {}", index, e)))?
+                    .address();
+                if !self.addresses.contains(&address) {
+                    continue;
+                }
+            }
+            kept.push(wallet);
+        }
+        if kept.is_empty() {
+            return Err(InkError::Config(
+                "没有符合 --wallet-range/--wallet-filter 的钱包".to_string(),
+            ));
+        }
+        Ok(kept)
+    }
+}
+
+/// 解析钱包索引范围, 如 0-9, 只写一个数时只选这一个钱包
+pub fn parse_wallet_range(text: &str) -> Result<(usize, usize), String> {
+    let number = |text: &str| {
+        text.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("钱包索引不合法: {}", text.trim()))
+    };
+    let (start, end) = match text.split_once('-') {
+        Some((start, end)) => (number(start)?, number(end)?),
+        None => {
+            let index = number(text)?;
+            (index, index)
+        }
+    };
+    if start > end {
+        return Err(format!("钱包索引范围不合法: {}", text.trim()));
+    }
+    Ok((start, end))
+}
+
+/// 私钥派生出的地址是否就是声明的地址, 地址或私钥不合法时返回 false
+pub fn verify_address_matches(declared: &str, key: &str) -> bool {
+    match (Address::from_str(declared.trim()), key.trim().parse::<LocalWallet>()) {
+        (Ok(declared), Ok(wallet)) => wallet.address() == declared,
+        _ => false,
+    }
+}
+
+fn check_address_matches(declared: &str, key: &str, line: usize) -> Result<(), InkError> {
+    if verify_address_matches(declared, key) {
+        return Ok(());
+    }
+    let actual = key
+        .parse::<LocalWallet>()
+        .map(|wallet| format!("{:?}", wallet.address()))
+        .unwrap_or_else(|_| "私钥不合法".to_string());
+    Err(InkError::Wallet(format!(
+        "wallets_file 第 {} 行地址与私钥不匹配: 声明 {} 实际 {}",
+        line, declared, actual
+    )))
+}
+
+/// 解析 wallets_file 的第 line 行, 格式为 `地址----私钥` 或 `私钥`
+///
+/// 行尾可以再加一列数字覆盖该钱包的 max_retries, 例如 `地址----私钥----5`, 没有这一列时沿用全局配置;
+/// 第一列是合法地址时 私钥派生的地址必须与它一致, 不是地址的第一列(如备注)忽略
+pub fn parse_wallet_line(line: &str, number: usize, config: &Config) -> Result<Config, InkError> {
+    let mut parts: Vec<&str> = line.split("----").map(str::trim).collect();
+    let mut config = config.clone();
+    if parts.len() > 1 {
+        if let Ok(max_retries) = parts[parts.len() - 1].parse::<u32>() {
+            config.max_retries = max_retries;
+            parts.pop();
+        }
+    }
+    let private_key = parts[parts.len() - 1];
+    // 第一列不是地址时(如备注)不校验
+    if parts.len() > 1 && parts[0].parse::<Address>().is_ok() {
+        check_address_matches(parts[0], private_key, number)?;
+    }
+    config.private_key = private_key.to_string();
+    Ok(config)
+}
+
+/// 解析带表头的 CSV 钱包文件, 每行一个钱包
+///
+/// 必须有 private_key 列, 可选列 rpc_url、count、max_fee(或 max_fee_per_gas)、
+/// max_priority_fee、to_address、max_retries, 缺列或单元格为空时沿用全局配置, 其它列忽略;
+/// 有 address 列时校验私钥与地址是否匹配
+pub fn parse_wallets_csv(text: &str, config: &Config) -> Result<Vec<Config>, InkError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| InkError::Config(format!("CSV 表头读取失败: {}", e)))?
+        .iter()
+        .map(str::to_lowercase)
+        .collect();
+    if !headers.iter().any(|header| header == "private_key") {
+        return Err(InkError::Config("CSV 缺少 private_key 列".to_string()));
+    }
+
+    let mut wallets = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        // 表头是第 1 行
+        let line = index + 2;
+        let record =
+            record.map_err(|e| InkError::Config(format!("CSV 第 {} 行读取失败: {}", line, e)))?;
+        let invalid = |column: &str, e: String| {
+            InkError::Config(format!("CSV 第 {} 行 {} 不合法: {}", line, column, e))
+        };
+        let mut wallet = config.clone();
+        let mut has_private_key = false;
+        let mut declared_address = None;
+        for (header, value) in headers.iter().zip(record.iter()) {
+            if value.is_empty() {
+                continue;
+            }
+            match header.as_str() {
+                "private_key" => {
+                    wallet.private_key = value.to_string();
+                    has_private_key = true;
+                }
+                "address" => declared_address = Some(value),
+                "rpc_url" => wallet.rpc_url = value.to_string(),
+                "to_address" => wallet.to_address = Some(value.to_string()),
+                "count" => {
+                    wallet.count = if value.eq_ignore_ascii_case("auto") {
+                        0
+                    } else {
+                        value
+                            .parse::<u64>()
+                            .map_err(|e| invalid(header, e.to_string()))?
+                    }
+                }
+                "max_fee" | "max_fee_per_gas" => {
+                    wallet.max_fee_per_gas =
+                        parse_number(value, "gwei").map_err(|e| invalid(header, e))?
+                }
+                "max_priority_fee" | "max_priority_fee_per_gas" => {
+                    wallet.max_priority_fee_per_gas =
+                        Some(parse_number(value, "gwei").map_err(|e| invalid(header, e))?)
+                }
+                "max_retries" => {
+                    wallet.max_retries = value
+                        .parse::<u32>()
+                        .map_err(|e| invalid(header, e.to_string()))?
+                }
+                "value" => {
+                    let amount = value.parse::<TxValue>().map_err(|e| invalid(header, e))?;
+                    if matches!(amount, TxValue::Fixed(amount) if amount.is_nan() || amount < 0.0) {
+                        return Err(invalid(header, format!("不能小于 0: {}", value)));
+                    }
+                    wallet.value = amount;
+                }
+                _ => {}
+            }
+        }
+        if !has_private_key {
+            return Err(InkError::Config(format!("CSV 第 {} 行缺少 private_key", line)));
+        }
+        if let Some(declared) = declared_address {
+            check_address_matches(declared, &wallet.private_key, line)?;
+        }
+        wallets.push(wallet);
+    }
+    Ok(wallets)
+}
+
+/// 定义 WalletOverride 的字段和合并方法, 字段名和类型与 Config 一一对应
+macro_rules! wallet_override {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        /// JSON 钱包文件中的一项, 每个字段都可以覆盖全局 Config 中的同名字段
+        #[derive(Deserialize, Debug, Clone, Default)]
+        #[serde(default, deny_unknown_fields)]
+        pub struct WalletOverride {
+            $(pub $field: Option<$ty>,)*
+        }
+
+        impl WalletOverride {
+            /// 以 config 为基础, 用设置了的字段覆盖后返回新的配置
+            pub fn apply_to(&self, config: &Config) -> Config {
+                let mut config = config.clone();
+                $(
+                    if let Some(value) = &self.$field {
+                        config.$field = value.clone();
+                    }
+                )*
+                config
+            }
+        }
+    };
+}
+
+wallet_override! {
+    prefix: String,
+    rpc_url: String,
+    private_key: String,
+    to_address: Option<String>,
+    max_fee_per_gas: f64,
+    max_priority_fee_per_gas: Option<f64>,
+    priority_equals_max: bool,
+    gas_limit: u64,
+    gas_limit_buffer_percent: f64,
+    gas_limit_min: Option<u64>,
+    gas_limit_max: Option<u64>,
+    count: u64,
+    data: String,
+    value: TxValue,
+    batch_size: u64,
+    interval: IntervalStrategy,
+    address_case: AddressCase,
+    placeholders: BTreeMap<String, String>,
+    strict_utf8: bool,
+    compress: Compress,
+    encoding: Option<String>,
+    tx_hashes_file: Option<String>,
+    reserve_ether: f64,
+    estimate_gas: bool,
+    estimate_sample_interval: u64,
+    concurrency: usize,
+    rpc_timeout_secs: u64,
+    max_retries: u32,
+    network_retries: u32,
+    network_backoff_secs: f64,
+    error_policy: BTreeMap<String, ErrorAction>,
+    rpc_headers: BTreeMap<String, String>,
+    adaptive_batch: bool,
+    wait_receipt: bool,
+    receipt_timeout_secs: u64,
+    verify_in_mempool: bool,
+    ws_url: Option<String>,
+    send_on_new_block: bool,
+    max_gas_gate_gwei: Option<f64>,
+    gate_poll_secs: u64,
+    watch_mode: bool,
+    watch_batch_size: u64,
+    watch_timeout_secs: u64,
+    start_block: Option<u64>,
+    private_rpc_url: Option<String>,
+    bundle_mode: bool,
+    bundle_method: String,
+    bundle_retries: u32,
+    nonce_step: u64,
+    wallet_interval: f64,
+    wallet_start_jitter_secs: Option<String>,
+    send_window: Option<String>,
+    funding_private_key: Option<String>,
+    min_gas_ether: f64,
+    approve_token: Option<String>,
+    approve_spender: Option<String>,
+    approve_amount: Option<String>,
+    fee_contract: Option<String>,
+    fee_value: f64,
+    fee_calldata: Option<String>,
+    base_fee_clamp_mul: Option<f64>,
+    expected_selector: Option<String>,
+}
+
+/// 解析 JSON 数组格式的钱包文件, 每项是一个 WalletOverride, 没有覆盖的字段继承全局配置
+pub fn parse_wallets_json(text: &str, config: &Config) -> Result<Vec<Config>, InkError> {
+    let overrides: Vec<WalletOverride> = serde_json::from_str(text)
+        .map_err(|e| InkError::Config(format!("JSON 钱包文件解析失败: {}", e)))?;
+    overrides
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            if item.private_key.is_none() {
+                return Err(InkError::Config(format!(
+                    "JSON 钱包文件第 {} 项缺少 private_key",
+                    index + 1
+                )));
+            }
+            let mut wallet = item.apply_to(config);
+            // data 被覆盖时 轮流使用的 data 列表只保留这一条
+            if item.data.is_some() {
+                wallet.data_list = vec![wallet.data.clone()];
+            }
+            Ok(wallet)
+        })
+        .collect()
+}
+
+/// 用同一个口令解密 keystore, dir 可以是单个 keystore 文件 也可以是目录(遍历其中所有 JSON 文件)
+///
+/// 解密失败的文件不会中断, 全部处理完后统一打印 warn
+pub fn load_keystore_dir(dir: &str, password: &str) -> Result<Vec<LocalWallet>, InkError> {
+    let path = std::path::Path::new(dir);
+    let mut files = Vec::new();
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            let file = entry?.path();
+            let is_json = file
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+            // geth 生成的 keystore 文件名形如 UTC--2023-...--address, 没有扩展名
+            let is_geth = file
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("UTC--"));
+            if file.is_file() && (is_json || is_geth) {
+                files.push(file);
+            }
+        }
+        files.sort();
+    } else {
+        files.push(path.to_path_buf());
+    }
+
+    let mut wallets = Vec::new();
+    let mut failed = Vec::new();
+    for file in files {
+        match LocalWallet::decrypt_keystore(&file, password) {
+            Ok(wallet) => wallets.push(wallet),
+            Err(e) => failed.push(format!("{}: {}", file.display(), e)),
+        }
+    }
+    if !failed.is_empty() {
+        warn!("{} 个 keystore 解密失败:", failed.len());
+        for e in &failed {
+            warn!("  - {}", e);
+        }
+    }
+    Ok(wallets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::prelude::U256;
+
+    fn valid_config() -> Config {
+        Config::builder()
+            .rpc_url("http://127.0.0.1:8545")
+            .private_key("0x01")
+            .data(r#"{"id":"[1-10]"}"#)
+            .count(1)
+            .max_fee_per_gas(10.0)
+            .max_priority_fee_per_gas(1.0)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn wallet_line_overrides_max_retries() {
+        let mut config = valid_config();
+        config.max_retries = 3;
+        let key = "0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+        let address = "0xFCAD0B19BB29D4674531D6F115237E16AFCE377C";
+
+        let plain = parse_wallet_line(key, 1, &config).unwrap();
+        assert_eq!(plain.private_key, key);
+        assert_eq!(plain.max_retries, 3);
+
+        let with_address = parse_wallet_line(&format!("{}----{}", address, key), 1, &config).unwrap();
+        assert_eq!(with_address.private_key, key);
+        assert_eq!(with_address.max_retries, 3);
+
+        let overridden =
+            parse_wallet_line(&format!("{}----{}----5", address, key), 1, &config).unwrap();
+        assert_eq!(overridden.private_key, key);
+        assert_eq!(overridden.max_retries, 5);
+
+        let without_address = parse_wallet_line(&format!("{} ---- 0", key), 1, &config).unwrap();
+        assert_eq!(without_address.private_key, key);
+        assert_eq!(without_address.max_retries, 0);
+
+        // 第一列不是地址时当作备注, 不校验
+        let noted = parse_wallet_line(&format!("note----{}", key), 1, &config).unwrap();
+        assert_eq!(noted.private_key, key);
+        assert_eq!(noted.max_retries, 3);
+    }
+
+    #[test]
+    fn address_matches_private_key() {
+        let key = "0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+        assert!(verify_address_matches("0xFCAD0B19BB29D4674531D6F115237E16AFCE377C", key));
+        // 不区分大小写
+        assert!(verify_address_matches("0xfcad0b19bb29d4674531d6f115237e16afce377c", key));
+        assert!(verify_address_matches(
+            "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf",
+            "0000000000000000000000000000000000000000000000000000000000000001"
+        ));
+        assert!(!verify_address_matches("0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf", key));
+        assert!(!verify_address_matches("0x1234", key));
+        assert!(!verify_address_matches("0xFCAD0B19BB29D4674531D6F115237E16AFCE377C", "0xzz"));
+    }
+
+    #[test]
+    fn wallet_line_address_mismatch() {
+        let config = valid_config();
+        let line = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf----0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+        match parse_wallet_line(line, 3, &config) {
+            Err(InkError::Wallet(e)) => {
+                assert!(e.contains("第 3 行"));
+                assert!(e.contains("0xfcad0b19bb29d4674531d6f115237e16afce377c"));
+            }
+            other => panic!("{:?}", other),
+        }
+
+        let text = "address,private_key\n0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf,0x02\n";
+        match parse_wallets_csv(text, &config) {
+            Err(InkError::Wallet(e)) => assert!(e.contains("第 2 行")),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn wallets_csv_only_private_key() {
+        let config = valid_config();
+        let wallets = parse_wallets_csv("private_key\n0x02\n0x03\n", &config).unwrap();
+        assert_eq!(wallets.len(), 2);
+        assert_eq!(wallets[0].private_key, "0x02");
+        assert_eq!(wallets[1].private_key, "0x03");
+        assert_eq!(wallets[1].rpc_url, config.rpc_url);
+        assert_eq!(wallets[1].count, config.count);
+        assert_eq!(wallets[1].max_fee_per_gas, config.max_fee_per_gas);
+    }
+
+    #[test]
+    fn wallets_csv_overrides_columns() {
+        let config = valid_config();
+        let text = "\
+name, Private_Key, rpc_url, count, max_fee, to_address, max_retries, 备注
+a1, 0x02, http://127.0.0.1:8546, 5, 1.5, 0x14791697260E4c9A71f18484C9f997B308e59325, 7, 小号1
+a2, 0x03, , auto, 2gwei, , , 小号2
+";
+        let wallets = parse_wallets_csv(text, &config).unwrap();
+        assert_eq!(wallets.len(), 2);
+
+        assert_eq!(wallets[0].private_key, "0x02");
+        assert_eq!(wallets[0].rpc_url, "http://127.0.0.1:8546");
+        assert_eq!(wallets[0].count, 5);
+        assert_eq!(wallets[0].max_fee_per_gas, 1.5);
+        assert_eq!(
+            wallets[0].to_address.as_deref(),
+            Some("0x14791697260E4c9A71f18484C9f997B308e59325")
+        );
+        assert_eq!(wallets[0].max_retries, 7);
+
+        // 空单元格沿用全局配置
+        assert_eq!(wallets[1].rpc_url, config.rpc_url);
+        assert_eq!(wallets[1].count, 0);
+        assert_eq!(wallets[1].max_fee_per_gas, 2.0);
+        assert_eq!(wallets[1].to_address, config.to_address);
+        assert_eq!(wallets[1].max_retries, config.max_retries);
+    }
+
+    #[test]
+    fn wallets_csv_value_column() {
+        let mut config = valid_config();
+        config.value = TxValue::Fixed(0.5);
+        let text = "\
+private_key,value
+0x02,0.01
+0x03,
+0x04,1000gwei
+";
+        let wallets = parse_wallets_csv(text, &config).unwrap();
+        let values: Vec<U256> = wallets
+            .iter()
+            .map(|wallet| wallet.init_gas_price().unwrap().value)
+            .collect();
+        assert_eq!(
+            values,
+            vec![U256::exp10(16), U256::exp10(17) * 5, U256::exp10(12)]
+        );
+        // 没有 value 列的文件全部沿用全局 value
+        let wallets = parse_wallets_csv("private_key\n0x02\n", &config).unwrap();
+        assert_eq!(wallets[0].init_gas_price().unwrap().value, U256::exp10(17) * 5);
+
+        match parse_wallets_csv("private_key,value\n0x02,-1\n", &config) {
+            Err(InkError::Config(e)) => assert!(e.contains("第 2 行 value 不合法"), "{}", e),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn wallets_csv_errors() {
+        let config = valid_config();
+        match parse_wallets_csv("address,count\n0xaaa,1\n", &config) {
+            Err(InkError::Config(e)) => assert!(e.contains("缺少 private_key 列")),
+            other => panic!("{:?}", other),
+        }
+        match parse_wallets_csv("private_key,count\n0x02,1\n,2\n", &config) {
+            Err(InkError::Config(e)) => assert!(e.contains("第 3 行缺少 private_key")),
+            other => panic!("{:?}", other),
+        }
+        match parse_wallets_csv("private_key,count\n0x02,abc\n", &config) {
+            Err(InkError::Config(e)) => assert!(e.contains("第 2 行 count 不合法")),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn wallet_override_apply_to() {
+        let config = valid_config();
+        let item = WalletOverride {
+            private_key: Some("0x02".to_string()),
+            to_address: Some(Some("0x14791697260E4c9A71f18484C9f997B308e59325".to_string())),
+            count: Some(5),
+            max_fee_per_gas: Some(1.5),
+            wait_receipt: Some(true),
+            ..Default::default()
+        };
+        let wallet = item.apply_to(&config);
+        assert_eq!(wallet.private_key, "0x02");
+        assert_eq!(
+            wallet.to_address.as_deref(),
+            Some("0x14791697260E4c9A71f18484C9f997B308e59325")
+        );
+        assert_eq!(wallet.count, 5);
+        assert_eq!(wallet.max_fee_per_gas, 1.5);
+        assert!(wallet.wait_receipt);
+        // 没有覆盖的字段继承全局
+        assert_eq!(wallet.rpc_url, config.rpc_url);
+        assert_eq!(wallet.data, config.data);
+        assert_eq!(wallet.max_priority_fee_per_gas, config.max_priority_fee_per_gas);
+
+        let unchanged = WalletOverride::default().apply_to(&config);
+        assert_eq!(unchanged.private_key, config.private_key);
+        assert_eq!(unchanged.count, config.count);
+    }
+
+    /// 只记录 derive(Deserialize) 的结构体声明的字段名, 不真正解析
+    struct FieldNames(Vec<&'static str>);
+
+    impl<'de> serde::Deserializer<'de> for &mut FieldNames {
+        type Error = serde::de::value::Error;
+
+        fn deserialize_any<V>(self, _: V) -> Result<V::Value, Self::Error>
+        where
+            V: serde::de::Visitor<'de>,
+        {
+            Err(serde::de::Error::custom("只支持结构体"))
+        }
+
+        fn deserialize_struct<V: serde::de::Visitor<'de>>(
+            self,
+            _: &'static str,
+            fields: &'static [&'static str],
+            _: V,
+        ) -> Result<V::Value, Self::Error> {
+            self.0 = fields.to_vec();
+            Err(serde::de::Error::custom("只记录字段名"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes
+            byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct map enum
+            identifier ignored_any
+        }
+    }
+
+    fn struct_fields<'de, T: Deserialize<'de>>() -> Vec<&'static str> {
+        let mut names = FieldNames(Vec::new());
+        let _ = T::deserialize(&mut names);
+        names.0
+    }
+
+    #[test]
+    fn wallet_override_covers_config() {
+        // 只对整个进程生效或者在读取钱包前就已经处理的字段, 不能按钱包覆盖
+        const GLOBAL_ONLY: &[&str] = &[
+            "id_stride",
+            "items_file",
+            "log_level",
+            "log_max_bytes",
+            "log_rotate_daily",
+            "log_keep_files",
+            "metrics_port",
+            "status_port",
+            "multichain",
+            "max_inflight",
+            "max_tps",
+            "quiet_banner",
+        ];
+        let overridable = struct_fields::<WalletOverride>();
+        assert!(overridable.contains(&"private_key"));
+        for field in struct_fields::<Config>() {
+            assert!(
+                overridable.contains(&field) || GLOBAL_ONLY.contains(&field),
+                "Config 新增的字段 {} 需要加到 wallet_override! 或 GLOBAL_ONLY",
+                field
+            );
+        }
+        for field in &overridable {
+            assert!(!GLOBAL_ONLY.contains(field), "{} 不能按钱包覆盖", field);
+        }
+    }
+
+    #[test]
+    fn wallet_filter_by_range_and_address() {
+        let wallets: Vec<Config> = (1..=5)
+            .map(|key| {
+                let mut config = valid_config();
+                config.private_key = format!("0x{:064x}", key);
+                config
+            })
+            .collect();
+        let address = |config: &Config| {
+            config.private_key.parse::<LocalWallet>().unwrap().address()
+        };
+        let keys = |wallets: Vec<Config>| -> Vec<String> {
+            wallets.into_iter().map(|config| config.private_key).collect()
+        };
+        assert_eq!(parse_wallet_range("1-3").unwrap(), (1, 3));
+        assert_eq!(parse_wallet_range(" 2 ").unwrap(), (2, 2));
+        assert!(parse_wallet_range("3-1").is_err());
+        assert!(parse_wallet_range("a-2").is_err());
+
+        assert_eq!(WalletFilter::default().apply(wallets.clone()).unwrap().len(), 5);
+        let filter = WalletFilter {
+            range: Some((1, 3)),
+            ..Default::default()
+        };
+        assert_eq!(
+            keys(filter.apply(wallets.clone()).unwrap()),
+            keys(wallets[1..=3].to_vec())
+        );
+        // 超出钱包数量的部分忽略
+        let filter = WalletFilter {
+            range: Some((3, 100)),
+            ..Default::default()
+        };
+        assert_eq!(filter.apply(wallets.clone()).unwrap().len(), 2);
+
+        let filter = WalletFilter {
+            range: None,
+            addresses: vec![address(&wallets[4]), address(&wallets[0])],
+        };
+        assert_eq!(
+            keys(filter.apply(wallets.clone()).unwrap()),
+            keys(vec![wallets[0].clone(), wallets[4].clone()])
+        );
+        // 范围和白名单同时满足
+        let filter = WalletFilter {
+            range: Some((1, 4)),
+            addresses: vec![address(&wallets[4]), address(&wallets[0])],
+        };
+        assert_eq!(
+            keys(filter.apply(wallets.clone()).unwrap()),
+            keys(vec![wallets[4].clone()])
+        );
+        let filter = WalletFilter {
+            range: None,
+            addresses: vec![Address::from_low_u64_be(1)],
+        };
+        assert!(filter.apply(wallets).is_err());
+    }
+
+    #[test]
+    fn wallets_json_parse() {
+        let mut config = valid_config();
+        config.data_list = vec!["a".to_string(), "b".to_string()];
+        let text = r#"[
+            {"private_key": "0x02"},
+            {"private_key": "0x03", "rpc_url": "http://127.0.0.1:8546", "count": 3,
+             "max_priority_fee_per_gas": 0.5, "data": "data:,hello",
+             "placeholders": {"tick": "abcd"}}
+        ]"#;
+        let wallets = parse_wallets_json(text, &config).unwrap();
+        assert_eq!(wallets.len(), 2);
+        assert_eq!(wallets[0].private_key, "0x02");
+        assert_eq!(wallets[0].rpc_url, config.rpc_url);
+        assert_eq!(wallets[0].data_list, config.data_list);
+
+        assert_eq!(wallets[1].rpc_url, "http://127.0.0.1:8546");
+        assert_eq!(wallets[1].count, 3);
+        assert_eq!(wallets[1].max_priority_fee_per_gas, Some(0.5));
+        assert_eq!(wallets[1].data, "data:,hello");
+        assert_eq!(wallets[1].data_list, vec!["data:,hello".to_string()]);
+        assert_eq!(wallets[1].placeholders["tick"], "abcd");
+
+        match parse_wallets_json(r#"[{"count": 1}]"#, &config) {
+            Err(InkError::Config(e)) => assert!(e.contains("第 1 项缺少 private_key")),
+            other => panic!("{:?}", other),
+        }
+        // 拼错的字段名直接报错 避免悄悄被忽略
+        assert!(parse_wallets_json(r#"[{"private_key": "0x02", "cout": 1}]"#, &config).is_err());
+        assert!(parse_wallets_json(r#"{"private_key": "0x02"}"#, &config).is_err());
+    }
+
+    #[test]
+    fn env_private_keys_sorted_and_deduped() {
+        let vars = [
+            ("PRIVATE_KEY_10", "0x0a"),
+            ("PRIVATE_KEY_2", "0x02"),
+            ("PRIVATE_KEY_1", " 0x01 "),
+            ("PRIVATE_KEY_3", "02"),
+            ("PRIVATE_KEY_4", ""),
+            ("PRIVATE_KEY_X", "0x05"),
+            ("MY_PRIVATE_KEY_6", "0x06"),
+        ];
+        for (key, value) in vars {
+            env::set_var(key, value);
+        }
+        let keys = env_private_keys(env::vars());
+        let wallets = execution_addresses(valid_config());
+        for (key, _) in vars {
+            env::remove_var(key);
+        }
+        assert_eq!(keys, vec!["0x01", "0x02", "0x0a"]);
+        let wallets: Vec<String> = wallets
+            .unwrap()
+            .into_iter()
+            .map(|wallet| wallet.private_key)
+            .collect();
+        assert_eq!(wallets, keys);
+    }
+
+    #[test]
+    fn load_wallets_files_merges_and_dedups() {
+        let dir = env::temp_dir();
+        let text = dir.join(format!("evm_ink_rs_wallets_{}.txt", std::process::id()));
+        let csv = dir.join(format!("evm_ink_rs_wallets_{}.csv", std::process::id()));
+        std::fs::write(&text, "0x01\n0x02\n").unwrap();
+        std::fs::write(&csv, "private_key,count\n0x03,5\n02,9\n0x04,1\n").unwrap();
+        let paths = format!("{}, {},", text.display(), csv.display());
+        let wallets = load_wallets_files(&paths, &valid_config());
+        let missing = format!("{},missing.txt", text.display());
+        let missing = load_wallets_files(&missing, &valid_config());
+        std::fs::remove_file(&text).unwrap();
+        std::fs::remove_file(&csv).unwrap();
+
+        // 按路径顺序拼接, 第二个文件里重复的 02 被跳过
+        let wallets: Vec<(String, u64)> = wallets
+            .unwrap()
+            .into_iter()
+            .map(|wallet| (wallet.private_key, wallet.count))
+            .collect();
+        let count = valid_config().count;
+        assert_eq!(
+            wallets,
+            vec![
+                ("0x01".to_string(), count),
+                ("0x02".to_string(), count),
+                ("0x03".to_string(), 5),
+                ("0x04".to_string(), 1),
+            ]
+        );
+        assert!(missing.unwrap_err().to_string().contains("missing.txt"));
+    }
+
+    #[test]
+    fn load_keystore_dir_decrypts_all() {
+        let dir = env::temp_dir().join(format!("evm_ink_rs_keystore_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut rng = rand::thread_rng();
+        let (first, _) = LocalWallet::new_keystore(&dir, &mut rng, "test-password", Some("a.json"))
+            .unwrap();
+        let (second, _) = LocalWallet::new_keystore(&dir, &mut rng, "test-password", Some("b.json"))
+            .unwrap();
+        // 口令不同的 keystore 解密失败 不影响其它文件
+        LocalWallet::new_keystore(&dir, &mut rng, "other-password", Some("c.json")).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a keystore").unwrap();
+
+        let wallets = load_keystore_dir(dir.to_str().unwrap(), "test-password").unwrap();
+        let addresses: Vec<Address> = wallets.iter().map(|wallet| wallet.address()).collect();
+        assert_eq!(addresses, vec![first.address(), second.address()]);
+
+        let single = load_keystore_dir(dir.join("b.json").to_str().unwrap(), "test-password")
+            .unwrap();
+        assert_eq!(single[0].address(), second.address());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
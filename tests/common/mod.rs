@@ -0,0 +1,40 @@
+//! 集成测试共用的 anvil 配置
+
+use evm_ink_rs::Config;
+
+/// 集成测试统一使用的 mint data, id 从 1 开始递增
+pub const DATA: &str = r#"{"p":"erc-20","op":"mint","tick":"test","id":"[1-:asc]","amt":"1000"}"#;
+
+/// anvil_config 的可选项, 没有设置的使用 Config 的默认值
+#[derive(Default)]
+pub struct AnvilOptions<'a> {
+    pub priority_fee: Option<&'a str>,
+    pub batch_size: Option<u64>,
+    /// 其它配置项, 如 ("multichain", "true")
+    pub extra: Vec<(&'a str, &'a str)>,
+}
+
+/// 用 rpc_url 和私钥打 count 笔的配置, max_fee_per_gas 为 10 gwei, 每批之间不等待
+pub fn anvil_config(rpc_url: &str, private_key: &str, count: u64, options: AnvilOptions) -> Config {
+    let mut vars = vec![
+        ("rpc_url".to_string(), rpc_url.to_string()),
+        ("private_key".to_string(), private_key.to_string()),
+        ("max_fee_per_gas".to_string(), "10".to_string()),
+        ("count".to_string(), count.to_string()),
+        ("interval".to_string(), "0".to_string()),
+        ("data".to_string(), DATA.to_string()),
+    ];
+    if let Some(priority_fee) = options.priority_fee {
+        vars.push((
+            "max_priority_fee_per_gas".to_string(),
+            priority_fee.to_string(),
+        ));
+    }
+    if let Some(batch_size) = options.batch_size {
+        vars.push(("batch_size".to_string(), batch_size.to_string()));
+    }
+    for (key, value) in options.extra {
+        vars.push((key.to_string(), value.to_string()));
+    }
+    envy::from_iter::<_, Config>(vars).unwrap()
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use ethers::prelude::{Bytes, H256, U256};
+
+use evm_ink_rs::{serve_metrics, Metrics, MintEvent};
+
+#[tokio::test]
+async fn scrape_metrics_endpoint() {
+    let metrics = Arc::new(Metrics::new());
+    let addr = serve_metrics(0, metrics.clone()).unwrap();
+
+    // 模拟多个钱包并发上报
+    let tasks: Vec<_> = (0..4)
+        .map(|_| {
+            let metrics = metrics.clone();
+            tokio::spawn(async move {
+                for index in 0..25 {
+                    metrics.record(&MintEvent::TxSent {
+                        index,
+                        nonce: U256::from(index),
+                        tx_hash: H256::zero(),
+                    });
+                }
+                metrics.record(&MintEvent::TxFailed {
+                    index: 26,
+                    nonce: U256::from(26),
+                    id: None,
+                    data: Bytes::new(),
+                    raw_tx: String::new(),
+                    error: "nonce too low".to_string(),
+                });
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let url = format!("http://127.0.0.1:{}/metrics", addr.port());
+    let response = reqwest::get(&url).await.unwrap();
+    assert!(response.status().is_success());
+    let text = response.text().await.unwrap();
+    assert!(text.contains("evm_ink_sent_total 104\n"), "{}", text);
+    assert!(text.contains("evm_ink_success_total 100\n"), "{}", text);
+    assert!(text.contains("evm_ink_failed_total 4\n"), "{}", text);
+
+    let url = format!("http://127.0.0.1:{}/status", addr.port());
+    let text = reqwest::get(&url).await.unwrap().text().await.unwrap();
+    let status: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(status["sent"], 104);
+    assert_eq!(status["success"], 100);
+    assert_eq!(status["current_wallets"], serde_json::json!([]));
+
+    let not_found = reqwest::get(format!("http://127.0.0.1:{}/other", addr.port()))
+        .await
+        .unwrap();
+    assert_eq!(not_found.status(), reqwest::StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,129 @@
+//! 对着本地 anvil 真实跑 mint 的回归测试, 运行: cargo test -- --ignored
+
+use ethers::prelude::{Http, LocalWallet, Middleware, Provider, Signer, H256, U256, U64};
+use ethers::utils::{hex, Anvil, AnvilInstance};
+
+use evm_ink_rs::{mint, prepare_config, Config, MintContext, MintEvent};
+
+use common::{anvil_config, AnvilOptions, DATA};
+
+mod common;
+
+/// 每批 2 笔的 mint 配置
+fn mint_config(anvil: &AnvilInstance, count: u64, priority_fee: Option<&str>) -> Config {
+    let private_key = hex::encode(anvil.keys()[0].to_bytes());
+    let options = AnvilOptions {
+        priority_fee,
+        batch_size: Some(2),
+        ..Default::default()
+    };
+    anvil_config(&anvil.endpoint(), &private_key, count, options)
+}
+
+/// 直接调用 mint 发送 count 笔, 返回发送前的 nonce、交易哈希和收到的事件
+async fn mint_on_anvil(
+    anvil: &AnvilInstance,
+    mut config: Config,
+) -> (U256, Vec<H256>, Vec<MintEvent>) {
+    let ctx = MintContext::connect(&config).await.unwrap();
+    let wallet = config
+        .private_key
+        .parse::<LocalWallet>()
+        .unwrap()
+        .with_chain_id(anvil.chain_id());
+    let nonce = ctx
+        .provider
+        .get_transaction_count(wallet.address(), None)
+        .await
+        .unwrap();
+    let gas_price = config.init_gas_price().unwrap();
+    let (recipients, _) = prepare_config(&mut config, wallet.address()).unwrap();
+    let mut events = Vec::new();
+    let summary = mint(
+        &ctx,
+        &wallet,
+        config,
+        &gas_price,
+        nonce,
+        &recipients,
+        |event| events.push(event),
+    )
+    .await
+    .unwrap();
+    assert_eq!(summary.failed, 0, "{:?}", summary.errors);
+    (nonce, summary.tx_hashes, events)
+}
+
+/// 检查 nonce 增长、每笔都已落块并且 data 按 id 递增, 返回每笔的交易类型
+async fn assert_minted(anvil: &AnvilInstance, nonce: U256, tx_hashes: &[H256]) -> Vec<Option<U64>> {
+    let provider = Provider::<Http>::try_from(anvil.endpoint()).unwrap();
+    let address = anvil.addresses()[0];
+    let current = provider.get_transaction_count(address, None).await.unwrap();
+    assert_eq!(current, nonce + tx_hashes.len());
+
+    let mut types = Vec::new();
+    for (i, tx_hash) in tx_hashes.iter().enumerate() {
+        let receipt = provider
+            .get_transaction_receipt(*tx_hash)
+            .await
+            .unwrap()
+            .expect("交易没有落块");
+        assert_eq!(receipt.status, Some(U64::one()));
+        assert!(receipt.block_number.is_some());
+
+        let tx = provider.get_transaction(*tx_hash).await.unwrap().unwrap();
+        assert_eq!(tx.from, address);
+        assert_eq!(tx.to, Some(address));
+        assert_eq!(tx.nonce, nonce + i);
+        let expected = format!("data:,{}", DATA.replace("[1-:asc]", &(i + 1).to_string()));
+        assert_eq!(tx.input.to_vec(), expected.into_bytes());
+        types.push(tx.transaction_type);
+    }
+    types
+}
+
+#[tokio::test]
+#[ignore = "需要本地安装 anvil"]
+async fn mint_legacy_transactions() {
+    let anvil = Anvil::new().spawn();
+    let config = mint_config(&anvil, 5, None);
+
+    let (nonce, tx_hashes, events) = mint_on_anvil(&anvil, config).await;
+    assert_eq!(tx_hashes.len(), 5);
+    // batch_size 为 2, 5 笔分 3 批
+    let batches = events
+        .iter()
+        .filter(|event| matches!(event, MintEvent::BatchStart { .. }))
+        .count();
+    assert_eq!(batches, 3);
+    for tx_type in assert_minted(&anvil, nonce, &tx_hashes).await {
+        assert_eq!(tx_type.unwrap_or_default(), U64::zero());
+    }
+}
+
+#[tokio::test]
+#[ignore = "需要本地安装 anvil"]
+async fn mint_eip1559_transactions() {
+    let anvil = Anvil::new().spawn();
+    let config = mint_config(&anvil, 3, Some("1"));
+
+    let (nonce, tx_hashes, _) = mint_on_anvil(&anvil, config).await;
+    assert_eq!(tx_hashes.len(), 3);
+    for tx_type in assert_minted(&anvil, nonce, &tx_hashes).await {
+        assert_eq!(tx_type, Some(U64::from(2)));
+    }
+}
+
+#[tokio::test]
+#[ignore = "需要本地安装 anvil"]
+async fn mint_continues_from_current_nonce() {
+    let anvil = Anvil::new().spawn();
+    let (nonce, tx_hashes, _) = mint_on_anvil(&anvil, mint_config(&anvil, 2, None)).await;
+    assert_eq!(nonce, U256::zero());
+    assert_minted(&anvil, nonce, &tx_hashes).await;
+
+    // 第二次从链上最新的 nonce 接着发, id 重新从 1 开始
+    let (nonce, tx_hashes, _) = mint_on_anvil(&anvil, mint_config(&anvil, 2, Some("1"))).await;
+    assert_eq!(nonce, U256::from(2));
+    assert_minted(&anvil, nonce, &tx_hashes).await;
+}
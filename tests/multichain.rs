@@ -0,0 +1,64 @@
+//! 多链模式的回归测试: 两个不同 chain_id 的 anvil 各打一份同样的 data, 运行: cargo test -- --ignored
+
+use std::sync::Arc;
+
+use ethers::prelude::{Http, Middleware, Provider};
+use ethers::utils::{hex, Anvil};
+
+use evm_ink_rs::{connect_chains, run_all, MintContext};
+
+use common::{anvil_config, AnvilOptions};
+
+mod common;
+
+#[tokio::test]
+#[ignore = "需要本地安装 anvil"]
+async fn mint_same_data_on_each_chain() {
+    let first = Anvil::new().chain_id(31337u64).spawn();
+    // 两条链使用同一个助记词, 第一个账户是同一个钱包
+    let second = Anvil::new().chain_id(31338u64).spawn();
+    let rpc_url = format!("{},{}", first.endpoint(), second.endpoint());
+    let private_key = hex::encode(first.keys()[0].to_bytes());
+    let options = AnvilOptions {
+        extra: vec![("multichain", "true")],
+        ..Default::default()
+    };
+    let config = anvil_config(&rpc_url, &private_key, 3, options);
+    assert!(config.validate().is_ok());
+
+    // 和命令行程序一样: 先连接第一条链, 再为其它链建立共用限制的连接, 每条链各自跑 run_all
+    let chains = config.chain_configs();
+    let ctx = Arc::new(MintContext::connect(&chains[0]).await.unwrap());
+    let ctxs = connect_chains(ctx, &chains).await.unwrap();
+    assert_eq!(ctxs.len(), 2);
+    let runs = chains
+        .into_iter()
+        .zip(ctxs)
+        .map(|(chain, ctx)| run_all(ctx, vec![chain], 1, None, |_, _| {}));
+    let results: Vec<_> = futures::future::join_all(runs)
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+    assert_eq!(results.len(), 2);
+    for (anvil, result) in [&first, &second].into_iter().zip(results) {
+        let summary = result.unwrap();
+        assert_eq!(summary.success, 3, "{:?}", summary.errors);
+
+        let provider = Provider::<Http>::try_from(anvil.endpoint()).unwrap();
+        assert_eq!(
+            provider.get_chainid().await.unwrap(),
+            anvil.chain_id().into()
+        );
+        // 每条链各自从 nonce 0 开始
+        let nonce = provider
+            .get_transaction_count(anvil.addresses()[0], None)
+            .await
+            .unwrap();
+        assert_eq!(nonce, 3.into());
+        for tx_hash in &summary.tx_hashes {
+            let tx = provider.get_transaction(*tx_hash).await.unwrap().unwrap();
+            assert_eq!(tx.chain_id, Some(anvil.chain_id().into()));
+        }
+    }
+}
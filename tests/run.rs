@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use ethers::prelude::{Http, LocalWallet, Middleware, Provider, Signer, U256};
+use ethers::utils::{hex, secret_key_to_address, Anvil};
+
+use evm_ink_rs::{distribute, run, run_all, top_up_wallets, Config, MintContext};
+
+use common::{anvil_config, AnvilOptions};
+
+mod common;
+
+#[tokio::test]
+#[ignore = "需要本地安装 anvil"]
+async fn run_against_anvil() {
+    let anvil = Anvil::new().spawn();
+    let private_key = hex::encode(anvil.keys()[0].to_bytes());
+    let config = anvil_config(&anvil.endpoint(), &private_key, 3, AnvilOptions::default());
+
+    let summary = run(config).await.unwrap();
+    assert_eq!(summary.sent, 3);
+    assert_eq!(summary.success, 3);
+    assert_eq!(summary.failed, 0);
+    assert_eq!(summary.tx_hashes.len(), 3);
+}
+
+#[tokio::test]
+#[ignore = "需要本地安装 anvil"]
+async fn run_all_wallets_concurrently() {
+    let anvil = Anvil::new().spawn();
+    let configs: Vec<Config> = anvil.keys()[..3]
+        .iter()
+        .map(|key| {
+            anvil_config(
+                &anvil.endpoint(),
+                &hex::encode(key.to_bytes()),
+                2,
+                AnvilOptions::default(),
+            )
+        })
+        .collect();
+    let ctx = Arc::new(MintContext::connect(&configs[0]).await.unwrap());
+
+    let results = run_all(ctx, configs, 3, None, |_, _| {}).await;
+    assert_eq!(results.len(), 3);
+    for (result, key) in results.iter().zip(anvil.keys()) {
+        let summary = result.as_ref().unwrap();
+        assert_eq!(summary.address, secret_key_to_address(&key.clone().into()));
+        assert_eq!(summary.success, 2);
+    }
+}
+
+#[tokio::test]
+#[ignore = "需要本地安装 anvil"]
+async fn send_through_private_rpc() {
+    // 两个 anvil 使用相同的助记词和链ID, public 负责查询 private 负责接收交易
+    let public = Anvil::new().spawn();
+    let private = Anvil::new().spawn();
+    let private_key = hex::encode(public.keys()[0].to_bytes());
+    let mut config = anvil_config(&public.endpoint(), &private_key, 2, AnvilOptions::default());
+    config.private_rpc_url = Some(private.endpoint());
+
+    let summary = run(config).await.unwrap();
+    assert_eq!(summary.success, 2);
+
+    let address = secret_key_to_address(&public.keys()[0].clone().into());
+    let public_provider = Provider::<Http>::try_from(public.endpoint()).unwrap();
+    let private_provider = Provider::<Http>::try_from(private.endpoint()).unwrap();
+    // 交易只出现在私有 RPC 上
+    assert_eq!(
+        public_provider.get_transaction_count(address, None).await.unwrap(),
+        U256::zero()
+    );
+    assert_eq!(
+        private_provider.get_transaction_count(address, None).await.unwrap(),
+        U256::from(2)
+    );
+    for tx_hash in &summary.tx_hashes {
+        assert!(public_provider.get_transaction(*tx_hash).await.unwrap().is_none());
+        assert!(private_provider.get_transaction(*tx_hash).await.unwrap().is_some());
+    }
+}
+
+#[tokio::test]
+#[ignore = "需要本地安装 anvil"]
+async fn top_up_empty_wallets_before_mint() {
+    let anvil = Anvil::new().spawn();
+    let funding_key = hex::encode(anvil.keys()[0].to_bytes());
+    let empty = LocalWallet::new(&mut rand::thread_rng());
+    let empty_key = hex::encode(empty.signer().to_bytes());
+
+    let mut config = anvil_config(&anvil.endpoint(), &empty_key, 2, AnvilOptions::default());
+    config.funding_private_key = Some(funding_key.clone());
+    config.min_gas_ether = 0.01;
+    // 资助钱包自己也在列表里 不会给自己转账
+    let wallets = vec![
+        config.clone(),
+        anvil_config(&anvil.endpoint(), &funding_key, 1, AnvilOptions::default()),
+    ];
+    let ctx = MintContext::connect(&config).await.unwrap();
+
+    let top_ups = top_up_wallets(&ctx, &config, &wallets).await.unwrap();
+    assert_eq!(top_ups.len(), 1);
+    assert_eq!(top_ups[0].to, empty.address());
+    assert!(top_ups[0].result.is_ok());
+    let provider = Provider::<Http>::try_from(anvil.endpoint()).unwrap();
+    let balance = provider.get_balance(empty.address(), None).await.unwrap();
+    assert_eq!(balance, U256::exp10(16));
+
+    // 余额已经足够 再次执行不会转账
+    assert!(top_up_wallets(&ctx, &config, &wallets).await.unwrap().is_empty());
+
+    let summary = run(config).await.unwrap();
+    assert_eq!(summary.success, 2);
+}
+
+#[tokio::test]
+#[ignore = "需要本地安装 anvil"]
+async fn distribute_to_all_wallets() {
+    let anvil = Anvil::new().spawn();
+    let source: LocalWallet = anvil.keys()[0].clone().into();
+    let source = source.with_chain_id(anvil.chain_id());
+    let targets: Vec<_> = (0..3)
+        .map(|_| LocalWallet::new(&mut rand::thread_rng()).address())
+        .collect();
+    let config = anvil_config(
+        &anvil.endpoint(),
+        &hex::encode(anvil.keys()[0].to_bytes()),
+        1,
+        AnvilOptions::default(),
+    );
+    let ctx = MintContext::connect(&config).await.unwrap();
+
+    let amount = U256::exp10(15);
+    let transfers = distribute(&ctx, &config, &source, &targets, amount).await.unwrap();
+    assert_eq!(transfers.len(), 3);
+    let provider = Provider::<Http>::try_from(anvil.endpoint()).unwrap();
+    for (transfer, target) in transfers.iter().zip(&targets) {
+        assert_eq!(transfer.to, *target);
+        assert!(transfer.result.is_ok());
+        assert_eq!(provider.get_balance(*target, None).await.unwrap(), amount);
+    }
+}